@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Replace `path` with `content` without ever exposing a partial file
+///
+/// The content is written to a temp file in the same directory, synced and
+/// renamed over the original. The original's permissions and ownership are
+/// kept; symlinks are followed so the link itself survives.
+pub fn write_atomic(path: impl AsRef<Path>, content: &[u8]) -> Result<()> {
+    write_atomic_with_mode(path.as_ref(), content, None)
+}
+
+/// Like [`write_atomic`], but the file is only readable by its owner
+pub(crate) fn write_atomic_private(path: &Path, content: &[u8]) -> Result<()> {
+    write_atomic_with_mode(path, content, Some(0o600))
+}
+
+fn write_atomic_with_mode(path: &Path, content: &[u8], mode: Option<u32>) -> Result<()> {
+    let target = resolve_symlink(path);
+    let dir = match target.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let original = fs::metadata(&target).ok();
+    let temp = temp_path(&dir, &target);
+
+    let result = (|| -> Result<()> {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            // Start private so the content is never readable by others, even
+            // briefly; brand-new files get the usual umask-based mode
+            options.mode(if mode.is_some() || original.is_some() {
+                0o600
+            } else {
+                0o666
+            });
+        }
+
+        let mut file = options
+            .open(&temp)
+            .with_context(|| format!("Failed to create {}", temp.display()))?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        drop(file);
+
+        if let Some(meta) = &original {
+            preserve_owner(&temp, meta);
+        }
+        match (mode, &original) {
+            (Some(mode), _) => set_mode(&temp, mode)?,
+            (None, Some(meta)) => fs::set_permissions(&temp, meta.permissions())?,
+            (None, None) => {}
+        }
+
+        fs::rename(&temp, &target)
+            .with_context(|| format!("Failed to replace {}", target.display()))?;
+        sync_dir(&dir);
+        Ok(())
+    })();
+
+    if result.is_err() {
+        fs::remove_file(&temp).ok();
+    }
+    result
+}
+
+fn resolve_symlink(path: &Path) -> PathBuf {
+    let is_link = fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+    if is_link {
+        fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn temp_path(dir: &Path, target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    dir.join(format!(".{}.tmp-{}-{}", name, std::process::id(), nanos))
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+// Only root can give a file away, so failing here is expected and harmless
+#[cfg(unix)]
+fn preserve_owner(path: &Path, original: &fs::Metadata) {
+    use std::os::unix::fs::MetadataExt;
+    std::os::unix::fs::chown(path, Some(original.uid()), Some(original.gid())).ok();
+}
+
+#[cfg(not(unix))]
+fn preserve_owner(_path: &Path, _original: &fs::Metadata) {}
+
+// Make the rename itself durable
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(dir) {
+        dir.sync_all().ok();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_and_keeps_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "old").unwrap();
+
+        #[cfg(unix)]
+        set_mode(&path, 0o640).unwrap();
+
+        write_atomic(&path, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+
+        // No temp files left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_follows_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real.toml");
+        let link = dir.path().join("link.toml");
+        fs::write(&real, "old").unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        write_atomic(&link, b"new").unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&real).unwrap(), "new");
+    }
+}
@@ -0,0 +1,241 @@
+use super::{write_atomic, write_atomic_private};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const BACKUP_EXTENSION: &str = "bak";
+
+/// Where and how many timestamped config backups to keep
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupPolicy {
+    /// Number of backups kept; older ones are deleted
+    pub keep: usize,
+    /// Directory for backups, defaults to the config's own directory
+    pub dir: Option<PathBuf>,
+}
+
+impl BackupPolicy {
+    pub fn new(keep: usize) -> Self {
+        Self { keep, dir: None }
+    }
+
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    fn backup_dir(&self, config_path: &Path) -> PathBuf {
+        match (&self.dir, config_path.parent()) {
+            (Some(dir), _) => dir.clone(),
+            (None, Some(parent)) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        }
+    }
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self::new(5)
+    }
+}
+
+/// A snapshot of the config file, named `<file>.<YYYYmmdd-HHMMSS.mmm>.bak`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// UTC time the snapshot was taken, e.g. `2024-05-01 12:30:00`
+    pub timestamp: String,
+}
+
+impl Backup {
+    pub fn read(&self) -> Result<String> {
+        std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read backup {}", self.path.display()))
+    }
+}
+
+/// Copy the current config to a new timestamped backup and prune old ones
+///
+/// Nothing is written if the config doesn't exist yet or is identical to
+/// the newest backup.
+pub fn create_backup(config_path: &Path, policy: &BackupPolicy) -> Result<Option<Backup>> {
+    if policy.keep == 0 || !config_path.exists() {
+        return Ok(None);
+    }
+
+    let content = std::fs::read(config_path)?;
+    let existing = list_backups(config_path, policy)?;
+    if let Some(newest) = existing.first() {
+        if std::fs::read(&newest.path).ok().as_deref() == Some(content.as_slice()) {
+            return Ok(None);
+        }
+    }
+
+    let dir = policy.backup_dir(config_path);
+    std::fs::create_dir_all(&dir)?;
+    let stamp = timestamp_now();
+    let path = dir.join(format!(
+        "{}.{}.{}",
+        file_name(config_path),
+        stamp,
+        BACKUP_EXTENSION
+    ));
+    write_atomic_private(&path, &content)?;
+    copy_permissions(config_path, &path);
+
+    prune_backups(config_path, policy)?;
+    Ok(Some(Backup {
+        timestamp: display_timestamp(&stamp),
+        path,
+    }))
+}
+
+/// Backups of `config_path`, newest first
+pub fn list_backups(config_path: &Path, policy: &BackupPolicy) -> Result<Vec<Backup>> {
+    let dir = policy.backup_dir(config_path);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let prefix = format!("{}.", file_name(config_path));
+    let suffix = format!(".{}", BACKUP_EXTENSION);
+    let mut backups: Vec<(String, Backup)> = std::fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let stamp = name.strip_prefix(&prefix)?.strip_suffix(&suffix)?;
+            is_timestamp(stamp).then(|| {
+                (
+                    stamp.to_string(),
+                    Backup {
+                        path: entry.path(),
+                        timestamp: display_timestamp(stamp),
+                    },
+                )
+            })
+        })
+        .collect();
+
+    // Timestamps sort chronologically as strings
+    backups.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(backups.into_iter().map(|(_, backup)| backup).collect())
+}
+
+/// Put a backup back in place of the config, backing up the current one first
+pub fn restore_backup(config_path: &Path, backup: &Backup, policy: &BackupPolicy) -> Result<()> {
+    let content = std::fs::read(&backup.path)
+        .with_context(|| format!("Failed to read backup {}", backup.path.display()))?;
+    create_backup(config_path, policy)?;
+    write_atomic(config_path, &content)
+}
+
+fn prune_backups(config_path: &Path, policy: &BackupPolicy) -> Result<()> {
+    for old in list_backups(config_path, policy)?.iter().skip(policy.keep) {
+        std::fs::remove_file(&old.path)?;
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "config".to_string())
+}
+
+// Backups may hold secrets, so they are as private as the config itself
+fn copy_permissions(from: &Path, to: &Path) {
+    if let Ok(meta) = std::fs::metadata(from) {
+        std::fs::set_permissions(to, meta.permissions()).ok();
+    }
+}
+
+/// Current UTC time as `YYYYmmdd-HHMMSS.mmm`
+fn timestamp_now() -> String {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = elapsed.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}.{:03}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60,
+        elapsed.subsec_millis()
+    )
+}
+
+fn is_timestamp(s: &str) -> bool {
+    s.len() == 19
+        && s.char_indices().all(|(i, c)| match i {
+            8 => c == '-',
+            15 => c == '.',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+fn display_timestamp(stamp: &str) -> String {
+    format!(
+        "{}-{}-{} {}:{}:{}",
+        &stamp[0..4],
+        &stamp[4..6],
+        &stamp[6..8],
+        &stamp[9..11],
+        &stamp[11..13],
+        &stamp[13..15]
+    )
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_backup_rotation_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config.toml");
+        let policy = BackupPolicy::new(2).with_dir(dir.path().join("backups"));
+
+        for i in 0..4 {
+            std::fs::write(&config, format!("value = {}\n", i)).unwrap();
+            create_backup(&config, &policy).unwrap().unwrap();
+            // Unchanged content isn't backed up twice
+            assert!(create_backup(&config, &policy).unwrap().is_none());
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        let backups = list_backups(&config, &policy).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].read().unwrap(), "value = 3\n");
+        assert_eq!(backups[1].read().unwrap(), "value = 2\n");
+
+        restore_backup(&config, &backups[1], &policy).unwrap();
+        assert_eq!(std::fs::read_to_string(&config).unwrap(), "value = 2\n");
+    }
+}
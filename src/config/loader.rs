@@ -1,4 +1,5 @@
-use super::{expand_env_vars, ConfigStore};
+use super::{expand_env_vars, resolve_secrets, ConfigStore};
+use crate::schema::ConfigSchema;
 use anyhow::Result;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -19,6 +20,17 @@ impl ConfigLoader {
         Self::from_toml_string_with_expansion(&content, expand)
     }
 
+    /// Load a config and replace the references in `schema`'s secret fields
+    /// with the secrets themselves
+    pub fn from_toml_file_with_secrets(
+        path: impl AsRef<Path>,
+        schema: &ConfigSchema,
+    ) -> Result<ConfigStore> {
+        let mut store = Self::from_toml_file(path)?;
+        resolve_secrets(&mut store, schema)?;
+        Ok(store)
+    }
+
     pub fn from_toml_string(content: &str) -> Result<ConfigStore> {
         Self::from_toml_string_with_expansion(content, true)
     }
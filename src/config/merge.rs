@@ -0,0 +1,98 @@
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+/// A key changed both in memory and on disk, to different values
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    pub key: String,
+    /// In-memory value, `None` if removed
+    pub ours: Option<Value>,
+    /// On-disk value, `None` if removed
+    pub theirs: Option<Value>,
+}
+
+/// Result of merging an external change into in-memory values
+#[derive(Debug, Clone, Default)]
+pub struct MergeOutcome {
+    /// Keys taken from the file because only it changed them, with their new value
+    pub updated: Vec<(String, Option<Value>)>,
+    /// Keys both sides changed differently
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Three-way merge of flat dot-notation values
+///
+/// `base` is what was last loaded or saved, `ours` the current in-memory
+/// values and `theirs` the file's new content.
+pub fn merge_values(
+    base: &HashMap<String, Value>,
+    ours: &HashMap<String, Value>,
+    theirs: &HashMap<String, Value>,
+) -> MergeOutcome {
+    let keys: BTreeSet<&String> = base
+        .keys()
+        .chain(ours.keys())
+        .chain(theirs.keys())
+        .collect();
+    let mut outcome = MergeOutcome::default();
+
+    for key in keys {
+        let (b, o, t) = (base.get(key), ours.get(key), theirs.get(key));
+        if t == b || o == t {
+            continue;
+        }
+        if o == b {
+            outcome.updated.push((key.clone(), t.cloned()));
+        } else {
+            outcome.conflicts.push(MergeConflict {
+                key: key.clone(),
+                ours: o.cloned(),
+                theirs: t.cloned(),
+            });
+        }
+    }
+
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn values(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_values() {
+        let base = values(&[("a", json!(1)), ("b", json!(1)), ("c", json!(1))]);
+        let ours = values(&[("a", json!(2)), ("b", json!(1)), ("c", json!(5))]);
+        let theirs = values(&[
+            ("a", json!(1)),
+            ("b", json!(3)),
+            ("c", json!(6)),
+            ("d", json!(true)),
+        ]);
+
+        let outcome = merge_values(&base, &ours, &theirs);
+        assert_eq!(
+            outcome.updated,
+            vec![
+                ("b".to_string(), Some(json!(3))),
+                ("d".to_string(), Some(json!(true)))
+            ]
+        );
+        assert_eq!(
+            outcome.conflicts,
+            vec![MergeConflict {
+                key: "c".to_string(),
+                ours: Some(json!(5)),
+                theirs: Some(json!(6)),
+            }]
+        );
+    }
+}
@@ -1,9 +1,21 @@
+mod atomic;
+mod backup;
+mod diff;
 mod env;
 mod loader;
+mod merge;
 mod saver;
+mod secrets;
 mod store;
+mod watch;
 
+pub use atomic::*;
+pub use backup::*;
+pub use diff::*;
 pub use env::*;
 pub use loader::*;
+pub use merge::*;
 pub use saver::*;
+pub use secrets::*;
 pub use store::*;
+pub use watch::*;
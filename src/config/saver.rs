@@ -1,5 +1,5 @@
-use super::ConfigStore;
-use crate::schema::{ConfigSchema, FieldType};
+use super::{write_atomic, write_atomic_private, ConfigStore};
+use crate::schema::{ConfigSchema, FieldType, SchemaValidator, SecretStorage};
 use anyhow::Result;
 use serde_json::Value;
 use std::path::Path;
@@ -13,8 +13,29 @@ impl ConfigSaver {
         path: impl AsRef<Path>,
     ) -> Result<()> {
         let content = Self::generate_toml_with_comments(store, schema)?;
-        std::fs::write(path, content)?;
-        Ok(())
+
+        if Self::has_inline_secrets(schema) {
+            write_atomic_private(path.as_ref(), content.as_bytes())
+        } else {
+            write_atomic(path, content.as_bytes())
+        }
+    }
+
+    /// The TOML `save_toml` would write, without writing it
+    pub fn to_toml_string(store: &ConfigStore, schema: &ConfigSchema) -> Result<String> {
+        Self::generate_toml_with_comments(store, schema)
+    }
+
+    fn has_inline_secrets(schema: &ConfigSchema) -> bool {
+        schema.sections.iter().flat_map(|s| &s.fields).any(|f| {
+            matches!(
+                f.field_type,
+                FieldType::Secret {
+                    storage: SecretStorage::Inline,
+                    ..
+                }
+            )
+        })
     }
 
     fn generate_toml_with_comments(store: &ConfigStore, schema: &ConfigSchema) -> Result<String> {
@@ -30,7 +51,7 @@ impl ConfigSaver {
         output.push_str("# This file is auto-generated but safe to edit manually\n\n");
 
         for section in &schema.sections {
-            output.push_str(&format!("[{}]\n", section.id));
+            output.push_str(&format!("[{}]\n", Self::format_key(&section.id)));
 
             if let Some(desc) = &section.description {
                 output.push_str(&format!("# {}\n", desc));
@@ -46,12 +67,17 @@ impl ConfigSaver {
                     Self::get_default_value(&field.field_type)
                 };
 
-                if let Some(val) = value {
-                    let value_str = Self::format_value(&val);
-                    output.push_str(&format!("{} = {}\n\n", field.id, value_str));
-                } else {
-                    // Write empty string for missing values
-                    output.push_str(&format!("{} = \"\"\n\n", field.id));
+                // TOML has no null, so unset fields are left out
+                match value
+                    .as_ref()
+                    .map(|v| Self::format_field_value(&field.field_type, v))
+                {
+                    Some(Some(value_str)) => output.push_str(&format!(
+                        "{} = {}\n\n",
+                        Self::format_key(&field.id),
+                        value_str
+                    )),
+                    _ => output.push('\n'),
                 }
             }
 
@@ -73,20 +99,128 @@ impl ConfigSaver {
             FieldType::Boolean { default } => Some(Value::Bool(*default)),
             FieldType::Enum { default, .. } => default.as_ref().map(|s| Value::String(s.clone())),
             FieldType::Path { default, .. } => default.as_ref().map(|s| Value::String(s.clone())),
+            FieldType::Color { default, .. } => default.as_ref().map(|s| Value::String(s.clone())),
+            FieldType::Duration { default, .. } | FieldType::ByteSize { default, .. } => {
+                default.as_ref().and_then(|d| {
+                    SchemaValidator::normalize_value(field_type, &Value::String(d.clone())).ok()
+                })
+            }
+            FieldType::Array { default, .. } => default.clone().map(Value::Array),
+            FieldType::Secret { .. } => None,
+        }
+    }
+
+    /// Format a field's value, keeping `float` fields floats even when the
+    /// value happens to be whole
+    fn format_field_value(field_type: &FieldType, value: &Value) -> Option<String> {
+        match (field_type, value) {
+            (FieldType::Float { .. }, Value::Number(n)) if !n.is_f64() => {
+                n.as_f64().map(Self::format_float)
+            }
+            _ => Self::format_value(value),
         }
     }
 
-    fn format_value(value: &Value) -> String {
+    /// TOML representation of a value, or `None` for null
+    fn format_value(value: &Value) -> Option<String> {
         match value {
-            Value::String(s) => format!("\"{}\"", s.replace('"', "\\\"")),
-            Value::Number(n) => n.to_string(),
-            Value::Bool(b) => b.to_string(),
+            Value::String(s) => Some(Self::format_string(s)),
+            Value::Number(n) => Some(match n.as_f64() {
+                Some(f) if n.is_f64() => Self::format_float(f),
+                _ => n.to_string(),
+            }),
+            Value::Bool(b) => Some(b.to_string()),
             Value::Array(arr) => {
-                let items: Vec<String> = arr.iter().map(Self::format_value).collect();
-                format!("[{}]", items.join(", "))
+                let items: Vec<String> = arr.iter().filter_map(Self::format_value).collect();
+                Some(format!("[{}]", items.join(", ")))
+            }
+            Value::Object(map) => {
+                let entries: Vec<String> = map
+                    .iter()
+                    .filter_map(|(k, v)| {
+                        Self::format_value(v).map(|v| format!("{} = {}", Self::format_key(k), v))
+                    })
+                    .collect();
+                Some(if entries.is_empty() {
+                    "{}".to_string()
+                } else {
+                    format!("{{ {} }}", entries.join(", "))
+                })
+            }
+            Value::Null => None,
+        }
+    }
+
+    /// Floats always keep a fractional part or exponent so they reload as floats
+    fn format_float(f: f64) -> String {
+        if f.is_nan() {
+            "nan".to_string()
+        } else if f.is_infinite() {
+            if f > 0.0 { "inf" } else { "-inf" }.to_string()
+        } else {
+            let s = f.to_string();
+            if s.contains(['.', 'e', 'E']) {
+                s
+            } else {
+                format!("{}.0", s)
+            }
+        }
+    }
+
+    /// Pick the most readable string form that preserves `s` exactly
+    ///
+    /// Literal strings are used for backslash- or quote-heavy values such as
+    /// Windows paths and regexes, since they need no escaping.
+    fn format_string(s: &str) -> String {
+        let plain = |c: char| !c.is_control() || c == '\t';
+
+        if s.contains('\n') {
+            let literal_ok = s.contains('\\')
+                && !s.contains("'''")
+                && !s.ends_with('\'')
+                && s.chars().all(|c| c == '\n' || plain(c));
+            // The newline right after the opening quotes is not part of the value
+            if literal_ok {
+                format!("'''\n{}'''", s)
+            } else {
+                format!("\"\"\"\n{}\"\"\"", Self::escape_string(s, true))
+            }
+        } else if s.contains(['\\', '"']) && !s.contains('\'') && s.chars().all(plain) {
+            format!("'{}'", s)
+        } else {
+            format!("\"{}\"", Self::escape_string(s, false))
+        }
+    }
+
+    /// Bare key if possible, quoted otherwise
+    fn format_key(key: &str) -> String {
+        let bare = !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if bare {
+            key.to_string()
+        } else {
+            format!("\"{}\"", Self::escape_string(key, false))
+        }
+    }
+
+    /// Escape a string for a TOML basic string, keeping newlines literal in
+    /// multi-line strings
+    fn escape_string(s: &str, multiline: bool) -> String {
+        let mut escaped = String::with_capacity(s.len());
+        for c in s.chars() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                '"' => escaped.push_str("\\\""),
+                '\n' if multiline => escaped.push('\n'),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\t' => escaped.push('\t'),
+                c if c.is_control() => escaped.push_str(&format!("\\u{:04X}", c as u32)),
+                c => escaped.push(c),
             }
-            Value::Object(_) => "{}".to_string(),
-            Value::Null => "null".to_string(),
         }
+        escaped
     }
 }
@@ -0,0 +1,271 @@
+use super::{expand_env_vars, write_atomic_private, ConfigLoader, ConfigStore};
+use crate::schema::{ConfigSchema, FieldType, SecretStorage};
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const REF_PREFIX: &str = "secret:";
+
+/// Reference to a secret kept outside the main config file
+///
+/// Written to the TOML in place of the secret itself:
+/// - `secret:file:<path>#<key>` - dot-notation key in a separate TOML file
+/// - `secret:cmd:<command>` - first line printed by a shell command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretRef {
+    File { path: String, key: String },
+    Command { command: String },
+}
+
+impl SecretRef {
+    pub fn parse(value: &str) -> Option<Self> {
+        let rest = value.strip_prefix(REF_PREFIX)?;
+
+        if let Some(file_ref) = rest.strip_prefix("file:") {
+            let (path, key) = file_ref.rsplit_once('#')?;
+            return Some(SecretRef::File {
+                path: path.to_string(),
+                key: key.to_string(),
+            });
+        }
+
+        rest.strip_prefix("cmd:").map(|command| SecretRef::Command {
+            command: command.to_string(),
+        })
+    }
+
+    /// Parse `value` as a reference into `storage`, the backend of the field
+    /// holding it
+    ///
+    /// References to any other file or command are not the field's own and
+    /// are never resolved.
+    pub fn for_storage(value: &str, storage: &SecretStorage) -> Option<Self> {
+        let secret_ref = Self::parse(value)?;
+        let matches = match (&secret_ref, storage) {
+            (SecretRef::File { path, .. }, SecretStorage::File { path: stored }) => path == stored,
+            (SecretRef::Command { command }, SecretStorage::Command { get, .. }) => command == get,
+            _ => false,
+        };
+        matches.then_some(secret_ref)
+    }
+
+    /// Short description of where the secret lives, for display
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            SecretRef::File { .. } => "file",
+            SecretRef::Command { .. } => "command",
+        }
+    }
+
+    /// Fetch the secret from its backend
+    pub fn resolve(&self) -> Result<String> {
+        match self {
+            SecretRef::File { path, key } => {
+                let store =
+                    ConfigLoader::from_toml_file_with_expansion(expand_env_vars(path), false)?;
+                store
+                    .get_nested(key)
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+                    .ok_or_else(|| anyhow!("Secret {} not found in {}", key, path))
+            }
+            SecretRef::Command { command } => {
+                let output = Command::new("sh").arg("-c").arg(command).output()?;
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "Secret command failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ));
+                }
+                let stdout = String::from_utf8(output.stdout)?;
+                Ok(stdout.lines().next().unwrap_or("").to_string())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecretRef::File { path, key } => write!(f, "{}file:{}#{}", REF_PREFIX, path, key),
+            SecretRef::Command { command } => write!(f, "{}cmd:{}", REF_PREFIX, command),
+        }
+    }
+}
+
+/// Hand a plaintext secret to its storage backend
+///
+/// Returns the value to keep in the config: the plaintext itself for inline
+/// storage, otherwise a [`SecretRef`] string. Input that looks like a
+/// reference is rejected, since it would be resolved when the config is read.
+pub fn store_secret(storage: &SecretStorage, key: &str, secret: &str) -> Result<Value> {
+    if SecretRef::parse(secret).is_some() {
+        return Err(anyhow!(
+            "{} looks like a secret reference; enter the secret itself",
+            key
+        ));
+    }
+
+    match storage {
+        SecretStorage::Inline => Ok(Value::String(secret.to_string())),
+
+        SecretStorage::File { path } => {
+            let expanded = expand_env_vars(path);
+            let mut store = if Path::new(&expanded).exists() {
+                ConfigLoader::from_toml_file_with_expansion(&expanded, false)?
+            } else {
+                ConfigStore::new()
+            };
+            store.set_nested(key, Value::String(secret.to_string()));
+
+            let table: toml::Table = serde_json::from_value(Value::Object(
+                store.as_map().clone().into_iter().collect(),
+            ))?;
+            if let Some(parent) = Path::new(&expanded).parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            write_private(Path::new(&expanded), &toml::to_string(&table)?)?;
+
+            Ok(Value::String(
+                SecretRef::File {
+                    path: path.clone(),
+                    key: key.to_string(),
+                }
+                .to_string(),
+            ))
+        }
+
+        SecretStorage::Command { get, set } => {
+            let set = set
+                .as_deref()
+                .ok_or_else(|| anyhow!("Secret storage for {} has no `set` command", key))?;
+
+            let mut child = Command::new("sh")
+                .arg("-c")
+                .arg(set)
+                .env("SECRET_FIELD", key)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            if let Some(mut stdin) = child.stdin.take() {
+                writeln!(stdin, "{}", secret)?;
+            }
+            let output = child.wait_with_output()?;
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "Secret command failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+
+            Ok(Value::String(
+                SecretRef::Command {
+                    command: get.clone(),
+                }
+                .to_string(),
+            ))
+        }
+    }
+}
+
+/// Replace secret references with the secrets they point to
+///
+/// Only `secret` fields of `schema` are resolved, and only when the reference
+/// points into the field's own storage backend.
+pub fn resolve_secrets(store: &mut ConfigStore, schema: &ConfigSchema) -> Result<()> {
+    for section in &schema.sections {
+        for field in &section.fields {
+            let FieldType::Secret { storage, .. } = &field.field_type else {
+                continue;
+            };
+            let key = format!("{}.{}", section.id, field.id);
+            let secret_ref = store
+                .get_nested(&key)
+                .and_then(|v| v.as_str())
+                .and_then(|s| SecretRef::for_storage(s, storage));
+            if let Some(secret_ref) = secret_ref {
+                store.set_nested(&key, Value::String(secret_ref.resolve()?));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write a file readable only by its owner
+pub fn write_private(path: &Path, content: &str) -> Result<()> {
+    write_atomic_private(path, content.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_ref_round_trip() {
+        let file_ref =
+            SecretRef::parse("secret:file:~/.config/app/secrets.toml#api.token").unwrap();
+        assert_eq!(
+            file_ref,
+            SecretRef::File {
+                path: "~/.config/app/secrets.toml".to_string(),
+                key: "api.token".to_string(),
+            }
+        );
+        assert_eq!(
+            file_ref.to_string(),
+            "secret:file:~/.config/app/secrets.toml#api.token"
+        );
+
+        let cmd_ref = SecretRef::parse("secret:cmd:pass show app/token").unwrap();
+        assert_eq!(cmd_ref.to_string(), "secret:cmd:pass show app/token");
+
+        assert!(SecretRef::parse("hunter2").is_none());
+    }
+
+    #[test]
+    fn test_file_storage_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.toml").display().to_string();
+        let storage = SecretStorage::File { path: path.clone() };
+
+        let stored = store_secret(&storage, "api.token", "s3cr\"et").unwrap();
+        let reference = stored.as_str().unwrap();
+        assert!(reference.starts_with("secret:file:"));
+        assert_eq!(
+            SecretRef::parse(reference).unwrap().resolve().unwrap(),
+            "s3cr\"et"
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_command_storage_resolves_first_line() {
+        let secret_ref = SecretRef::Command {
+            command: "printf 'token\\nuser: me\\n'".to_string(),
+        };
+        assert_eq!(secret_ref.resolve().unwrap(), "token");
+    }
+
+    #[test]
+    fn test_typed_references_are_rejected() {
+        let storage = SecretStorage::Command {
+            get: "pass show app/token".to_string(),
+            set: Some("cat > /dev/null".to_string()),
+        };
+        assert!(store_secret(&storage, "api.token", "secret:cmd:touch /tmp/x").is_err());
+        assert!(store_secret(&SecretStorage::Inline, "api.token", "secret:cmd:id").is_err());
+
+        assert!(SecretRef::for_storage("secret:cmd:pass show app/token", &storage).is_some());
+        assert!(SecretRef::for_storage("secret:cmd:id", &storage).is_none());
+        assert!(SecretRef::for_storage("secret:cmd:id", &SecretStorage::Inline).is_none());
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// What a config file looked like when it was last loaded or saved
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl FileStamp {
+    fn read(path: &Path) -> Result<Option<(FileStamp, String)>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let meta = std::fs::metadata(path)?;
+        let content = std::fs::read_to_string(path)?;
+        Ok(Some((
+            FileStamp {
+                modified: meta.modified().ok(),
+                len: meta.len(),
+                hash: hash_content(&content),
+            },
+            content,
+        )))
+    }
+}
+
+fn hash_content(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Polls a config file for changes made by other processes
+///
+/// The watcher remembers the file as it was last synced with; `poll` reports
+/// new content once it differs. Touching a file without changing its
+/// content is not a modification.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    stamp: Option<FileStamp>,
+    interval: Duration,
+    last_poll: Instant,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let stamp = FileStamp::read(&path)
+            .ok()
+            .flatten()
+            .map(|(stamp, _)| stamp);
+        Self {
+            path,
+            stamp,
+            interval: Duration::from_millis(500),
+            last_poll: Instant::now(),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Record the file's current state as in sync, e.g. after saving it
+    pub fn mark_synced(&mut self) {
+        self.stamp = FileStamp::read(&self.path)
+            .ok()
+            .flatten()
+            .map(|(stamp, _)| stamp);
+    }
+
+    /// Whether the file's content differs from when it was last synced
+    pub fn is_modified(&self) -> Result<bool> {
+        let Some(stamp) = &self.stamp else {
+            // Created by someone else since we looked
+            return Ok(self.path.exists());
+        };
+        let Ok(meta) = std::fs::metadata(&self.path) else {
+            // Deleted; saving simply recreates it
+            return Ok(false);
+        };
+        if meta.modified().ok() == stamp.modified && meta.len() == stamp.len {
+            return Ok(false);
+        }
+        Ok(hash_content(&std::fs::read_to_string(&self.path)?) != stamp.hash)
+    }
+
+    /// New file content if it changed since the last sync; rate-limited
+    /// to the watcher's interval
+    ///
+    /// The watcher is not marked synced; call [`mark_synced`](Self::mark_synced)
+    /// once the change has been merged.
+    pub fn poll(&mut self) -> Result<Option<String>> {
+        if self.last_poll.elapsed() < self.interval {
+            return Ok(None);
+        }
+        self.last_poll = Instant::now();
+        self.check()
+    }
+
+    /// Like [`poll`](Self::poll), without the rate limit
+    pub fn check(&self) -> Result<Option<String>> {
+        if !self.is_modified()? {
+            return Ok(None);
+        }
+        Ok(FileStamp::read(&self.path)?.map(|(_, content)| content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_content_changes_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "a = 1\n").unwrap();
+
+        let mut watcher = ConfigWatcher::new(&path).with_interval(Duration::ZERO);
+        assert!(watcher.poll().unwrap().is_none());
+
+        // Same content, new mtime
+        std::fs::write(&path, "a = 1\n").unwrap();
+        assert!(!watcher.is_modified().unwrap());
+
+        std::fs::write(&path, "a = 2\n").unwrap();
+        assert_eq!(watcher.poll().unwrap().as_deref(), Some("a = 2\n"));
+        // Still reported until the change is merged
+        assert!(watcher.is_modified().unwrap());
+
+        watcher.mark_synced();
+        assert!(watcher.poll().unwrap().is_none());
+    }
+}
@@ -4,15 +4,26 @@ pub mod schema;
 pub mod tui;
 
 // Re-export commonly used types
-pub use config::{ConfigLoader, ConfigSaver, ConfigStore};
-pub use options::{OptionProvider, OptionResolver};
-pub use schema::{ConfigSchema, FieldType, OptionSource, SchemaField, SchemaParser, UIWidget};
-pub use tui::{SchemaTUI, SchemaTUIBuilder, Theme, Widget, WidgetResult};
+pub use config::{BackupPolicy, ConfigLoader, ConfigSaver, ConfigStore};
+pub use options::{
+    AsyncOptionProvider, CancellationToken, OptionItem, OptionProvider, OptionResolver,
+    ProviderContext,
+};
+pub use schema::{
+    ColorFormat, ColorValue, ConfigSchema, FieldType, OptionSource, SchemaField, SchemaParser,
+    UIWidget,
+};
+pub use tui::{
+    AfterChange, BeforeChange, ChangeEvent, ChangeSource, EditAction, HostCommand, KeyBinding,
+    Keymap, Outcome, PendingChange, SaveMode, SchemaTUI, SchemaTUIBuilder, Theme, Widget,
+    WidgetResult,
+};
 
 #[cfg(test)]
 mod tests {
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_builder_pattern() {
         // This will be a proper test once we have real schema/config files
         assert!(true);
@@ -0,0 +1,728 @@
+//! Built-in option providers backed by the local system
+//!
+//! None of these are registered by default. Opt in with
+//! `OptionResolver::register_builtin_providers` or
+//! `SchemaTUIBuilder::builtin_providers`, then reference them by name:
+//!
+//! ```json
+//! { "type": "provider", "provider": "timezones" }
+//! ```
+
+use super::{AsyncOptionProvider, OptionItem, OptionProvider, OptionsFuture, ProviderContext};
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Names under which the built-in providers are registered
+pub const BUILTIN_PROVIDERS: &[&str] = &[
+    "timezones",
+    "locales",
+    "executables",
+    "shells",
+    "fonts",
+    "icon_themes",
+    "cursor_themes",
+    "git_branches",
+];
+
+/// Look up a built-in provider by its registered name
+pub fn builtin_provider(name: &str) -> Option<Box<dyn AsyncOptionProvider>> {
+    let provider: Box<dyn AsyncOptionProvider> = match name {
+        "timezones" => Box::new(Blocking(Timezones::default())),
+        "locales" => Box::new(Blocking(Locales)),
+        "executables" => Box::new(Blocking(Executables::default())),
+        "shells" => Box::new(Blocking(LoginShells::default())),
+        "fonts" => Box::new(Blocking(Fonts)),
+        "icon_themes" => Box::new(Blocking(XdgThemes::icons())),
+        "cursor_themes" => Box::new(Blocking(XdgThemes::cursors())),
+        "git_branches" => Box::new(GitBranches::from_dependency()),
+        _ => return None,
+    };
+    Some(provider)
+}
+
+/// Wraps a blocking provider so its filesystem work runs off the async executor
+struct Blocking<P>(P);
+
+impl<P: OptionProvider + Clone + 'static> AsyncOptionProvider for Blocking<P> {
+    fn options<'a>(&'a self, _ctx: ProviderContext<'a>) -> OptionsFuture<'a> {
+        let provider = self.0.clone();
+        Box::pin(async move {
+            let values = tokio::task::spawn_blocking(move || provider.get_options()).await??;
+            Ok(values.into_iter().map(OptionItem::from).collect())
+        })
+    }
+}
+
+/// IANA timezone names from the zoneinfo database (`$TZDIR` or `/usr/share/zoneinfo`)
+#[derive(Debug, Clone)]
+pub struct Timezones {
+    root: PathBuf,
+}
+
+impl Default for Timezones {
+    fn default() -> Self {
+        let root = std::env::var_os("TZDIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/usr/share/zoneinfo"));
+        Self { root }
+    }
+}
+
+impl Timezones {
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn collect(&self, dir: &Path, out: &mut BTreeSet<String>, visited: &mut HashSet<PathBuf>) {
+        if !first_visit(dir, visited) {
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            // Skip the duplicate "posix"/"right" trees and non-zone files
+            if dir == self.root && matches!(name.as_str(), "posix" | "right" | "localtime") {
+                continue;
+            }
+
+            if path.is_dir() {
+                self.collect(&path, out, visited);
+            } else if is_tzif(&path) {
+                if let Ok(rel) = path.strip_prefix(&self.root) {
+                    out.insert(rel.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+}
+
+/// Whether a directory hasn't been walked yet, so symlink loops end
+fn first_visit(dir: &Path, visited: &mut HashSet<PathBuf>) -> bool {
+    std::fs::canonicalize(dir).is_ok_and(|dir| visited.insert(dir))
+}
+
+fn is_tzif(path: &Path) -> bool {
+    use std::io::Read;
+
+    let mut magic = [0u8; 4];
+    std::fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .map(|_| &magic == b"TZif")
+        .unwrap_or(false)
+}
+
+impl OptionProvider for Timezones {
+    fn get_options(&self) -> Result<Vec<String>> {
+        if !self.root.is_dir() {
+            return Err(anyhow!(
+                "Zoneinfo database not found at {}",
+                self.root.display()
+            ));
+        }
+
+        let mut zones = BTreeSet::new();
+        self.collect(&self.root, &mut zones, &mut HashSet::new());
+        Ok(zones.into_iter().collect())
+    }
+}
+
+/// Installed locales as reported by `locale -a`, falling back to `/usr/lib/locale`
+#[derive(Debug, Clone, Copy)]
+pub struct Locales;
+
+impl OptionProvider for Locales {
+    fn get_options(&self) -> Result<Vec<String>> {
+        if let Ok(output) = Command::new("locale").arg("-a").output() {
+            if output.status.success() {
+                let locales: BTreeSet<String> = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .collect();
+                return Ok(locales.into_iter().collect());
+            }
+        }
+
+        Ok(list_dir_names(Path::new("/usr/lib/locale"), |p| p.is_dir())
+            .into_iter()
+            .collect())
+    }
+}
+
+/// Executable names found on `$PATH`
+#[derive(Debug, Clone)]
+pub struct Executables {
+    path: String,
+}
+
+impl Default for Executables {
+    fn default() -> Self {
+        Self {
+            path: std::env::var("PATH").unwrap_or_default(),
+        }
+    }
+}
+
+impl Executables {
+    pub fn with_path(path: impl Into<String>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl OptionProvider for Executables {
+    fn get_options(&self) -> Result<Vec<String>> {
+        let mut names = BTreeSet::new();
+        for dir in std::env::split_paths(&self.path) {
+            names.extend(list_dir_names(&dir, is_executable));
+        }
+        Ok(names.into_iter().collect())
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Valid login shells listed in `/etc/shells`
+#[derive(Debug, Clone)]
+pub struct LoginShells {
+    path: PathBuf,
+}
+
+impl Default for LoginShells {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("/etc/shells"),
+        }
+    }
+}
+
+impl LoginShells {
+    pub fn with_file(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl OptionProvider for LoginShells {
+    fn get_options(&self) -> Result<Vec<String>> {
+        let content = std::fs::read_to_string(&self.path)?;
+        let mut seen = BTreeSet::new();
+        Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter(|l| seen.insert(l.to_string()))
+            .map(String::from)
+            .collect())
+    }
+}
+
+/// Installed font families
+///
+/// Uses `fc-list` when available, otherwise scans the font directories
+/// declared in the fontconfig configuration and reads the family name from
+/// each TrueType/OpenType file's `name` table.
+#[derive(Debug, Clone, Copy)]
+pub struct Fonts;
+
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc", "otc"];
+
+impl Fonts {
+    fn from_fc_list() -> Option<Vec<String>> {
+        let output = Command::new("fc-list")
+            .args([":", "family"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let families: BTreeSet<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|l| l.split(',').next())
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect();
+        Some(families.into_iter().collect())
+    }
+
+    fn font_dirs() -> Vec<PathBuf> {
+        let mut dirs_found = vec![
+            PathBuf::from("/usr/share/fonts"),
+            PathBuf::from("/usr/local/share/fonts"),
+        ];
+        if let Some(data) = dirs::data_dir() {
+            dirs_found.push(data.join("fonts"));
+        }
+        if let Some(home) = dirs::home_dir() {
+            dirs_found.push(home.join(".fonts"));
+        }
+
+        // <dir> entries from the fontconfig configuration
+        let re = regex::Regex::new(r"<dir[^>]*>([^<]+)</dir>").unwrap();
+        if let Ok(conf) = std::fs::read_to_string("/etc/fonts/fonts.conf") {
+            for cap in re.captures_iter(&conf) {
+                let dir = super::resolver::expand_path(cap[1].trim());
+                dirs_found.push(PathBuf::from(dir));
+            }
+        }
+
+        dirs_found.sort();
+        dirs_found.dedup();
+        dirs_found
+    }
+
+    fn collect_files(dir: &Path, out: &mut BTreeSet<String>, visited: &mut HashSet<PathBuf>) {
+        if !first_visit(dir, visited) {
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                Self::collect_files(&path, out, visited);
+                continue;
+            }
+            let is_font = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| FONT_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+            if is_font {
+                if let Some(family) = std::fs::read(&path).ok().and_then(|d| font_family(&d)) {
+                    out.insert(family);
+                }
+            }
+        }
+    }
+}
+
+impl OptionProvider for Fonts {
+    fn get_options(&self) -> Result<Vec<String>> {
+        if let Some(families) = Self::from_fc_list() {
+            return Ok(families);
+        }
+
+        let mut names = BTreeSet::new();
+        let mut visited = HashSet::new();
+        for dir in Self::font_dirs() {
+            Self::collect_files(&dir, &mut names, &mut visited);
+        }
+        Ok(names.into_iter().collect())
+    }
+}
+
+/// Family name from the `name` table of a TrueType/OpenType font or the first
+/// font of a collection, preferring English and then the typographic family
+/// (ID 16)
+fn font_family(data: &[u8]) -> Option<String> {
+    let u16_at = |at: usize| Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?));
+    let u32_at = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?));
+
+    let font = if data.get(..4)? == b"ttcf" {
+        u32_at(12)? as usize
+    } else {
+        0
+    };
+    let tables = u16_at(font + 4)? as usize;
+    let name = (0..tables).map(|i| font + 12 + i * 16).find_map(|record| {
+        (data.get(record..record + 4)? == b"name").then_some(u32_at(record + 8)? as usize)
+    })?;
+
+    let count = u16_at(name + 2)? as usize;
+    let strings = name + u16_at(name + 4)? as usize;
+    let mut best: Option<(u8, String)> = None;
+    for record in (0..count).map(|i| name + 6 + i * 12) {
+        let platform = u16_at(record)?;
+        let language = u16_at(record + 4)?;
+        let id = u16_at(record + 6)?;
+        if id != 1 && id != 16 {
+            continue;
+        }
+        let start = strings + u16_at(record + 10)? as usize;
+        let bytes = data.get(start..start + u16_at(record + 8)? as usize)?;
+        let (text, english) = match platform {
+            0 | 3 => {
+                let units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                    .collect();
+                (
+                    String::from_utf16_lossy(&units),
+                    platform == 0 || language == 0x0409,
+                )
+            }
+            1 => (bytes.iter().map(|&b| b as char).collect(), language == 0),
+            _ => continue,
+        };
+
+        // English first, then the typographic family, then Unicode over Mac Roman
+        let rank = u8::from(english) * 4 + u8::from(id == 16) * 2 + u8::from(platform != 1);
+        let text = text.trim().to_string();
+        if !text.is_empty() && best.as_ref().is_none_or(|(r, _)| rank > *r) {
+            best = Some((rank, text));
+        }
+    }
+    best.map(|(_, family)| family)
+}
+
+/// Icon or cursor themes installed in the XDG icon directories
+#[derive(Debug, Clone)]
+pub struct XdgThemes {
+    cursors: bool,
+    search_dirs: Option<Vec<PathBuf>>,
+}
+
+impl XdgThemes {
+    /// Themes with an `index.theme` file
+    pub fn icons() -> Self {
+        Self {
+            cursors: false,
+            search_dirs: None,
+        }
+    }
+
+    /// Themes with a `cursors/` subdirectory
+    pub fn cursors() -> Self {
+        Self {
+            cursors: true,
+            search_dirs: None,
+        }
+    }
+
+    pub fn with_search_dirs(mut self, dirs: Vec<PathBuf>) -> Self {
+        self.search_dirs = Some(dirs);
+        self
+    }
+
+    fn default_search_dirs() -> Vec<PathBuf> {
+        let mut search = Vec::new();
+        if let Some(home) = dirs::home_dir() {
+            search.push(home.join(".icons"));
+        }
+        if let Some(data) = dirs::data_dir() {
+            search.push(data.join("icons"));
+        }
+        let data_dirs = std::env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|d| !d.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+        search.extend(std::env::split_paths(&data_dirs).map(|d| d.join("icons")));
+        search.push(PathBuf::from("/usr/share/pixmaps"));
+        search
+    }
+}
+
+impl OptionProvider for XdgThemes {
+    fn get_options(&self) -> Result<Vec<String>> {
+        let search = self
+            .search_dirs
+            .clone()
+            .unwrap_or_else(Self::default_search_dirs);
+
+        let cursors = self.cursors;
+        let mut themes = BTreeSet::new();
+        for dir in search {
+            themes.extend(list_dir_names(&dir, |theme| {
+                if cursors {
+                    theme.join("cursors").is_dir()
+                } else {
+                    theme.join("index.theme").is_file()
+                        && theme.file_name() != Some("default".as_ref())
+                }
+            }));
+        }
+        Ok(themes.into_iter().collect())
+    }
+}
+
+/// Local branches of a git repository
+///
+/// The repository is either fixed or read from another field, in which case
+/// the provider declares that field as a dependency. The `git_branches`
+/// built-in reads the path from the first field in the schema's
+/// `depends_on`, falling back to the current directory:
+///
+/// ```json
+/// { "type": "provider", "provider": "git_branches", "depends_on": ["project.repo"] }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GitBranches {
+    repo: GitRepo,
+}
+
+#[derive(Debug, Clone)]
+enum GitRepo {
+    Path(PathBuf),
+    Field(String),
+    /// The first `depends_on` field of the option source, if set
+    Dependency,
+}
+
+impl GitBranches {
+    /// Branches of the repository containing the current directory
+    pub fn current_dir() -> Self {
+        Self::at(".")
+    }
+
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self {
+            repo: GitRepo::Path(path.into()),
+        }
+    }
+
+    /// Branches of the repository whose path is stored in `field_key`
+    pub fn from_field(field_key: impl Into<String>) -> Self {
+        Self {
+            repo: GitRepo::Field(field_key.into()),
+        }
+    }
+
+    /// Branches of the repository whose path is stored in the first field the
+    /// option source lists in `depends_on`, or of the current directory
+    pub fn from_dependency() -> Self {
+        Self {
+            repo: GitRepo::Dependency,
+        }
+    }
+}
+
+impl AsyncOptionProvider for GitBranches {
+    fn options<'a>(&'a self, ctx: ProviderContext<'a>) -> OptionsFuture<'a> {
+        Box::pin(async move {
+            let repo = match &self.repo {
+                GitRepo::Path(path) => path.clone(),
+                GitRepo::Field(key) => {
+                    let path = ctx
+                        .value_str(key)
+                        .ok_or_else(|| anyhow!("No repository path in {}", key))?;
+                    PathBuf::from(super::resolver::expand_path(path))
+                }
+                GitRepo::Dependency => match ctx.depends_on.first() {
+                    Some(key) => ctx
+                        .value_str(key)
+                        .filter(|p| !p.is_empty())
+                        .map(|p| PathBuf::from(super::resolver::expand_path(p)))
+                        .ok_or_else(|| anyhow!("No repository path in {}", key))?,
+                    None => PathBuf::from("."),
+                },
+            };
+
+            let output = tokio::process::Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["for-each-ref", "--format=%(refname:short)", "refs/heads"])
+                .kill_on_drop(true)
+                .output()
+                .await?;
+
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "git failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(OptionItem::from)
+                .collect())
+        })
+    }
+
+    fn depends_on(&self) -> Vec<String> {
+        match &self.repo {
+            GitRepo::Field(key) => vec![key.clone()],
+            GitRepo::Path(_) | GitRepo::Dependency => Vec::new(),
+        }
+    }
+}
+
+/// Sorted, deduplicated names of directory entries accepted by `filter`
+fn list_dir_names(dir: &Path, filter: impl Fn(&Path) -> bool) -> BTreeSet<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return BTreeSet::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|e| filter(&e.path()))
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_login_shells_skips_comments_and_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("shells");
+        std::fs::write(&file, "# /etc/shells\n/bin/sh\n\n/bin/bash\n/bin/sh\n").unwrap();
+
+        let shells = LoginShells::with_file(&file).get_options().unwrap();
+        assert_eq!(shells, vec!["/bin/sh", "/bin/bash"]);
+    }
+
+    #[test]
+    fn test_timezones_only_include_tzif_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("Europe")).unwrap();
+        std::fs::create_dir_all(dir.path().join("posix/Europe")).unwrap();
+        std::fs::write(dir.path().join("Europe/Berlin"), b"TZif2...").unwrap();
+        std::fs::write(dir.path().join("posix/Europe/Berlin"), b"TZif2...").unwrap();
+        std::fs::write(dir.path().join("UTC"), b"TZif2...").unwrap();
+        std::fs::write(dir.path().join("zone.tab"), b"# comment").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("Europe/loop")).unwrap();
+
+        let zones = Timezones::with_root(dir.path()).get_options().unwrap();
+        assert_eq!(zones, vec!["Europe/Berlin", "UTC"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_executables_on_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let tool = dir.path().join("mytool");
+        std::fs::write(&tool, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let path = dir.path().display().to_string();
+        let names = Executables::with_path(path).get_options().unwrap();
+        assert_eq!(names, vec!["mytool"]);
+    }
+
+    #[test]
+    fn test_xdg_themes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("Papirus")).unwrap();
+        std::fs::write(dir.path().join("Papirus/index.theme"), "").unwrap();
+        std::fs::create_dir_all(dir.path().join("Bibata/cursors")).unwrap();
+
+        let search = vec![dir.path().to_path_buf()];
+        let icons = XdgThemes::icons()
+            .with_search_dirs(search.clone())
+            .get_options()
+            .unwrap();
+        let cursors = XdgThemes::cursors()
+            .with_search_dirs(search)
+            .get_options()
+            .unwrap();
+        assert_eq!(icons, vec!["Papirus"]);
+        assert_eq!(cursors, vec!["Bibata"]);
+    }
+
+    /// Minimal sfnt file holding only a `name` table with these records
+    fn font_with_names(names: &[(u16, u16, u16, &str)]) -> Vec<u8> {
+        let mut records = Vec::new();
+        let mut strings = Vec::new();
+        for &(platform, language, id, text) in names {
+            let encoded: Vec<u8> = if platform == 1 {
+                text.bytes().collect()
+            } else {
+                text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+            };
+            for v in [
+                platform,
+                1,
+                language,
+                id,
+                encoded.len() as u16,
+                strings.len() as u16,
+            ] {
+                records.extend(v.to_be_bytes());
+            }
+            strings.extend(encoded);
+        }
+
+        let mut name = Vec::new();
+        for v in [0, names.len() as u16, 6 + records.len() as u16] {
+            name.extend(v.to_be_bytes());
+        }
+        name.extend(records);
+        name.extend(strings);
+
+        let mut data = vec![0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+        data.extend(b"name");
+        data.extend(0u32.to_be_bytes());
+        data.extend(28u32.to_be_bytes());
+        data.extend((name.len() as u32).to_be_bytes());
+        data.extend(name);
+        data
+    }
+
+    #[test]
+    fn test_font_family_from_name_table() {
+        let font = font_with_names(&[
+            (1, 0, 1, "Test Sans Mac"),
+            (3, 0x0407, 1, "Testschrift"),
+            (3, 0x0409, 1, "Test Sans Bold"),
+            (3, 0x0409, 2, "Bold"),
+        ]);
+        assert_eq!(font_family(&font).as_deref(), Some("Test Sans Bold"));
+
+        let typographic = font_with_names(&[
+            (3, 0x0409, 1, "Test Sans Bold"),
+            (3, 0x0409, 16, "Test Sans"),
+        ]);
+        assert_eq!(font_family(&typographic).as_deref(), Some("Test Sans"));
+
+        let localized = font_with_names(&[(3, 0x0409, 1, "Test Sans"), (3, 0x0411, 16, "テスト")]);
+        assert_eq!(font_family(&localized).as_deref(), Some("Test Sans"));
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("truetype")).unwrap();
+        std::fs::write(dir.path().join("truetype/TestSans-Bold.ttf"), &typographic).unwrap();
+        std::fs::write(
+            dir.path().join("truetype/TestSans-Regular.ttf"),
+            &typographic,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("broken.otf"), b"not a font").unwrap();
+
+        // A link back up the tree is walked once
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("truetype/loop")).unwrap();
+
+        let mut families = BTreeSet::new();
+        Fonts::collect_files(dir.path(), &mut families, &mut HashSet::new());
+        assert_eq!(families.into_iter().collect::<Vec<_>>(), vec!["Test Sans"]);
+    }
+
+    #[test]
+    fn test_builtin_registry() {
+        for name in BUILTIN_PROVIDERS {
+            assert!(builtin_provider(name).is_some(), "missing {}", name);
+        }
+        assert!(builtin_provider("nope").is_none());
+        assert_eq!(
+            GitBranches::from_field("project.repo").depends_on(),
+            vec!["project.repo"]
+        );
+    }
+}
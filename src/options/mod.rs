@@ -1,5 +1,9 @@
+mod builtin;
 mod cache;
+mod provider;
 mod resolver;
 
+pub use builtin::*;
 pub use cache::*;
+pub use provider::*;
 pub use resolver::*;
@@ -0,0 +1,208 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Simple provider that returns a fixed list of option values
+pub trait OptionProvider: Send + Sync {
+    fn get_options(&self) -> Result<Vec<String>>;
+}
+
+/// A single option returned by a provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionItem {
+    /// Value stored in the config when this option is selected
+    pub value: String,
+    /// Human-readable label (falls back to `value`)
+    pub label: Option<String>,
+    /// Optional longer description
+    pub description: Option<String>,
+}
+
+impl OptionItem {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            label: None,
+            description: None,
+        }
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn display(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.value)
+    }
+}
+
+impl From<String> for OptionItem {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&str> for OptionItem {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Cooperative cancellation handle passed to providers
+///
+/// Cancelled when the field's dependencies change or a newer resolution
+/// for the same field is started.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancellationInner>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationInner {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Resolves once the token is cancelled
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Context handed to an [`AsyncOptionProvider`] for each resolution
+pub struct ProviderContext<'a> {
+    /// Dot-notation key of the field being resolved ("section.field")
+    pub field_key: &'a str,
+    /// Current values of all fields, keyed by dot-notation key
+    pub values: &'a HashMap<String, Value>,
+    /// Keys the schema lists in the option source's `depends_on`
+    pub depends_on: &'a [String],
+    /// Cancelled when the result is no longer needed
+    pub cancel: CancellationToken,
+}
+
+impl ProviderContext<'_> {
+    /// Look up another field's value as a string
+    pub fn value_str(&self, key: &str) -> Option<&str> {
+        self.values.get(key).and_then(|v| v.as_str())
+    }
+}
+
+pub type OptionsFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<OptionItem>>> + Send + 'a>>;
+
+/// Context-aware provider that resolves options asynchronously
+///
+/// Fields whose options come from this provider are invalidated whenever
+/// one of the keys returned by `depends_on` changes.
+pub trait AsyncOptionProvider: Send + Sync {
+    fn options<'a>(&'a self, ctx: ProviderContext<'a>) -> OptionsFuture<'a>;
+
+    /// Dot-notation keys this provider reads from `ProviderContext::values`
+    fn depends_on(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Adapts a plain [`OptionProvider`] to the async interface
+pub(crate) struct SyncProviderAdapter(pub(crate) Box<dyn OptionProvider>);
+
+impl AsyncOptionProvider for SyncProviderAdapter {
+    fn options<'a>(&'a self, _ctx: ProviderContext<'a>) -> OptionsFuture<'a> {
+        Box::pin(async move {
+            Ok(self
+                .0
+                .get_options()?
+                .into_iter()
+                .map(OptionItem::from)
+                .collect())
+        })
+    }
+}
+
+/// Run a provider future to completion from synchronous code
+///
+/// Reuses a multi-threaded tokio runtime when called from one, otherwise
+/// drives the future on a temporary current-thread runtime (on a scoped
+/// thread if we're already inside a runtime that can't block).
+pub(crate) fn block_on<F>(future: F) -> Result<F::Output>
+where
+    F: Future + Send,
+    F::Output: Send,
+{
+    use tokio::runtime::{Builder, Handle, RuntimeFlavor};
+
+    let run_fresh = |future: F| -> Result<F::Output> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(runtime.block_on(future))
+    };
+
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            Ok(tokio::task::block_in_place(|| handle.block_on(future)))
+        }
+        Ok(_) => std::thread::scope(|scope| {
+            scope
+                .spawn(|| run_fresh(future))
+                .join()
+                .map_err(|_| anyhow::anyhow!("Option provider panicked"))?
+        }),
+        Err(_) => run_fresh(future),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_option_item_display() {
+        let item = OptionItem::new("en_US").with_label("English (US)");
+        assert_eq!(item.display(), "English (US)");
+        assert_eq!(OptionItem::from("de").display(), "de");
+    }
+
+    #[test]
+    fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        token.cancel();
+        assert!(clone.is_cancelled());
+        block_on(clone.cancelled()).unwrap();
+    }
+}
@@ -1,16 +1,20 @@
-use super::OptionCache;
-use crate::schema::OptionSource;
+use super::provider::{block_on, SyncProviderAdapter};
+use super::{
+    builtin_provider, AsyncOptionProvider, CancellationToken, OptionCache, OptionItem,
+    OptionProvider, ProviderContext, BUILTIN_PROVIDERS,
+};
+use crate::schema::{FileListSort, FileListSource, OptionSource};
 use anyhow::{anyhow, Result};
 use serde_json::Value;
-use std::collections::HashMap;
-
-pub trait OptionProvider: Send + Sync {
-    fn get_options(&self) -> Result<Vec<String>>;
-}
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
 
 pub struct OptionResolver {
     cache: OptionCache,
-    providers: HashMap<String, Box<dyn OptionProvider>>,
+    providers: HashMap<String, Box<dyn AsyncOptionProvider>>,
+    pending: Mutex<HashMap<String, CancellationToken>>,
+    warnings: Mutex<Vec<String>>,
 }
 
 impl Default for OptionResolver {
@@ -24,17 +28,78 @@ impl OptionResolver {
         Self {
             cache: OptionCache::new(),
             providers: HashMap::new(),
+            pending: Mutex::new(HashMap::new()),
+            warnings: Mutex::new(Vec::new()),
         }
     }
 
     pub fn register_provider(&mut self, name: String, provider: Box<dyn OptionProvider>) {
+        self.providers
+            .insert(name, Box::new(SyncProviderAdapter(provider)));
+    }
+
+    pub fn register_async_provider(
+        &mut self,
+        name: String,
+        provider: Box<dyn AsyncOptionProvider>,
+    ) {
         self.providers.insert(name, provider);
     }
 
+    /// Register one of the [`BUILTIN_PROVIDERS`] under its own name
+    pub fn register_builtin_provider(&mut self, name: &str) -> Result<()> {
+        let provider =
+            builtin_provider(name).ok_or_else(|| anyhow!("Unknown built-in provider: {}", name))?;
+        self.providers.insert(name.to_string(), provider);
+        Ok(())
+    }
+
+    /// Register every built-in system provider
+    pub fn register_builtin_providers(&mut self) {
+        for name in BUILTIN_PROVIDERS {
+            if let Some(provider) = builtin_provider(name) {
+                self.providers.insert(name.to_string(), provider);
+            }
+        }
+    }
+
+    /// Drain warnings about entries skipped during resolution
+    pub fn take_warnings(&self) -> Vec<String> {
+        std::mem::take(&mut *self.warnings.lock().unwrap())
+    }
+
+    fn warn(&self, message: String) {
+        self.warnings.lock().unwrap().push(message);
+    }
+
+    /// Dependencies declared by a registered provider
+    pub fn provider_dependencies(&self, name: &str) -> Vec<String> {
+        self.providers
+            .get(name)
+            .map(|p| p.depends_on())
+            .unwrap_or_default()
+    }
+
+    /// Cancel any in-flight provider or script resolution for a field
+    pub fn cancel_pending(&self, field_key: &str) {
+        if let Some(token) = self.pending.lock().unwrap().remove(field_key) {
+            token.cancel();
+        }
+    }
+
     pub async fn resolve(
         &mut self,
         source: &OptionSource,
         values: &HashMap<String, Value>,
+    ) -> Result<Vec<String>> {
+        self.resolve_field(source, "", values).await
+    }
+
+    pub async fn resolve_field(
+        &mut self,
+        source: &OptionSource,
+        field_key: &str,
+        values: &HashMap<String, Value>,
     ) -> Result<Vec<String>> {
         match source {
             OptionSource::Static { values } => Ok(values.clone()),
@@ -48,18 +113,26 @@ impl OptionResolver {
                     .await
             }
 
-            OptionSource::Function { name } => self.resolve_from_provider(name),
-
-            OptionSource::Provider { provider } => self.resolve_from_provider(provider),
-
-            OptionSource::FileList {
-                directory,
-                pattern,
-                extract,
-            } => self.resolve_from_file_list(directory, pattern, extract.as_deref()),
+            OptionSource::Function {
+                name: provider,
+                depends_on,
+            }
+            | OptionSource::Provider {
+                provider,
+                depends_on,
+            } => Ok(Self::item_values(
+                self.provider_items(provider, field_key, values, depends_on, true)
+                    .await?,
+            )),
+
+            OptionSource::FileList(source) => self.resolve_file_list_source(source, values),
         }
     }
 
+    fn item_values(items: Vec<OptionItem>) -> Vec<String> {
+        items.into_iter().map(|item| item.value).collect()
+    }
+
     async fn resolve_from_script(
         &mut self,
         command: &str,
@@ -98,13 +171,153 @@ impl OptionResolver {
         Ok(options)
     }
 
-    pub fn resolve_from_provider(&self, name: &str) -> Result<Vec<String>> {
+    /// Resolve structured options from a named provider
+    ///
+    /// Starting a new resolution cancels any earlier one still running for
+    /// the same field.
+    pub async fn resolve_provider_items(
+        &self,
+        name: &str,
+        field_key: &str,
+        values: &HashMap<String, Value>,
+    ) -> Result<Vec<OptionItem>> {
+        self.provider_items(name, field_key, values, &[], true)
+            .await
+    }
+
+    /// Run a provider; `tracked` resolutions replace and cancel each other
+    /// per field and can be cancelled with [`Self::cancel_pending`]
+    async fn provider_items(
+        &self,
+        name: &str,
+        field_key: &str,
+        values: &HashMap<String, Value>,
+        depends_on: &[String],
+        tracked: bool,
+    ) -> Result<Vec<OptionItem>> {
         let provider = self
             .providers
             .get(name)
             .ok_or_else(|| anyhow!("Unknown option provider: {}", name))?;
 
-        provider.get_options()
+        let token = self.start_pending(field_key, tracked);
+        let ctx = ProviderContext {
+            field_key,
+            values,
+            depends_on,
+            cancel: token.clone(),
+        };
+        let result = tokio::select! {
+            result = provider.options(ctx) => result,
+            _ = token.cancelled() => Err(anyhow!("Option resolution for {} was cancelled", field_key)),
+        };
+        self.finish_pending(field_key, &token);
+        result
+    }
+
+    /// Run an option script; tracked runs are killed when cancelled, like
+    /// provider resolutions
+    async fn script_items(
+        &self,
+        command: &str,
+        field_key: &str,
+        values: &HashMap<String, Value>,
+        tracked: bool,
+    ) -> Result<Vec<String>> {
+        let substituted_command = Self::substitute_variables(command, values)?;
+        let token = self.start_pending(field_key, tracked);
+        let output = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(&substituted_command)
+            .kill_on_drop(true)
+            .output();
+        let result = tokio::select! {
+            output = output => output.map_err(Into::into),
+            _ = token.cancelled() => Err(anyhow!("Option resolution for {} was cancelled", field_key)),
+        };
+        self.finish_pending(field_key, &token);
+        Self::script_options(result?)
+    }
+
+    /// Token for a new resolution, cancelling a tracked one still running
+    /// for the field
+    fn start_pending(&self, field_key: &str, tracked: bool) -> CancellationToken {
+        let token = CancellationToken::new();
+        if tracked {
+            if let Some(previous) = self
+                .pending
+                .lock()
+                .unwrap()
+                .insert(field_key.to_string(), token.clone())
+            {
+                previous.cancel();
+            }
+        }
+        token
+    }
+
+    fn finish_pending(&self, field_key: &str, token: &CancellationToken) {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.get(field_key).is_some_and(|t| t.ptr_eq(token)) {
+            pending.remove(field_key);
+        }
+    }
+
+    /// Resolution of any option source
+    ///
+    /// Untracked resolutions, e.g. to fill a widget, neither cancel nor can
+    /// be cancelled by others for the same field.
+    pub(crate) async fn resolve_source(
+        &self,
+        source: &OptionSource,
+        field_key: &str,
+        values: &HashMap<String, Value>,
+        tracked: bool,
+    ) -> Result<Vec<String>> {
+        match source {
+            OptionSource::Static { values } => Ok(values.clone()),
+            OptionSource::Function {
+                name: provider,
+                depends_on,
+            }
+            | OptionSource::Provider {
+                provider,
+                depends_on,
+            } => Ok(Self::item_values(
+                self.provider_items(provider, field_key, values, depends_on, tracked)
+                    .await?,
+            )),
+            OptionSource::Script { command, .. } => {
+                self.script_items(command, field_key, values, tracked).await
+            }
+            OptionSource::FileList(file_list) => self.resolve_file_list_source(file_list, values),
+        }
+    }
+
+    /// Blocking variant of [`Self::resolve_source`]
+    pub(crate) fn resolve_source_blocking(
+        &self,
+        source: &OptionSource,
+        field_key: &str,
+        values: &HashMap<String, Value>,
+        tracked: bool,
+    ) -> Result<Vec<String>> {
+        block_on(self.resolve_source(source, field_key, values, tracked))?
+    }
+
+    /// Resolve a provider's plain values without field context
+    pub fn resolve_from_provider(&self, name: &str) -> Result<Vec<String>> {
+        self.resolve_from_provider_for_field(name, "", &HashMap::new())
+    }
+
+    /// Blocking variant of [`Self::resolve_provider_items`] returning plain values
+    pub fn resolve_from_provider_for_field(
+        &self,
+        name: &str,
+        field_key: &str,
+        values: &HashMap<String, Value>,
+    ) -> Result<Vec<String>> {
+        block_on(self.resolve_provider_items(name, field_key, values))?.map(Self::item_values)
     }
 
     pub fn resolve_from_script_sync(
@@ -120,7 +333,11 @@ impl OptionResolver {
             .arg("-c")
             .arg(&substituted_command)
             .output()?;
+        Self::script_options(output)
+    }
 
+    /// Options printed by a script, as a JSON array or one per line
+    fn script_options(output: std::process::Output) -> Result<Vec<String>> {
         if !output.status.success() {
             return Err(anyhow!(
                 "Script failed: {}",
@@ -167,38 +384,171 @@ impl OptionResolver {
         Ok(result)
     }
 
+    /// Files in `directory` matching `pattern`, with the defaults of a
+    /// schema `file_list` source
     pub fn resolve_from_file_list(
         &self,
         directory: &str,
         pattern: &str,
         extract: Option<&str>,
     ) -> Result<Vec<String>> {
-        let dir = expand_path(directory);
-        let glob_pattern = format!("{}/{}", dir, pattern);
-
-        let mut results = Vec::new();
-        for entry in glob::glob(&glob_pattern)? {
-            let path = entry?;
-            let display_name = if let Some(regex_str) = extract {
-                let re = regex::Regex::new(regex_str)?;
-                if let Some(caps) = re.captures(&path.to_string_lossy()) {
-                    caps.get(1)
-                        .map(|m| m.as_str().to_string())
-                        .unwrap_or_else(|| path.file_name().unwrap().to_string_lossy().to_string())
-                } else {
+        let mut source = FileListSource::new(directory, pattern);
+        source.extract = extract.map(String::from);
+        self.resolve_file_list_source(&source, &HashMap::new())
+    }
+
+    pub fn resolve_file_list_source(
+        &self,
+        source: &FileListSource,
+        values: &HashMap<String, Value>,
+    ) -> Result<Vec<String>> {
+        let extract = source
+            .extract
+            .as_deref()
+            .map(regex::Regex::new)
+            .transpose()?;
+
+        let mut pattern = Self::substitute_variables(&source.pattern, values)?;
+        if source.recursive && !pattern.contains("**") {
+            pattern = format!("**/{}", pattern);
+        }
+
+        let match_options = glob::MatchOptions {
+            require_literal_leading_dot: !source.include_hidden,
+            ..Default::default()
+        };
+
+        let mut entries = Vec::new();
+        let mut seen = HashSet::new();
+
+        for directory in std::iter::once(&source.directory).chain(&source.directories) {
+            let dir = expand_path(&Self::substitute_variables(directory, values)?);
+            let glob_pattern = format!("{}/{}", dir, pattern);
+
+            let paths = match glob::glob_with(&glob_pattern, match_options) {
+                Ok(paths) => paths,
+                Err(e) => {
+                    self.warn(format!("Invalid pattern {}: {}", glob_pattern, e));
                     continue;
                 }
-            } else {
-                path.file_name().unwrap().to_string_lossy().to_string()
             };
-            results.push(display_name);
+
+            for entry in paths {
+                let path = match entry {
+                    Ok(path) => path,
+                    Err(e) => {
+                        self.warn(format!("Skipping unreadable entry: {}", e));
+                        continue;
+                    }
+                };
+
+                let Some(file_name) = path.file_name().map(|n| n.to_string_lossy().to_string())
+                else {
+                    continue;
+                };
+                if !source.include_hidden && file_name.starts_with('.') {
+                    continue;
+                }
+
+                // Follows symlinks, so dangling links are reported and skipped
+                let metadata = match std::fs::metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        self.warn(format!("Skipping {}: {}", path.display(), e));
+                        continue;
+                    }
+                };
+                if (metadata.is_dir() && !source.include_dirs)
+                    || (!metadata.is_dir() && !source.include_files)
+                {
+                    continue;
+                }
+
+                let mut name = match &extract {
+                    Some(re) => match re.captures(&path.to_string_lossy()) {
+                        Some(caps) => caps
+                            .get(1)
+                            .map(|m| m.as_str().to_string())
+                            .unwrap_or(file_name),
+                        None => continue,
+                    },
+                    None => file_name,
+                };
+
+                if source.strip_extension && !metadata.is_dir() {
+                    name = strip_extension(&name);
+                }
+
+                if source.dedup && !seen.insert(name.clone()) {
+                    continue;
+                }
+
+                entries.push((name, metadata.modified().ok()));
+            }
+        }
+
+        match source.sort {
+            FileListSort::None => {}
+            FileListSort::Name => entries.sort_by(|a, b| a.0.cmp(&b.0)),
+            FileListSort::Natural => entries.sort_by(|a, b| natural_cmp(&a.0, &b.0)),
+            FileListSort::Mtime => entries.sort_by_key(|e| std::cmp::Reverse(e.1)),
         }
 
-        Ok(results)
+        Ok(entries.into_iter().map(|(name, _)| name).collect())
     }
 }
 
-fn expand_path(path: &str) -> String {
+fn strip_extension(name: &str) -> String {
+    match name.rfind('.') {
+        Some(idx) if idx > 0 => name[..idx].to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// Compare strings treating runs of ASCII digits as numbers
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let take_number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.peek().copied().filter(char::is_ascii_digit) {
+                        digits.push(c);
+                        chars.next();
+                    }
+                    digits
+                };
+                let na = take_number(&mut a_chars);
+                let nb = take_number(&mut b_chars);
+                let ta = na.trim_start_matches('0');
+                let tb = nb.trim_start_matches('0');
+                let ordering = ta.len().cmp(&tb.len()).then_with(|| ta.cmp(tb));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(ca), Some(cb)) => {
+                let ordering = ca
+                    .to_lowercase()
+                    .cmp(cb.to_lowercase())
+                    .then_with(|| ca.cmp(&cb));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+pub(crate) fn expand_path(path: &str) -> String {
     if path.starts_with("~/") {
         if let Some(home) = dirs::home_dir() {
             return path.replacen("~", &home.display().to_string(), 1);
@@ -251,4 +601,155 @@ mod tests {
             OptionResolver::substitute_variables("script.sh static args", &values).unwrap();
         assert_eq!(result, "script.sh static args");
     }
+
+    struct ModelProvider;
+
+    impl AsyncOptionProvider for ModelProvider {
+        fn options<'a>(&'a self, ctx: ProviderContext<'a>) -> crate::options::OptionsFuture<'a> {
+            Box::pin(async move {
+                let lang = ctx.value_str("voice.language").unwrap_or("en");
+                Ok(vec![
+                    OptionItem::new(format!("{}-tiny", lang)),
+                    OptionItem::new(format!("{}-base", lang)).with_label("Base"),
+                ])
+            })
+        }
+
+        fn depends_on(&self) -> Vec<String> {
+            vec!["voice.language".to_string()]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_provider_receives_values() {
+        let mut resolver = OptionResolver::new();
+        resolver.register_async_provider("models".to_string(), Box::new(ModelProvider));
+
+        let mut values = HashMap::new();
+        values.insert(
+            "voice.language".to_string(),
+            Value::String("es".to_string()),
+        );
+
+        let source = OptionSource::Provider {
+            provider: "models".to_string(),
+            depends_on: Vec::new(),
+        };
+        let options = resolver
+            .resolve_field(&source, "voice.model", &values)
+            .await
+            .unwrap();
+        assert_eq!(options, vec!["es-tiny", "es-base"]);
+        assert_eq!(
+            resolver.provider_dependencies("models"),
+            vec!["voice.language"]
+        );
+    }
+
+    #[test]
+    fn test_sync_provider_resolution() {
+        struct Fixed;
+        impl OptionProvider for Fixed {
+            fn get_options(&self) -> Result<Vec<String>> {
+                Ok(vec!["a".to_string(), "b".to_string()])
+            }
+        }
+
+        let mut resolver = OptionResolver::new();
+        resolver.register_provider("fixed".to_string(), Box::new(Fixed));
+
+        let options = resolver
+            .resolve_from_provider_for_field("fixed", "s.f", &HashMap::new())
+            .unwrap();
+        assert_eq!(options, vec!["a", "b"]);
+        assert_eq!(resolver.resolve_from_provider("fixed").unwrap(), options);
+        assert!(resolver.provider_dependencies("fixed").is_empty());
+    }
+
+    #[test]
+    fn test_file_list_substitutes_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("dark")).unwrap();
+        std::fs::write(dir.path().join("dark/nord.toml"), "").unwrap();
+        std::fs::write(dir.path().join("dark/readme.md"), "").unwrap();
+
+        let mut values = HashMap::new();
+        values.insert("theme.variant".to_string(), Value::String("dark".into()));
+        values.insert("theme.ext".to_string(), Value::String("toml".into()));
+
+        let resolver = OptionResolver::new();
+        let directory = format!("{}/${{theme.variant}}", dir.path().display());
+        let source = FileListSource::new(directory, "*.${theme.ext}");
+        let options = resolver.resolve_file_list_source(&source, &values).unwrap();
+        assert_eq!(options, vec!["nord.toml"]);
+
+        let directory = dir.path().join("dark").display().to_string();
+        let options = resolver
+            .resolve_from_file_list(&directory, "*", Some(r"(\w+)\.toml$"))
+            .unwrap();
+        assert_eq!(options, vec!["nord"]);
+    }
+
+    #[test]
+    fn test_file_list_precedence_sorting_and_stripping() {
+        let user = tempfile::tempdir().unwrap();
+        let system = tempfile::tempdir().unwrap();
+        for name in ["theme10.toml", "theme2.toml", ".hidden.toml"] {
+            std::fs::write(user.path().join(name), "").unwrap();
+        }
+        for name in ["theme2.toml", "theme1.toml"] {
+            std::fs::write(system.path().join(name), "").unwrap();
+        }
+        std::fs::create_dir(system.path().join("nested")).unwrap();
+        std::fs::write(system.path().join("nested/deep.toml"), "").unwrap();
+
+        let mut source = FileListSource::new(user.path().display().to_string(), "*.toml");
+        source.directories = vec![system.path().display().to_string()];
+        source.strip_extension = true;
+        source.sort = FileListSort::Natural;
+
+        let resolver = OptionResolver::new();
+        let options = resolver
+            .resolve_file_list_source(&source, &HashMap::new())
+            .unwrap();
+        assert_eq!(options, vec!["theme1", "theme2", "theme10"]);
+
+        source.recursive = true;
+        source.sort = FileListSort::Name;
+        let options = resolver
+            .resolve_file_list_source(&source, &HashMap::new())
+            .unwrap();
+        assert_eq!(options, vec!["deep", "theme1", "theme10", "theme2"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_list_skips_broken_symlinks_and_filters_kinds() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("file"), "").unwrap();
+        std::fs::create_dir(dir.path().join("folder")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("missing"), dir.path().join("dangling"))
+            .unwrap();
+
+        let mut source = FileListSource::new(dir.path().display().to_string(), "*");
+        let resolver = OptionResolver::new();
+        let options = resolver
+            .resolve_file_list_source(&source, &HashMap::new())
+            .unwrap();
+        assert_eq!(options, vec!["file", "folder"]);
+        assert_eq!(resolver.take_warnings().len(), 1);
+
+        source.include_dirs = false;
+        let options = resolver
+            .resolve_file_list_source(&source, &HashMap::new())
+            .unwrap();
+        assert_eq!(options, vec!["file"]);
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        let mut names = vec!["img10", "img2", "Img1", "img02b"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, vec!["Img1", "img2", "img02b", "img10"]);
+    }
 }
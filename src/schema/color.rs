@@ -0,0 +1,242 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// How a `color` field is written to the config
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorFormat {
+    /// `#rrggbb`
+    #[default]
+    Hex,
+    /// `#rrggbbaa`
+    HexAlpha,
+    /// `rgb(r, g, b)`
+    Rgb,
+    /// `rgba(r, g, b, a)` with alpha in 0.0-1.0
+    Rgba,
+}
+
+impl ColorFormat {
+    pub fn has_alpha(&self) -> bool {
+        matches!(self, ColorFormat::HexAlpha | ColorFormat::Rgba)
+    }
+}
+
+/// An 8-bit RGBA color parsed from any of the accepted notations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorValue {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+const NAMED_COLORS: &[(&str, [u8; 4])] = &[
+    ("black", [0, 0, 0, 255]),
+    ("white", [255, 255, 255, 255]),
+    ("red", [255, 0, 0, 255]),
+    ("green", [0, 128, 0, 255]),
+    ("lime", [0, 255, 0, 255]),
+    ("blue", [0, 0, 255, 255]),
+    ("yellow", [255, 255, 0, 255]),
+    ("cyan", [0, 255, 255, 255]),
+    ("aqua", [0, 255, 255, 255]),
+    ("magenta", [255, 0, 255, 255]),
+    ("fuchsia", [255, 0, 255, 255]),
+    ("gray", [128, 128, 128, 255]),
+    ("grey", [128, 128, 128, 255]),
+    ("silver", [192, 192, 192, 255]),
+    ("maroon", [128, 0, 0, 255]),
+    ("olive", [128, 128, 0, 255]),
+    ("navy", [0, 0, 128, 255]),
+    ("purple", [128, 0, 128, 255]),
+    ("teal", [0, 128, 128, 255]),
+    ("orange", [255, 165, 0, 255]),
+    ("pink", [255, 192, 203, 255]),
+    ("brown", [165, 42, 42, 255]),
+    ("transparent", [0, 0, 0, 0]),
+];
+
+impl ColorValue {
+    pub fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    /// Parse `#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb(...)`, `rgba(...)` or a named color
+    pub fn parse(input: &str) -> Result<Self> {
+        let s = input.trim();
+
+        if let Some(hex) = s.strip_prefix('#') {
+            return Self::parse_hex(hex).ok_or_else(|| anyhow!("Invalid hex color: {}", input));
+        }
+
+        let lower = s.to_ascii_lowercase();
+        if let Some(args) = lower
+            .strip_prefix("rgba(")
+            .or_else(|| lower.strip_prefix("rgb("))
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return Self::parse_rgb_args(args)
+                .ok_or_else(|| anyhow!("Invalid rgb color: {}", input));
+        }
+
+        NAMED_COLORS
+            .iter()
+            .find(|(name, _)| *name == lower)
+            .map(|(_, [r, g, b, a])| Self {
+                r: *r,
+                g: *g,
+                b: *b,
+                a: *a,
+            })
+            .ok_or_else(|| anyhow!("Unknown color: {}", input))
+    }
+
+    fn parse_hex(hex: &str) -> Option<Self> {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        let nibble = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|n| n * 17);
+
+        match hex.len() {
+            3 => Some(Self::rgb(nibble(0)?, nibble(1)?, nibble(2)?)),
+            6 => Some(Self::rgb(byte(0)?, byte(2)?, byte(4)?)),
+            8 => Some(Self {
+                r: byte(0)?,
+                g: byte(2)?,
+                b: byte(4)?,
+                a: byte(6)?,
+            }),
+            _ => None,
+        }
+    }
+
+    fn parse_rgb_args(args: &str) -> Option<Self> {
+        let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+        let channel = |s: &str| s.parse::<u8>().ok();
+
+        match parts.as_slice() {
+            [r, g, b] => Some(Self::rgb(channel(r)?, channel(g)?, channel(b)?)),
+            [r, g, b, a] => {
+                let alpha = a.parse::<f64>().ok().filter(|a| (0.0..=1.0).contains(a))?;
+                Some(Self {
+                    r: channel(r)?,
+                    g: channel(g)?,
+                    b: channel(b)?,
+                    a: (alpha * 255.0).round() as u8,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn format(&self, format: ColorFormat) -> String {
+        match format {
+            ColorFormat::Hex => format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b),
+            ColorFormat::HexAlpha => {
+                format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+            }
+            ColorFormat::Rgb => format!("rgb({}, {}, {})", self.r, self.g, self.b),
+            ColorFormat::Rgba => {
+                let alpha = format!("{:.2}", self.a as f64 / 255.0);
+                let alpha = alpha.trim_end_matches('0').trim_end_matches('.');
+                let alpha = if alpha.is_empty() { "0" } else { alpha };
+                format!("rgba({}, {}, {}, {})", self.r, self.g, self.b, alpha)
+            }
+        }
+    }
+
+    /// Hue (0-360), saturation and value (0-100)
+    pub fn to_hsv(&self) -> (f64, f64, f64) {
+        let r = self.r as f64 / 255.0;
+        let g = self.g as f64 / 255.0;
+        let b = self.b as f64 / 255.0;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+
+        (hue, saturation * 100.0, max * 100.0)
+    }
+
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64, alpha: u8) -> Self {
+        let s = (saturation / 100.0).clamp(0.0, 1.0);
+        let v = (value / 100.0).clamp(0.0, 1.0);
+        let h = hue.rem_euclid(360.0);
+
+        let c = v * s;
+        let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+        let m = v - c;
+        let (r, g, b) = match (h / 60.0) as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let to_byte = |v: f64| ((v + m) * 255.0).round() as u8;
+
+        Self {
+            r: to_byte(r),
+            g: to_byte(g),
+            b: to_byte(b),
+            a: alpha,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notations() {
+        let expected = ColorValue::rgb(0x1e, 0x1e, 0x2e);
+        assert_eq!(ColorValue::parse("#1e1e2e").unwrap(), expected);
+        assert_eq!(ColorValue::parse("#1E1E2EFF").unwrap(), expected);
+        assert_eq!(ColorValue::parse("rgb(30, 30, 46)").unwrap(), expected);
+        assert_eq!(
+            ColorValue::parse("#fff").unwrap(),
+            ColorValue::rgb(255, 255, 255)
+        );
+        assert_eq!(
+            ColorValue::parse("Orange").unwrap(),
+            ColorValue::rgb(255, 165, 0)
+        );
+        assert_eq!(ColorValue::parse("rgba(0, 0, 0, 0.5)").unwrap().a, 128);
+
+        assert!(ColorValue::parse("#12345").is_err());
+        assert!(ColorValue::parse("rgb(300, 0, 0)").is_err());
+        assert!(ColorValue::parse("notacolor").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        let color = ColorValue::parse("#ff800080").unwrap();
+        assert_eq!(color.format(ColorFormat::Hex), "#ff8000");
+        assert_eq!(color.format(ColorFormat::HexAlpha), "#ff800080");
+        assert_eq!(color.format(ColorFormat::Rgb), "rgb(255, 128, 0)");
+        assert_eq!(color.format(ColorFormat::Rgba), "rgba(255, 128, 0, 0.5)");
+    }
+
+    #[test]
+    fn test_hsv_round_trip() {
+        for hex in ["#ff0000", "#1e1e2e", "#89b4fa", "#ffffff", "#000000"] {
+            let color = ColorValue::parse(hex).unwrap();
+            let (h, s, v) = color.to_hsv();
+            assert_eq!(ColorValue::from_hsv(h, s, v, 255), color, "{}", hex);
+        }
+    }
+}
@@ -1,7 +1,11 @@
+mod color;
 mod parser;
 mod types;
+mod units;
 mod validation;
 
+pub use color::*;
 pub use parser::*;
 pub use types::*;
+pub use units::*;
 pub use validation::*;
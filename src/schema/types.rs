@@ -1,3 +1,4 @@
+use super::{ByteUnit, ColorFormat, DurationUnit, Quantity, UnitStorage};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +22,21 @@ pub struct SchemaSection {
     pub fields: Vec<SchemaField>,
     #[serde(default)]
     pub visible_when: Option<String>,
+    /// Actions available on every field of the section
+    #[serde(default)]
+    pub actions: Vec<SchemaAction>,
+    /// Command run after any field of the section changes, see
+    /// [`SchemaField::on_change`]
+    #[serde(default)]
+    pub on_change: Option<String>,
+    /// Live-apply command for fields without their own, see
+    /// [`SchemaField::apply_command`]
+    #[serde(default)]
+    pub apply_command: Option<String>,
+    /// Countdown for fields without their own, see
+    /// [`SchemaField::confirm_timeout`]
+    #[serde(default)]
+    pub confirm_timeout: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,11 +57,69 @@ pub struct SchemaField {
     #[serde(default)]
     pub ui_widget: UIWidget,
 
+    /// Key that jumps to this field and starts editing it, e.g. `ctrl-t`
     #[serde(default)]
     pub keybind: Option<String>,
 
     #[serde(default)]
     pub subsection: Option<String>,
+
+    /// Actions available while the field is focused
+    #[serde(default)]
+    pub actions: Vec<SchemaAction>,
+
+    /// Command run through `sh -c` after the value is changed and saved,
+    /// e.g. `systemctl --user reload app`; with explicit saving that is
+    /// from the review screen
+    ///
+    /// Runs with `CHANGED_KEY` and `CURRENT_VALUE` set to the changed field
+    /// and every other field as `CONFIG_<SECTION>_<FIELD>`, like actions.
+    /// The editor waits for it to finish, also under `run_async`.
+    #[serde(default)]
+    pub on_change: Option<String>,
+
+    /// Command that makes a changed value take effect immediately, run
+    /// like `on_change`; if it fails the change is reverted
+    ///
+    /// Like `on_change` it waits for the save, so with explicit saving
+    /// changes are applied, and their countdown starts, from the review
+    /// screen rather than while editing.
+    #[serde(default)]
+    pub apply_command: Option<String>,
+
+    /// Seconds the user has to keep a live-applied change before it is
+    /// reverted and `apply_command` re-run with the previous value
+    ///
+    /// `0` applies the change without asking, even when the section sets a
+    /// countdown.
+    #[serde(default)]
+    pub confirm_timeout: Option<u64>,
+}
+
+/// A key-triggered action declared in the schema
+///
+/// Commands run through `sh -c` with `CURRENT_VALUE` set to the focused
+/// field's value and every other field as `CONFIG_<SECTION>_<FIELD>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaAction {
+    pub key: String,
+    pub label: String,
+    #[serde(flatten)]
+    pub kind: ActionKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActionKind {
+    /// Edit the value in `$EDITOR`, in a file with the given extension
+    Editor {
+        #[serde(default)]
+        extension: Option<String>,
+    },
+    /// The command's trimmed stdout becomes the field's new value
+    Command { command: String },
+    /// Run the command for its side effects and show its output
+    Run { command: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +141,9 @@ pub enum FieldType {
         min: Option<i64>,
         #[serde(default)]
         max: Option<i64>,
+        /// Unit label shown next to the value, e.g. `px`
+        #[serde(default)]
+        unit: Option<String>,
     },
 
     #[serde(rename = "float")]
@@ -79,6 +156,39 @@ pub enum FieldType {
         max: Option<f64>,
         #[serde(default)]
         step: Option<f64>,
+        /// Unit label shown next to the value, e.g. `%`
+        #[serde(default)]
+        unit: Option<String>,
+    },
+
+    /// Time span entered as e.g. `1m30s`; min/max take the same notation
+    #[serde(rename = "duration")]
+    Duration {
+        #[serde(default)]
+        default: Option<String>,
+        #[serde(default)]
+        unit: DurationUnit,
+        #[serde(default)]
+        store_as: UnitStorage,
+        #[serde(default)]
+        min: Option<String>,
+        #[serde(default)]
+        max: Option<String>,
+    },
+
+    /// Size entered as e.g. `512MiB`; min/max take the same notation
+    #[serde(rename = "byte_size")]
+    ByteSize {
+        #[serde(default)]
+        default: Option<String>,
+        #[serde(default)]
+        unit: ByteUnit,
+        #[serde(default)]
+        store_as: UnitStorage,
+        #[serde(default)]
+        min: Option<String>,
+        #[serde(default)]
+        max: Option<String>,
     },
 
     #[serde(rename = "boolean")]
@@ -89,6 +199,8 @@ pub enum FieldType {
         options_source: OptionSource,
         #[serde(default)]
         default: Option<String>,
+        #[serde(default)]
+        on_dependency_change: DependencyChangePolicy,
     },
 
     #[serde(rename = "path")]
@@ -100,6 +212,112 @@ pub enum FieldType {
         #[serde(default)]
         must_exist: bool,
     },
+
+    #[serde(rename = "color")]
+    Color {
+        #[serde(default)]
+        default: Option<String>,
+        #[serde(default)]
+        format: ColorFormat,
+        /// Preset colors offered by the picker
+        #[serde(default)]
+        palette: Option<OptionSource>,
+    },
+
+    #[serde(rename = "secret")]
+    Secret {
+        #[serde(default)]
+        storage: SecretStorage,
+        #[serde(default)]
+        max_length: Option<usize>,
+    },
+
+    /// List of values, edited one item per line
+    #[serde(rename = "array")]
+    Array {
+        #[serde(default)]
+        items: ArrayItems,
+        #[serde(default)]
+        default: Option<Vec<serde_json::Value>>,
+        #[serde(default)]
+        min_items: Option<usize>,
+        #[serde(default)]
+        max_items: Option<usize>,
+    },
+}
+
+/// Type of the items of an `array` field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrayItems {
+    #[default]
+    String,
+    Number,
+    Float,
+    Boolean,
+}
+
+impl ArrayItems {
+    /// Field type each item is validated and parsed as
+    pub fn field_type(self) -> FieldType {
+        match self {
+            ArrayItems::String => FieldType::String {
+                default: None,
+                max_length: None,
+            },
+            ArrayItems::Number => FieldType::Number {
+                default: None,
+                min: None,
+                max: None,
+                unit: None,
+            },
+            ArrayItems::Float => FieldType::Float {
+                default: None,
+                min: None,
+                max: None,
+                step: None,
+                unit: None,
+            },
+            ArrayItems::Boolean => FieldType::Boolean { default: false },
+        }
+    }
+}
+
+impl FieldType {
+    /// Source of the options (or palette) offered for this field, if any
+    pub fn option_source(&self) -> Option<&OptionSource> {
+        match self {
+            FieldType::Enum { options_source, .. } => Some(options_source),
+            FieldType::Color { palette, .. } => palette.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Quantity measured by a `duration` or `byte_size` field
+    pub fn quantity(&self) -> Option<Quantity> {
+        match self {
+            FieldType::Duration { unit, .. } => Some(Quantity::Duration(*unit)),
+            FieldType::ByteSize { unit, .. } => Some(Quantity::ByteSize(*unit)),
+            _ => None,
+        }
+    }
+}
+
+/// Where the value of a `secret` field is kept
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SecretStorage {
+    /// In the config file itself, which is then written with mode 0600
+    #[default]
+    Inline,
+    /// In a separate TOML file (mode 0600), keyed by the field's dot-notation key
+    File { path: String },
+    /// Read with `get`; written by piping the secret to `set`'s stdin
+    Command {
+        get: String,
+        #[serde(default)]
+        set: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -118,18 +336,116 @@ pub enum OptionSource {
     },
 
     #[serde(rename = "function")]
-    Function { name: String },
+    Function {
+        name: String,
+        #[serde(default)]
+        depends_on: Vec<String>,
+    },
 
     #[serde(rename = "provider")]
-    Provider { provider: String },
-
-    #[serde(rename = "file_list")]
-    FileList {
-        directory: String,
-        pattern: String,
+    Provider {
+        provider: String,
         #[serde(default)]
-        extract: Option<String>,
+        depends_on: Vec<String>,
     },
+
+    #[serde(rename = "file_list")]
+    FileList(FileListSource),
+}
+
+/// Options built from files matching a glob pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileListSource {
+    pub directory: String,
+    pub pattern: String,
+    /// Regex applied to the full path; capture group 1 becomes the option
+    #[serde(default)]
+    pub extract: Option<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Further directories searched after `directory`; a name found in an
+    /// earlier directory shadows the same name in later ones
+    #[serde(default)]
+    pub directories: Vec<String>,
+    /// Match `pattern` in every subdirectory as well
+    #[serde(default)]
+    pub recursive: bool,
+    #[serde(default)]
+    pub sort: FileListSort,
+    #[serde(default = "default_true")]
+    pub dedup: bool,
+    /// Drop the file extension from option names
+    #[serde(default)]
+    pub strip_extension: bool,
+    #[serde(default = "default_true")]
+    pub include_files: bool,
+    #[serde(default = "default_true")]
+    pub include_dirs: bool,
+    /// Include entries whose name starts with '.'
+    #[serde(default)]
+    pub include_hidden: bool,
+}
+
+impl FileListSource {
+    pub fn new(directory: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self {
+            directory: directory.into(),
+            pattern: pattern.into(),
+            extract: None,
+            depends_on: Vec::new(),
+            directories: Vec::new(),
+            recursive: false,
+            sort: FileListSort::default(),
+            dedup: true,
+            strip_extension: false,
+            include_files: true,
+            include_dirs: true,
+            include_hidden: false,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileListSort {
+    /// Keep the order in which directories and matches were found
+    None,
+    /// Lexicographic by option name
+    #[default]
+    Name,
+    /// Natural order, so "img2" sorts before "img10"
+    Natural,
+    /// Most recently modified first
+    Mtime,
+}
+
+impl OptionSource {
+    /// Field keys declared in the schema that this source depends on
+    pub fn depends_on(&self) -> &[String] {
+        match self {
+            OptionSource::Static { .. } => &[],
+            OptionSource::Script { depends_on, .. }
+            | OptionSource::Function { depends_on, .. }
+            | OptionSource::Provider { depends_on, .. }
+            | OptionSource::FileList(FileListSource { depends_on, .. }) => depends_on,
+        }
+    }
+}
+
+/// What to do with an enum value that is no longer among its options
+/// after one of its dependencies changed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyChangePolicy {
+    /// Keep the value but mark the field as invalid
+    #[default]
+    Flag,
+    /// Replace the value with the field default (or the first option)
+    Reset,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
@@ -142,6 +458,10 @@ pub enum UIWidget {
     Dropdown,
     DropdownSearchable,
     FilePicker,
+    /// Horizontal bar for `number`/`float` fields with both `min` and `max`
+    Slider,
+    /// Popup editor for multi-line `string` fields
+    Multiline,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -0,0 +1,320 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Base unit of a `duration` field, used for bare numbers and integer storage
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DurationUnit {
+    Milliseconds,
+    #[default]
+    Seconds,
+    Minutes,
+    Hours,
+}
+
+impl DurationUnit {
+    fn millis(&self) -> u64 {
+        match self {
+            DurationUnit::Milliseconds => 1,
+            DurationUnit::Seconds => 1_000,
+            DurationUnit::Minutes => 60_000,
+            DurationUnit::Hours => 3_600_000,
+        }
+    }
+}
+
+/// Base unit of a `byte_size` field, used for bare numbers and integer storage
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ByteUnit {
+    #[default]
+    Bytes,
+    Kilobytes,
+    Kibibytes,
+    Megabytes,
+    Mebibytes,
+    Gigabytes,
+    Gibibytes,
+}
+
+impl ByteUnit {
+    fn bytes(&self) -> u64 {
+        match self {
+            ByteUnit::Bytes => 1,
+            ByteUnit::Kilobytes => 1_000,
+            ByteUnit::Kibibytes => 1 << 10,
+            ByteUnit::Megabytes => 1_000_000,
+            ByteUnit::Mebibytes => 1 << 20,
+            ByteUnit::Gigabytes => 1_000_000_000,
+            ByteUnit::Gibibytes => 1 << 30,
+        }
+    }
+}
+
+/// How a duration or byte size is written to the config
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitStorage {
+    /// Integer count of the field's base unit
+    #[default]
+    Integer,
+    /// Canonical string such as `1m30s` or `512MiB`
+    String,
+}
+
+const DURATION_UNITS: &[(&str, u64)] = &[
+    ("d", 86_400_000),
+    ("h", 3_600_000),
+    ("m", 60_000),
+    ("s", 1_000),
+    ("ms", 1),
+];
+
+const BINARY_UNITS: &[(&str, u64)] = &[
+    ("TiB", 1 << 40),
+    ("GiB", 1 << 30),
+    ("MiB", 1 << 20),
+    ("KiB", 1 << 10),
+];
+
+const DECIMAL_UNITS: &[(&str, u64)] = &[
+    ("TB", 1_000_000_000_000),
+    ("GB", 1_000_000_000),
+    ("MB", 1_000_000),
+    ("kB", 1_000),
+];
+
+/// A physical quantity a numeric field is measured in
+///
+/// Amounts are held in the smallest unit: milliseconds for durations and
+/// bytes for sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    Duration(DurationUnit),
+    ByteSize(ByteUnit),
+}
+
+impl Quantity {
+    fn base(&self) -> u64 {
+        match self {
+            Quantity::Duration(unit) => unit.millis(),
+            Quantity::ByteSize(unit) => unit.bytes(),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Quantity::Duration(_) => "duration",
+            Quantity::ByteSize(_) => "size",
+        }
+    }
+
+    /// Parse human input such as `1m30s` or `512MiB`; bare numbers are in the base unit
+    pub fn parse(&self, input: &str) -> Result<u64> {
+        let s = input.trim();
+        if s.is_empty() {
+            return Err(anyhow!("Empty {}", self.name()));
+        }
+        if let Ok(n) = s.parse::<f64>() {
+            return scale(n, self.base()).ok_or_else(|| anyhow!("Invalid {}: {}", self.name(), s));
+        }
+
+        match self {
+            Quantity::Duration(_) => parse_duration(s),
+            Quantity::ByteSize(_) => parse_byte_size(s),
+        }
+        .ok_or_else(|| anyhow!("Invalid {}: {}", self.name(), s))
+    }
+
+    /// Read a stored value: an integer in the base unit or a human-readable string
+    pub fn from_value(&self, value: &Value) -> Result<u64> {
+        match value {
+            Value::Number(n) => n
+                .as_u64()
+                .and_then(|n| n.checked_mul(self.base()))
+                .ok_or_else(|| anyhow!("Invalid {}: {}", self.name(), n)),
+            Value::String(s) => self.parse(s),
+            _ => Err(anyhow!("{} must be a number or a string", self.name())),
+        }
+    }
+
+    /// Value to write to the config for `amount` in the smallest unit
+    pub fn to_value(&self, amount: u64, storage: UnitStorage) -> Result<Value> {
+        match storage {
+            UnitStorage::String => Ok(Value::String(self.canonical(amount))),
+            UnitStorage::Integer if amount.is_multiple_of(self.base()) => {
+                Ok(Value::from(amount / self.base()))
+            }
+            UnitStorage::Integer => Err(anyhow!(
+                "{} is not a whole number of {}",
+                self.canonical(amount),
+                self.base_name()
+            )),
+        }
+    }
+
+    fn base_name(&self) -> String {
+        let name = match self {
+            Quantity::Duration(unit) => serde_json::to_value(unit),
+            Quantity::ByteSize(unit) => serde_json::to_value(unit),
+        };
+        name.ok()
+            .and_then(|v| v.as_str().map(String::from))
+            .unwrap_or_default()
+    }
+
+    /// Exact, compact form, e.g. `1m30s` or `1536KiB`
+    pub fn canonical(&self, amount: u64) -> String {
+        match self {
+            Quantity::Duration(_) => format_duration(amount, ""),
+            Quantity::ByteSize(_) => {
+                if amount == 0 {
+                    return "0B".to_string();
+                }
+                BINARY_UNITS
+                    .iter()
+                    .chain(DECIMAL_UNITS)
+                    .find(|(_, size)| amount.is_multiple_of(*size))
+                    .map(|(suffix, size)| format!("{}{}", amount / size, suffix))
+                    .unwrap_or_else(|| format!("{}B", amount))
+            }
+        }
+    }
+
+    /// Readable, possibly rounded form, e.g. `1m 30s` or `1.5 MiB`
+    pub fn humanize(&self, amount: u64) -> String {
+        match self {
+            Quantity::Duration(_) => format_duration(amount, " "),
+            Quantity::ByteSize(_) => match BINARY_UNITS.iter().find(|(_, size)| amount >= *size) {
+                Some((suffix, size)) => {
+                    let scaled = format!("{:.1}", amount as f64 / *size as f64);
+                    format!("{} {}", scaled.trim_end_matches(".0"), suffix)
+                }
+                None => format!("{} B", amount),
+            },
+        }
+    }
+}
+
+fn scale(n: f64, factor: u64) -> Option<u64> {
+    let scaled = (n * factor as f64).round();
+    (n.is_finite() && n >= 0.0 && scaled <= u64::MAX as f64).then_some(scaled as u64)
+}
+
+/// Split `1.5GiB` into the number and the unit suffix
+fn split_number(s: &str) -> Option<(f64, &str)> {
+    let end = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let number = s[..end].parse::<f64>().ok()?;
+    Some((number, s[end..].trim_start()))
+}
+
+fn parse_duration(s: &str) -> Option<u64> {
+    let mut total: u64 = 0;
+    let mut rest = s;
+
+    while !rest.is_empty() {
+        let (number, tail) = split_number(rest)?;
+        let unit_len = tail
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(tail.len());
+        let unit = tail[..unit_len].to_ascii_lowercase();
+        let (_, millis) = DURATION_UNITS.iter().find(|(suffix, _)| *suffix == unit)?;
+
+        total = total.checked_add(scale(number, *millis)?)?;
+        rest = tail[unit_len..].trim_start();
+    }
+
+    Some(total)
+}
+
+// Single-letter and unqualified units (`512M`, `1g`) are binary, as in most
+// config files; `kB`/`MB`/`GB` are decimal
+fn parse_byte_size(s: &str) -> Option<u64> {
+    let (number, unit) = split_number(s)?;
+    let size = match unit.to_ascii_lowercase().as_str() {
+        "b" => 1,
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        "t" | "tib" => 1 << 40,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        _ => return None,
+    };
+    scale(number, size)
+}
+
+fn format_duration(millis: u64, separator: &str) -> String {
+    if millis == 0 {
+        return "0s".to_string();
+    }
+
+    let mut remaining = millis;
+    let mut parts = Vec::new();
+    for (suffix, size) in DURATION_UNITS {
+        if remaining >= *size {
+            parts.push(format!("{}{}", remaining / size, suffix));
+            remaining %= size;
+        }
+    }
+    parts.join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        let seconds = Quantity::Duration(DurationUnit::Seconds);
+        assert_eq!(seconds.parse("1m30s").unwrap(), 90_000);
+        assert_eq!(seconds.parse("1h 5m").unwrap(), 3_900_000);
+        assert_eq!(seconds.parse("250ms").unwrap(), 250);
+        assert_eq!(seconds.parse("1.5h").unwrap(), 5_400_000);
+        assert_eq!(seconds.parse("45").unwrap(), 45_000);
+        assert!(seconds.parse("1x").is_err());
+        assert!(seconds.parse("-5s").is_err());
+    }
+
+    #[test]
+    fn test_parse_byte_size() {
+        let bytes = Quantity::ByteSize(ByteUnit::Bytes);
+        assert_eq!(bytes.parse("512MiB").unwrap(), 512 << 20);
+        assert_eq!(bytes.parse("512 M").unwrap(), 512 << 20);
+        assert_eq!(bytes.parse("2GB").unwrap(), 2_000_000_000);
+        assert_eq!(bytes.parse("1.5k").unwrap(), 1536);
+        assert_eq!(bytes.parse("100").unwrap(), 100);
+        assert!(bytes.parse("5 parsecs").is_err());
+
+        let mebibytes = Quantity::ByteSize(ByteUnit::Mebibytes);
+        assert_eq!(mebibytes.parse("2").unwrap(), 2 << 20);
+    }
+
+    #[test]
+    fn test_canonical_and_storage() {
+        let seconds = Quantity::Duration(DurationUnit::Seconds);
+        assert_eq!(seconds.canonical(90_000), "1m30s");
+        assert_eq!(seconds.humanize(90_000), "1m 30s");
+        assert_eq!(
+            seconds.to_value(90_000, UnitStorage::Integer).unwrap(),
+            Value::from(90)
+        );
+        assert!(seconds.to_value(1_500, UnitStorage::Integer).is_err());
+        assert_eq!(
+            seconds.to_value(1_500, UnitStorage::String).unwrap(),
+            Value::from("1s500ms")
+        );
+
+        let bytes = Quantity::ByteSize(ByteUnit::Bytes);
+        assert_eq!(bytes.canonical(512 << 20), "512MiB");
+        assert_eq!(bytes.canonical(3_000_000), "3MB");
+        assert_eq!(bytes.canonical(1_000_001), "1000001B");
+        assert_eq!(bytes.humanize(1536 << 10), "1.5 MiB");
+        assert_eq!(bytes.from_value(&Value::from("1GiB")).unwrap(), 1 << 30);
+    }
+}
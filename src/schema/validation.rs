@@ -1,4 +1,4 @@
-use super::{ConfigSchema, FieldType};
+use super::{ColorValue, ConfigSchema, FieldType, Quantity};
 use anyhow::{anyhow, Result};
 use serde_json::Value;
 
@@ -69,6 +69,34 @@ impl SchemaValidator {
                 }
             }
 
+            FieldType::Secret { max_length, .. } => {
+                let s = value
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Secret must be a string"))?;
+
+                // References are checked when the secret itself is stored
+                if crate::config::SecretRef::parse(s).is_none() {
+                    if let Some(max) = max_length {
+                        if s.len() > *max {
+                            return Err(anyhow!("Secret exceeds max length of {}", max));
+                        }
+                    }
+                }
+            }
+
+            FieldType::Duration { min, max, .. } | FieldType::ByteSize { min, max, .. } => {
+                if let Some(quantity) = field_type.quantity() {
+                    Self::validate_quantity(quantity, value, min.as_deref(), max.as_deref())?;
+                }
+            }
+
+            FieldType::Color { .. } => {
+                let s = value
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Color must be a string"))?;
+                ColorValue::parse(s)?;
+            }
+
             FieldType::Boolean { .. } => {
                 if !value.is_boolean() {
                     return Err(anyhow!("Value must be a boolean"));
@@ -93,6 +121,132 @@ impl SchemaValidator {
                     }
                 }
             }
+
+            FieldType::Array {
+                items,
+                min_items,
+                max_items,
+                ..
+            } => {
+                let values = value
+                    .as_array()
+                    .ok_or_else(|| anyhow!("Value must be an array"))?;
+
+                if let Some(min) = min_items {
+                    if values.len() < *min {
+                        return Err(anyhow!("Array needs at least {} items", min));
+                    }
+                }
+
+                if let Some(max) = max_items {
+                    if values.len() > *max {
+                        return Err(anyhow!("Array exceeds max of {} items", max));
+                    }
+                }
+
+                let item_type = items.field_type();
+                for (index, item) in values.iter().enumerate() {
+                    Self::validate_value(&item_type, item)
+                        .map_err(|e| anyhow!("Item {}: {}", index + 1, e))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Turn text, e.g. from a command or an editor, into a normalized value
+    pub fn parse_text(field_type: &FieldType, text: &str) -> Result<Value> {
+        let trimmed = text.trim();
+        let value = match field_type {
+            FieldType::Number { .. } => Value::from(
+                trimmed
+                    .parse::<i64>()
+                    .map_err(|_| anyhow!("Value must be a number"))?,
+            ),
+            FieldType::Float { .. } => Value::from(
+                trimmed
+                    .parse::<f64>()
+                    .map_err(|_| anyhow!("Value must be a number"))?,
+            ),
+            FieldType::Boolean { .. } => match trimmed.to_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Value::Bool(true),
+                "false" | "no" | "off" | "0" => Value::Bool(false),
+                _ => return Err(anyhow!("Value must be a boolean")),
+            },
+            FieldType::String { .. } | FieldType::Secret { .. } => Value::String(text.to_string()),
+            // One item per line, blank lines are skipped
+            FieldType::Array { items, .. } => {
+                let item_type = items.field_type();
+                Value::Array(
+                    text.lines()
+                        .filter(|line| !line.trim().is_empty())
+                        .enumerate()
+                        .map(|(index, line)| {
+                            Self::parse_text(&item_type, line.trim())
+                                .map_err(|e| anyhow!("Item {}: {}", index + 1, e))
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            _ => Value::String(trimmed.to_string()),
+        };
+        Self::normalize_value(field_type, &value)
+    }
+
+    /// Text form of a value, as read back by [`Self::parse_text`]
+    pub fn format_text(value: &Value) -> String {
+        match value {
+            Value::String(s) => s.clone(),
+            Value::Array(items) => items
+                .iter()
+                .map(Self::format_text)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        }
+    }
+
+    /// Validate a value and convert it to the canonical form for its field
+    ///
+    /// Colors are rewritten in the field's configured `format` and durations
+    /// and sizes in their `store_as` form; all other values are returned
+    /// unchanged.
+    pub fn normalize_value(field_type: &FieldType, value: &Value) -> Result<Value> {
+        Self::validate_value(field_type, value)?;
+
+        match (field_type, value) {
+            (FieldType::Color { format, .. }, Value::String(s)) => {
+                Ok(Value::String(ColorValue::parse(s)?.format(*format)))
+            }
+            (FieldType::Duration { store_as, .. }, _)
+            | (FieldType::ByteSize { store_as, .. }, _) => match field_type.quantity() {
+                Some(quantity) => quantity.to_value(quantity.from_value(value)?, *store_as),
+                None => Ok(value.clone()),
+            },
+            _ => Ok(value.clone()),
+        }
+    }
+
+    fn validate_quantity(
+        quantity: Quantity,
+        value: &Value,
+        min: Option<&str>,
+        max: Option<&str>,
+    ) -> Result<()> {
+        let amount = quantity.from_value(value)?;
+
+        if let Some(min) = min {
+            if amount < quantity.parse(min)? {
+                return Err(anyhow!("Value is below minimum of {}", min));
+            }
+        }
+
+        if let Some(max) = max {
+            if amount > quantity.parse(max)? {
+                return Err(anyhow!("Value exceeds maximum of {}", max));
+            }
         }
 
         Ok(())
@@ -1,84 +1,440 @@
-use anyhow::Result;
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use anyhow::{anyhow, Context, Result};
+use crossterm::{
+    cursor::{Hide, Show},
+    event::DisableMouseCapture,
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::fs::{DirBuilder, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Field-specific actions that can be triggered by keybinds
 #[derive(Debug, Clone)]
 pub enum FieldAction {
     /// Open external editor for the field value
     ExternalEditor { editor: String, extension: String },
-    /// Custom shell command
+    /// Custom shell command whose output becomes the new value
     CustomCommand { command: String },
+    /// Shell command run for its side effects
+    RunCommand { command: String },
+}
+
+/// What running a [`FieldAction`] produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionOutput {
+    Unchanged,
+    /// New value for the field
+    Value(String),
+    /// Output of a side-effect command, to show the user
+    Output(String),
 }
 
 impl FieldAction {
     /// Execute the action for a given value, returning the new value if changed
+    ///
+    /// A command exiting with an error leaves the value unchanged; use
+    /// [`FieldAction::run`] to have the failure reported instead.
     pub fn execute(&self, current_value: &str) -> Result<Option<String>> {
+        match self.run(current_value, &[]) {
+            Ok(ActionOutput::Value(value)) => Ok(Some(value)),
+            Ok(_) => Ok(None),
+            Err(e) if e.is::<CommandFailed>() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Execute the action with extra environment variables for commands
+    pub fn run(&self, current_value: &str, env: &[(String, String)]) -> Result<ActionOutput> {
         match self {
             FieldAction::ExternalEditor { editor, extension } => {
-                execute_external_editor(current_value, editor, extension)
+                // Editors end the file with a newline the value never had
+                let edited = execute_external_editor(current_value, editor, extension)?
+                    .map(|text| strip_final_newline(&text).to_string());
+                Ok(match edited {
+                    Some(value) if value != current_value => ActionOutput::Value(value),
+                    _ => ActionOutput::Unchanged,
+                })
             }
             FieldAction::CustomCommand { command } => {
-                execute_custom_command(command, current_value)
+                let output = execute_custom_command(command, current_value, env)?;
+                let new_value = output.trim();
+                if new_value != current_value && !new_value.is_empty() {
+                    Ok(ActionOutput::Value(new_value.to_string()))
+                } else {
+                    Ok(ActionOutput::Unchanged)
+                }
+            }
+            FieldAction::RunCommand { command } => Ok(ActionOutput::Output(
+                execute_custom_command(command, current_value, env)?,
+            )),
+        }
+    }
+}
+
+/// Environment variable holding a field's value for action commands,
+/// e.g. `CONFIG_GENERAL_FONT_SIZE` for `general.font-size`
+pub fn env_var_name(field_key: &str) -> String {
+    let name: String = field_key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("CONFIG_{}", name)
+}
+
+fn strip_final_newline(text: &str) -> &str {
+    text.strip_suffix('\n')
+        .map(|t| t.strip_suffix('\r').unwrap_or(t))
+        .unwrap_or(text)
+}
+
+/// The user's editor command: `$VISUAL`, then `$EDITOR`, then `vi`
+pub fn default_editor() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Split an editor command such as `code --wait` into program and arguments
+///
+/// Follows the usual shell rules for single quotes, double quotes and
+/// backslash escapes, so paths with spaces can be quoted.
+pub fn split_command(command: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow!("Unterminated quote in '{}'", command)),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(anyhow!("Unterminated quote in '{}'", command)),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow!("Unterminated quote in '{}'", command)),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(c) = chars.next() {
+                    word.push(c);
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
             }
         }
     }
+    if in_word {
+        words.push(word);
+    }
+    if words.is_empty() {
+        return Err(anyhow!("Editor command is empty"));
+    }
+    Ok(words)
 }
 
 fn execute_external_editor(content: &str, editor: &str, extension: &str) -> Result<Option<String>> {
-    // Create temp file
-    let temp_dir = std::env::temp_dir();
-    let temp_file = temp_dir.join(format!("schema-tui-edit.{}", extension));
-    std::fs::write(&temp_file, content)?;
+    edit_in_editor(content, editor, extension, None)
+}
+
+/// Hand the terminal to `editor` to edit `content`, optionally starting at `line`
+pub(crate) fn edit_in_editor(
+    content: &str,
+    editor: &str,
+    extension: &str,
+    line: Option<usize>,
+) -> Result<Option<String>> {
+    // Check the command before touching the terminal
+    split_command(editor)?;
+    let _suspended = SuspendedTerminal::new()?;
+    edit_text(content, editor, extension, line)
+}
 
-    // Disable raw mode for editor
-    disable_raw_mode()?;
+/// Hand the terminal to `editor` to edit `path` in place, returning whether
+/// the editor exited successfully
+pub(crate) fn edit_file_in_editor(path: &Path, editor: &str, line: Option<usize>) -> Result<bool> {
+    split_command(editor)?;
+    let _suspended = SuspendedTerminal::new()?;
+    run_editor(editor, path, line)
+}
 
-    // Launch editor
-    let status = Command::new(editor).arg(&temp_file).status();
+/// Run `editor` on a private temp copy of `content` and read the result back
+///
+/// Returns `None` when the editor exits with an error or leaves the text
+/// unchanged. The caller is responsible for handing over the terminal.
+fn edit_text(
+    content: &str,
+    editor: &str,
+    extension: &str,
+    line: Option<usize>,
+) -> Result<Option<String>> {
+    let dir = TempDir::new()?;
+    let file = dir.path.join(format!("edit.{}", extension));
+    write_private(&file, content)?;
 
-    // Re-enable raw mode
-    enable_raw_mode()?;
+    if !run_editor(editor, &file, line)? {
+        return Ok(None);
+    }
+    let new_content = std::fs::read_to_string(&file)?;
+    Ok((new_content != content).then_some(new_content))
+}
 
-    match status {
-        Ok(exit_status) if exit_status.success() => {
-            // Read modified content
-            let new_content = std::fs::read_to_string(&temp_file)?;
-            std::fs::remove_file(&temp_file).ok();
+fn run_editor(editor: &str, path: &Path, line: Option<usize>) -> Result<bool> {
+    let words = split_command(editor)?;
+    let mut command = Command::new(&words[0]);
+    command.args(&words[1..]);
 
-            if new_content != content {
-                Ok(Some(new_content))
-            } else {
-                Ok(None)
-            }
+    let program = Path::new(&words[0])
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    match (line, program) {
+        (Some(line), "code" | "code-insiders" | "codium") => {
+            command
+                .arg("--goto")
+                .arg(format!("{}:{}", path.display(), line));
         }
-        Ok(_) => {
-            std::fs::remove_file(&temp_file).ok();
-            Ok(None)
+        (Some(line), "subl") => {
+            command.arg(format!("{}:{}", path.display(), line));
         }
-        Err(e) => {
-            std::fs::remove_file(&temp_file).ok();
-            Err(e.into())
+        // vi, nano, emacs, micro and kak all accept `+LINE`
+        (Some(line), _) => {
+            command.arg(format!("+{}", line)).arg(path);
+        }
+        (None, _) => {
+            command.arg(path);
+        }
+    }
+
+    let status = command
+        .status()
+        .with_context(|| format!("Failed to launch editor '{}'", words[0]))?;
+    Ok(status.success())
+}
+
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+// Directory only the current user can enter, removed with its contents on drop
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new() -> Result<Self> {
+        let base = std::env::temp_dir();
+        for attempt in 0..100u32 {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.subsec_nanos())
+                .unwrap_or_default();
+            let path = base.join(format!(
+                "schema-tui-{}-{}-{}",
+                std::process::id(),
+                nanos,
+                attempt
+            ));
+            let mut builder = DirBuilder::new();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::DirBuilderExt;
+                builder.mode(0o700);
+            }
+            match builder.create(&path) {
+                Ok(()) => return Ok(Self { path }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e.into()),
+            }
         }
+        Err(anyhow!(
+            "Could not create a temp directory in {}",
+            base.display()
+        ))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.path).ok();
+    }
+}
+
+// Hands the terminal to a child process and takes it back on drop, even if
+// the child could not be started
+struct SuspendedTerminal;
+
+impl SuspendedTerminal {
+    fn new() -> Result<Self> {
+        execute!(
+            io::stdout(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            Show
+        )?;
+        disable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for SuspendedTerminal {
+    fn drop(&mut self) {
+        enable_raw_mode().ok();
+        execute!(io::stdout(), EnterAlternateScreen, Hide).ok();
     }
 }
 
-fn execute_custom_command(command: &str, current_value: &str) -> Result<Option<String>> {
-    // Set current value as environment variable
+/// Run a command through `sh -c` and return its stdout
+fn execute_custom_command(
+    command: &str,
+    current_value: &str,
+    env: &[(String, String)],
+) -> Result<String> {
     let output = Command::new("sh")
         .arg("-c")
         .arg(command)
         .env("CURRENT_VALUE", current_value)
+        .envs(env.iter().map(|(k, v)| (k, v)))
         .output()?;
 
-    if output.status.success() {
-        let new_value = String::from_utf8(output.stdout)?.trim().to_string();
-        if new_value != current_value && !new_value.is_empty() {
-            Ok(Some(new_value))
-        } else {
-            Ok(None)
+    if !output.status.success() {
+        return Err(CommandFailed {
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
         }
-    } else {
-        Ok(None)
+        .into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// An action command exited with an error
+#[derive(Debug, thiserror::Error)]
+#[error("Command failed ({status}): {stderr}")]
+struct CommandFailed {
+    status: std::process::ExitStatus,
+    stderr: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_see_field_values() {
+        let env = vec![(env_var_name("display.font-size"), "12".to_string())];
+        assert_eq!(env[0].0, "CONFIG_DISPLAY_FONT_SIZE");
+
+        let action = FieldAction::CustomCommand {
+            command: "echo $((CURRENT_VALUE + CONFIG_DISPLAY_FONT_SIZE))".to_string(),
+        };
+        assert_eq!(
+            action.run("3", &env).unwrap(),
+            ActionOutput::Value("15".to_string())
+        );
+
+        let run = FieldAction::RunCommand {
+            command: "echo done; exit 0".to_string(),
+        };
+        assert_eq!(
+            run.run("", &[]).unwrap(),
+            ActionOutput::Output("done\n".to_string())
+        );
+
+        let failing = FieldAction::RunCommand {
+            command: "echo oops >&2; exit 3".to_string(),
+        };
+        assert!(failing
+            .run("", &[])
+            .unwrap_err()
+            .to_string()
+            .contains("oops"));
+    }
+
+    #[test]
+    fn test_execute_ignores_failing_commands() {
+        let failing = FieldAction::CustomCommand {
+            command: "echo new; exit 1".to_string(),
+        };
+        assert_eq!(failing.execute("old").unwrap(), None);
+        assert!(failing.run("old", &[]).is_err());
+
+        let action = FieldAction::CustomCommand {
+            command: "echo new".to_string(),
+        };
+        assert_eq!(action.execute("old").unwrap().as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn test_split_editor_command() {
+        assert_eq!(split_command("code --wait").unwrap(), ["code", "--wait"]);
+        assert_eq!(
+            split_command(r#"'/opt/My Editor/bin/ed' -c "set ft=\"toml\"" a\ b"#).unwrap(),
+            ["/opt/My Editor/bin/ed", "-c", "set ft=\"toml\"", "a b"]
+        );
+        assert!(split_command("  ").is_err());
+        assert!(split_command("vim 'oops").is_err());
+    }
+
+    #[test]
+    fn test_edit_text_uses_private_temp_file() {
+        // The "editor" records the file's mode and appends to it
+        let script = "ls -l \"$1\" | cut -c1-10 > \"$0\"; echo more >> \"$1\"";
+        let record = tempfile::NamedTempFile::new().unwrap();
+        let editor = format!("sh -c '{}' {}", script, record.path().display());
+        let edited = edit_text("text\n", &editor, "txt", None).unwrap();
+        assert_eq!(edited.as_deref(), Some("text\nmore\n"));
+        assert_eq!(
+            std::fs::read_to_string(record.path()).unwrap().trim(),
+            "-rw-------"
+        );
+
+        assert_eq!(edit_text("same", "true", "txt", None).unwrap(), None);
+        assert_eq!(edit_text("same", "false", "txt", None).unwrap(), None);
     }
 }
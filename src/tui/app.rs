@@ -1,26 +1,124 @@
+use super::actions::{
+    default_editor, edit_file_in_editor, edit_in_editor, env_var_name, ActionOutput, FieldAction,
+};
+use super::confirm::ConfirmDialog;
+use super::conflict::{ConflictAction, ConflictDialog};
+use super::events::{AfterChange, BeforeChange, ChangeEvent, ChangeSource, HostCommand};
+use super::help::HelpDialog;
+use super::keymap::{KeyBinding, Keymap};
+use super::live_apply::LiveApplyDialog;
+use super::output::OutputDialog;
+use super::palette::{Command, CommandPalette, PaletteAction, PaletteEntry};
+use super::raw_edit::{check_config_text, RawEditAction, RawEditDialog, RawError};
+use super::restore::{RestoreAction, RestoreDialog};
+use super::review::{PendingChange, ReviewAction, ReviewDialog};
+use super::search::{SearchAction, SearchDialog, SearchEntry};
 use super::theme::Theme;
 use super::widgets::*;
-use crate::options::OptionResolver;
-use crate::schema::{ConfigSchema, FieldType, SchemaField, SchemaSection, UIWidget};
+use crate::config::{
+    create_backup, diff_lines, list_backups, merge_values, restore_backup, store_secret,
+    write_atomic, write_atomic_private, BackupPolicy, ConfigLoader, ConfigSaver, ConfigStore,
+    ConfigWatcher, DiffLine, SecretRef,
+};
+use crate::options::{block_on, OptionResolver};
+use crate::schema::{
+    ActionKind, ColorValue, ConfigSchema, DependencyChangePolicy, FieldType, FileTypeFilter,
+    OptionSource, SchemaAction, SchemaField, SchemaSection, SchemaValidator, UIWidget,
+};
 use anyhow::Result;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyCode, KeyEvent,
+        KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use futures::StreamExt;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Tabs},
     Frame, Terminal,
 };
 use serde_json::Value;
-use std::collections::HashMap;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::MissedTickBehavior;
 
 type ChangeHandler = Box<dyn Fn(&str, &Value) + Send>;
+type BeforeHook = Box<dyn Fn(&str, &Value) -> BeforeChange + Send>;
+type AfterHook = Box<dyn Fn(&str, &Value) -> AfterChange + Send>;
+
+// Snapshots kept for undo
+const UNDO_LIMIT: usize = 100;
+
+// Longest gap between the two clicks of a double click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Options resolved in the background to re-check a dependent field
+struct OptionCheck {
+    field_key: String,
+    generation: u64,
+    // Source of the change that triggered the check, for resulting resets
+    source: ChangeSource,
+    options: Result<Vec<String>>,
+}
+
+/// A dependent field to re-check, with the values to resolve its options for
+struct OptionJob {
+    check: OptionCheck,
+    source: OptionSource,
+    values: HashMap<String, Value>,
+}
+
+// Change events buffered for each subscriber
+const CHANGE_CAPACITY: usize = 256;
+
+/// Screen areas from the last render, for mouse hit testing
+#[derive(Default)]
+struct HitAreas {
+    /// Everything the editor draws into
+    pane: Rect,
+    tabs: Rect,
+    /// Each visible tab with the section it shows
+    tab_titles: Vec<(Rect, usize)>,
+    fields: Rect,
+    /// Screen row of each visible field
+    field_rows: Vec<(u16, usize)>,
+}
+
+/// What handling an event did, for hosts embedding the editor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The editor used the event
+    Consumed,
+    /// The event means nothing to the editor; the host may handle it
+    Ignored,
+    /// The user asked to leave the editor
+    QuitRequested,
+    /// These settings changed, in key order
+    Changed(Vec<String>),
+}
+
+/// When edits are written to the config file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SaveMode {
+    /// Every confirmed change is saved immediately
+    #[default]
+    Auto,
+    /// Changes are kept in memory until saved from the review screen
+    /// (Ctrl-S); change hooks, `on_change` and live-apply run on that save
+    Explicit,
+}
 
 pub struct SchemaTUI {
     // Core data
@@ -28,6 +126,17 @@ pub struct SchemaTUI {
     values: HashMap<String, Value>,
     config_path: Option<std::path::PathBuf>,
 
+    // Backups; the config is snapshotted once per session, before the first save
+    backup_policy: Option<BackupPolicy>,
+    backed_up: Cell<bool>,
+    save_mode: SaveMode,
+    // Settings at their schema default are left out of the saved file
+    omit_defaults: bool,
+
+    // External changes; `base_values` is what the file held when last synced
+    watcher: Option<ConfigWatcher>,
+    base_values: HashMap<String, Value>,
+
     // UI state
     current_section: usize,
     current_field: usize,
@@ -38,18 +147,68 @@ pub struct SchemaTUI {
     active_field: Option<String>,
     active_widgets: HashMap<String, Box<dyn Widget>>,
 
-    // Event system
-    change_handlers: Vec<ChangeHandler>,
+    // Fields whose value is no longer among their options, with the reason
+    invalid_fields: HashMap<String, String>,
 
-    // Options
-    option_resolver: OptionResolver,
+    // Value snapshots before each change; `undo_key` is the field whose
+    // live edits are folded into the newest snapshot
+    undo_stack: Vec<HashMap<String, Value>>,
+    undo_key: Option<String>,
+    // Field showing a live value that isn't confirmed yet, with its value
+    // from before the edit
+    preview: Option<(String, Option<Value>)>,
 
-    // Theme
+    // Event system
+    change_handlers: Vec<ChangeHandler>,
+    before_hooks: Vec<BeforeHook>,
+    after_hooks: Vec<AfterHook>,
+    // Keys changed by the user or host whose after-change hooks and
+    // `on_change` commands haven't run yet, with their value before
+    pending_effects: Vec<(String, Option<Value>)>,
+    // Live-applied keys being put back; applied again without confirmation
+    reverting: HashSet<String>,
+    change_tx: broadcast::Sender<ChangeEvent>,
+    commands_tx: mpsc::UnboundedSender<HostCommand>,
+    commands_rx: mpsc::UnboundedReceiver<HostCommand>,
+
+    // Options; dependent fields are re-checked as tasks on one background
+    // thread and the results applied if their generation is still the latest
+    option_resolver: Arc<OptionResolver>,
+    option_generations: HashMap<String, u64>,
+    option_jobs: Option<mpsc::UnboundedSender<OptionJob>>,
+    option_checks_tx: mpsc::UnboundedSender<OptionCheck>,
+    option_checks_rx: mpsc::UnboundedReceiver<OptionCheck>,
+
+    keymap: Keymap,
+
+    // Theme; `theme_index` cycles through the configured, dark and light themes
     theme: Theme,
+    configured_theme: Theme,
+    theme_index: usize,
+
+    // Overlays
+    restore_dialog: Option<RestoreDialog>,
+    conflict_dialog: Option<ConflictDialog>,
+    review_dialog: Option<ReviewDialog>,
+    search_dialog: Option<SearchDialog>,
+    palette: Option<CommandPalette>,
+    help_dialog: Option<HelpDialog>,
+    output_dialog: Option<OutputDialog>,
+    confirm_dialog: Option<ConfirmDialog>,
+    raw_edit_dialog: Option<RawEditDialog>,
+    live_apply_dialog: Option<LiveApplyDialog>,
 
     // Status
     message: Option<String>,
     should_quit: bool,
+    // Set after a first `q` with unsaved changes in explicit-save mode
+    quit_armed: bool,
+    // Set after an external program used the terminal
+    needs_redraw: bool,
+    mouse: bool,
+    hit_areas: HitAreas,
+    // Time and position of the last click, to detect double clicks
+    last_click: Option<(Instant, usize, usize)>,
 }
 
 impl SchemaTUI {
@@ -63,36 +222,102 @@ impl SchemaTUI {
         let mut list_state = ListState::default();
         list_state.select(Some(0));
 
-        // Merge defaults from schema with initial values
         let mut values = initial_values;
-        for section in &schema.sections {
-            for field in &section.fields {
-                let field_key = format!("{}.{}", section.id, field.id);
-
-                // Only set default if value not already present
-                if let std::collections::hash_map::Entry::Vacant(e) = values.entry(field_key) {
-                    if let Some(default_value) = Self::get_field_default(&field.field_type) {
-                        e.insert(default_value);
-                    }
-                }
-            }
-        }
+        Self::merge_defaults(&schema, &mut values);
+        let watcher = config_path.as_ref().map(ConfigWatcher::new);
+        let (commands_tx, commands_rx) = mpsc::unbounded_channel();
+        let (option_checks_tx, option_checks_rx) = mpsc::unbounded_channel();
 
         Self {
             schema,
+            base_values: values.clone(),
             values,
             config_path,
+            backup_policy: None,
+            backed_up: Cell::new(false),
+            save_mode: SaveMode::default(),
+            omit_defaults: false,
+            watcher,
             current_section: 0,
             current_field: 0,
             list_state,
             edit_mode: false,
             active_field: None,
             active_widgets: HashMap::new(),
+            invalid_fields: HashMap::new(),
+            keymap: Keymap::default(),
+            undo_stack: Vec::new(),
+            undo_key: None,
+            preview: None,
             change_handlers: Vec::new(),
-            option_resolver,
+            before_hooks: Vec::new(),
+            after_hooks: Vec::new(),
+            pending_effects: Vec::new(),
+            reverting: HashSet::new(),
+            change_tx: broadcast::channel(CHANGE_CAPACITY).0,
+            commands_tx,
+            commands_rx,
+            option_resolver: Arc::new(option_resolver),
+            option_generations: HashMap::new(),
+            option_jobs: None,
+            option_checks_tx,
+            option_checks_rx,
+            configured_theme: theme.clone(),
             theme,
+            theme_index: 0,
+            restore_dialog: None,
+            conflict_dialog: None,
+            review_dialog: None,
+            search_dialog: None,
+            palette: None,
+            help_dialog: None,
+            output_dialog: None,
+            confirm_dialog: None,
+            raw_edit_dialog: None,
+            live_apply_dialog: None,
             message: None,
             should_quit: false,
+            quit_armed: false,
+            needs_redraw: false,
+            mouse: true,
+            hit_areas: HitAreas::default(),
+            last_click: None,
+        }
+    }
+
+    pub(crate) fn set_backup_policy(&mut self, policy: BackupPolicy) {
+        self.backup_policy = Some(policy);
+    }
+
+    pub(crate) fn set_save_mode(&mut self, mode: SaveMode) {
+        self.save_mode = mode;
+    }
+
+    pub(crate) fn set_omit_defaults(&mut self, omit: bool) {
+        self.omit_defaults = omit;
+    }
+
+    pub(crate) fn set_mouse(&mut self, enabled: bool) {
+        self.mouse = enabled;
+    }
+
+    pub(crate) fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    /// Merge defaults from the schema into values that aren't set
+    fn merge_defaults(schema: &ConfigSchema, values: &mut HashMap<String, Value>) {
+        for section in &schema.sections {
+            for field in &section.fields {
+                let field_key = format!("{}.{}", section.id, field.id);
+
+                // Only set default if value not already present
+                if let std::collections::hash_map::Entry::Vacant(e) = values.entry(field_key) {
+                    if let Some(default_value) = Self::get_field_default(&field.field_type) {
+                        e.insert(default_value);
+                    }
+                }
+            }
         }
     }
 
@@ -105,6 +330,14 @@ impl SchemaTUI {
             FieldType::Number { default, .. } => default.map(|n| Value::Number(n.into())),
             FieldType::Float { default, .. } => default.map(Value::from),
             FieldType::Enum { default, .. } => default.as_ref().map(|s| Value::String(s.clone())),
+            FieldType::Color { default, .. } => default.as_ref().map(|s| Value::String(s.clone())),
+            FieldType::Duration { default, .. } | FieldType::ByteSize { default, .. } => {
+                default.as_ref().and_then(|d| {
+                    SchemaValidator::normalize_value(field_type, &Value::String(d.clone())).ok()
+                })
+            }
+            FieldType::Array { default, .. } => default.clone().map(Value::Array),
+            FieldType::Secret { .. } => None,
         }
     }
 
@@ -115,6 +348,76 @@ impl SchemaTUI {
         self.change_handlers.push(Box::new(handler));
     }
 
+    /// Check a value entered in the editor or pushed by the host before it
+    /// is stored
+    ///
+    /// Hooks run in the order they were added; a replaced value is
+    /// validated against the schema and passed on to the next hook. Values
+    /// shown live while a field is edited are checked once it is confirmed.
+    ///
+    /// Undo, resets, reloads, hand edits of the config file and conflict
+    /// resolution put whole sets of values back and don't run the hooks.
+    pub fn before_change<F>(&mut self, hook: F)
+    where
+        F: Fn(&str, &Value) -> BeforeChange + Send + 'static,
+    {
+        self.before_hooks.push(Box::new(hook));
+    }
+
+    /// Async variant of [`before_change`](Self::before_change); the editor
+    /// waits for the future
+    pub fn before_change_async<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(String, Value) -> Fut + Send + 'static,
+        Fut: Future<Output = BeforeChange> + Send,
+    {
+        self.before_change(move |key, value| {
+            block_on(hook(key.to_string(), value.clone()))
+                .unwrap_or_else(|e| BeforeChange::Reject(e.to_string()))
+        });
+    }
+
+    /// Apply a saved change made by the user or host, e.g. tell a daemon
+    /// to reload; the returned status is shown in the footer
+    ///
+    /// Runs once editing of the field is finished and the change is saved,
+    /// not on every keystroke; in [`SaveMode::Explicit`] that is from the
+    /// review screen.
+    pub fn after_change<F>(&mut self, hook: F)
+    where
+        F: Fn(&str, &Value) -> AfterChange + Send + 'static,
+    {
+        self.after_hooks.push(Box::new(hook));
+    }
+
+    /// Async variant of [`after_change`](Self::after_change); the editor
+    /// waits for the future
+    pub fn after_change_async<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(String, Value) -> Fut + Send + 'static,
+        Fut: Future<Output = AfterChange> + Send,
+    {
+        self.after_change(move |key, value| {
+            block_on(hook(key.to_string(), value.clone()))
+                .unwrap_or_else(|e| AfterChange::Failed(e.to_string()))
+        });
+    }
+
+    /// Stream of every change to a setting, whatever its source
+    ///
+    /// A receiver that falls more than a few hundred events behind skips
+    /// the oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.change_tx.subscribe()
+    }
+
+    /// Channel for pushing values and messages into the running editor
+    ///
+    /// Commands are applied on the next `tick`.
+    pub fn command_sender(&self) -> mpsc::UnboundedSender<HostCommand> {
+        self.commands_tx.clone()
+    }
+
     pub fn get_value(&self, key: &str) -> Option<&Value> {
         self.values.get(key)
     }
@@ -123,340 +426,2148 @@ impl SchemaTUI {
         &self.values
     }
 
+    /// Fields whose value differs from the config file, in schema order
+    ///
+    /// Without a config file, changes are relative to the initial values.
+    pub fn pending_changes(&self) -> Vec<PendingChange> {
+        let mut changes = Vec::new();
+        for section in &self.schema.sections {
+            for field in &section.fields {
+                let key = format!("{}.{}", section.id, field.id);
+                let (old, new) = (self.base_values.get(&key), self.values.get(&key));
+                if old != new {
+                    changes.push(PendingChange {
+                        key,
+                        section: section.title.clone(),
+                        old: old.cloned(),
+                        new: new.cloned(),
+                    });
+                }
+            }
+        }
+        changes
+    }
+
     pub fn run(&mut self) -> Result<()> {
+        let mut terminal = self.enter_terminal()?;
+        let result = self.run_loop(&mut terminal);
+        self.leave_terminal(&mut terminal)?;
+        result
+    }
+
+    /// Run the editor without blocking the tokio runtime it's awaited on
+    ///
+    /// Terminal events, host commands and file changes are awaited together,
+    /// so other tasks keep running and can drive the editor through
+    /// [`command_sender`](Self::command_sender) and [`subscribe`](Self::subscribe).
+    ///
+    /// Change hooks and schema `apply_command`/`on_change` commands still run
+    /// to completion on this task, as their results decide whether a change
+    /// is kept; on a current-thread runtime they hold up other tasks too.
+    pub async fn run_async(&mut self) -> Result<()> {
+        let mut terminal = self.enter_terminal()?;
+        let result = self.run_loop_async(&mut terminal).await;
+        self.leave_terminal(&mut terminal)?;
+        result
+    }
+
+    fn enter_terminal(&self) -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
         enable_raw_mode()?;
         let mut stdout = io::stdout();
         execute!(stdout, EnterAlternateScreen)?;
-        let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
-
-        let result = self.run_loop(&mut terminal);
+        if self.mouse {
+            execute!(stdout, EnableMouseCapture)?;
+        }
+        Ok(Terminal::new(CrosstermBackend::new(stdout))?)
+    }
 
+    fn leave_terminal(&self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
         disable_raw_mode()?;
+        if self.mouse {
+            execute!(terminal.backend_mut(), DisableMouseCapture)?;
+        }
         execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
         terminal.show_cursor()?;
+        Ok(())
+    }
 
-        result
+    fn redraw(&mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+        if self.take_needs_redraw() {
+            // An external program drew over the screen and may have
+            // turned mouse reporting off
+            if self.mouse {
+                execute!(terminal.backend_mut(), EnableMouseCapture)?;
+            }
+            terminal.clear()?;
+        }
+        terminal.draw(|f| self.render(f, f.area()))?;
+        Ok(())
     }
 
     fn run_loop(&mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
         loop {
-            terminal.draw(|f| self.render(f))?;
+            self.redraw(terminal)?;
 
-            if self.should_quit {
+            if event::poll(Duration::from_millis(100))? {
+                let event = event::read()?;
+                if self.handle_event(&event)? == Outcome::QuitRequested {
+                    break;
+                }
+            }
+            if self.tick() == Outcome::QuitRequested {
                 break;
             }
+        }
 
-            if event::poll(std::time::Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    self.handle_key(key)?;
-                }
+        Ok(())
+    }
+
+    async fn run_loop_async(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> Result<()> {
+        let mut events = EventStream::new();
+        let mut ticker = tokio::time::interval(Duration::from_millis(250));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            self.redraw(terminal)?;
+
+            let quit = tokio::select! {
+                event = events.next() => match event {
+                    Some(event) => self.handle_event(&event?)? == Outcome::QuitRequested,
+                    None => true,
+                },
+                Some(command) = self.commands_rx.recv() => self.handle_host_command(command),
+                _ = ticker.tick() => false,
+            };
+            if quit || self.tick() == Outcome::QuitRequested {
+                break;
             }
         }
 
         Ok(())
     }
 
-    fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
-        if self.edit_mode {
-            self.handle_edit_mode(key)?;
+    /// Handle one terminal event from the host's event loop
+    ///
+    /// Key events are always for the editor while it has focus; mouse events
+    /// outside the area it was last rendered to are ignored.
+    pub fn handle_event(&mut self, event: &Event) -> Result<Outcome> {
+        let before = self.values.clone();
+        let consumed = match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => self.handle_key(*key)?,
+            Event::Mouse(mouse)
+                if self.mouse
+                    && self
+                        .hit_areas
+                        .pane
+                        .contains((mouse.column, mouse.row).into()) =>
+            {
+                self.handle_mouse(*mouse)?;
+                true
+            }
+            _ => false,
+        };
+        self.run_change_effects();
+
+        if std::mem::take(&mut self.should_quit) {
+            return Ok(Outcome::QuitRequested);
+        }
+        let changed = self.changed_since(&before);
+        Ok(if !changed.is_empty() {
+            Outcome::Changed(changed)
+        } else if consumed {
+            Outcome::Consumed
+        } else {
+            Outcome::Ignored
+        })
+    }
+
+    /// Apply host commands and background option checks, and merge changes
+    /// other programs made to the config file
+    ///
+    /// Call this regularly from the host's loop, e.g. after each event poll;
+    /// the file is checked at most every half second.
+    pub fn tick(&mut self) -> Outcome {
+        let before = self.values.clone();
+        let mut quit = false;
+        while let Ok(command) = self.commands_rx.try_recv() {
+            quit |= self.handle_host_command(command);
+        }
+        self.apply_option_checks();
+        if self.live_apply_dialog.as_ref().is_some_and(|d| d.expired()) {
+            let dialog = self.live_apply_dialog.take().unwrap();
+            self.roll_back(dialog.previous);
+            self.message = Some("Not confirmed in time, reverted applied settings".to_string());
+        }
+        self.run_change_effects();
+        if let Err(e) = self.check_external_changes(false) {
+            self.message = Some(format!("Failed to reload config: {}", e));
+        }
+        if quit {
+            return Outcome::QuitRequested;
+        }
+        let changed = self.changed_since(&before);
+        if changed.is_empty() {
+            Outcome::Ignored
         } else {
-            self.handle_navigation_mode(key)?;
+            Outcome::Changed(changed)
+        }
+    }
+
+    /// Returns true when the host asked the editor to close
+    fn handle_host_command(&mut self, command: HostCommand) -> bool {
+        match command {
+            HostCommand::SetValue { key, value } => {
+                if let Err(e) = self.set_host_value(&key, value) {
+                    self.message = Some(format!("{}: {}", key, e));
+                }
+            }
+            HostCommand::Message(message) => self.message = Some(message),
+            HostCommand::Quit => return true,
+        }
+        false
+    }
+
+    fn set_host_value(&mut self, key: &str, value: Value) -> Result<()> {
+        if self.field_by_key(key).is_none() {
+            anyhow::bail!("unknown setting");
+        }
+        let value = self.normalize_field_value(key, value)?;
+        let value = self
+            .check_before_change(key, value)
+            .map_err(|reason| anyhow::anyhow!("rejected: {}", reason))?;
+        if self.values.get(key) == Some(&value) {
+            return Ok(());
         }
 
+        self.push_undo();
+        self.undo_key = None;
+        self.apply_values(&[(key.to_string(), Some(value))], ChangeSource::Host);
+        self.invalidate_dependent_fields(key, ChangeSource::Host);
+        self.save_if_auto();
         Ok(())
     }
 
-    fn handle_navigation_mode(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Char('q') | KeyCode::Esc => {
-                self.should_quit = true;
+    fn changed_since(&self, before: &HashMap<String, Value>) -> Vec<String> {
+        Self::value_updates(before, &self.values)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    /// Whether an external program such as `$EDITOR` used the terminal since
+    /// the last call
+    ///
+    /// The screen must then be cleared and fully redrawn. The editor is run
+    /// outside the alternate screen with mouse capture off; hosts that
+    /// capture the mouse need to enable it again.
+    pub fn take_needs_redraw(&mut self) -> bool {
+        std::mem::take(&mut self.needs_redraw)
+    }
+
+    /// Whether a field is being edited, so keys go to its widget
+    pub fn is_editing(&self) -> bool {
+        self.edit_mode
+    }
+
+    /// Whether a dialog (search, palette, review, …) is open over the editor
+    pub fn has_dialog(&self) -> bool {
+        self.overlay_open()
+    }
+
+    /// Id of the section being shown
+    pub fn current_section_id(&self) -> Option<&str> {
+        self.get_current_section().map(|s| s.id.as_str())
+    }
+
+    /// Dot-notation key of the focused field
+    pub fn current_field_key(&self) -> Option<String> {
+        self.get_current_field()
+            .is_some()
+            .then(|| self.get_current_field_key())
+    }
+
+    /// Status message shown in the footer, if any
+    pub fn status_message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Returns false when the key means nothing to the editor
+    fn handle_key(&mut self, key: KeyEvent) -> Result<bool> {
+        if let Some(dialog) = &self.live_apply_dialog {
+            if let Some(keep) = dialog.handle_key(key) {
+                let dialog = self.live_apply_dialog.take().unwrap();
+                if keep {
+                    self.message = Some("Kept applied settings".to_string());
+                } else {
+                    self.roll_back(dialog.previous);
+                    self.message = Some("Reverted applied settings".to_string());
+                }
+            }
+        } else if let Some(dialog) = &mut self.conflict_dialog {
+            if let ConflictAction::Resolve(resolved) = dialog.handle_key(key) {
+                self.conflict_dialog = None;
+                self.resolve_conflicts(resolved);
             }
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.should_quit = true;
+        } else if let Some(dialog) = &mut self.raw_edit_dialog {
+            let action = dialog.handle_key(key);
+            if !matches!(action, RawEditAction::None) {
+                let dialog = self.raw_edit_dialog.take().unwrap();
+                if let Err(e) = self.finish_raw_edit(action, dialog) {
+                    self.message = Some(format!("Editing config file failed: {}", e));
+                }
+            }
+        } else if let Some(dialog) = &self.confirm_dialog {
+            if let Some(confirmed) = dialog.handle_key(key) {
+                let dialog = self.confirm_dialog.take().unwrap();
+                if confirmed {
+                    self.reset_fields(&dialog.keys);
+                } else {
+                    self.message = Some("Reset cancelled".to_string());
+                }
             }
-            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => {
-                self.next_section();
+        } else if let Some(output) = &mut self.output_dialog {
+            if output.handle_key(key) {
+                self.output_dialog = None;
             }
-            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
-                self.previous_section();
+        } else if let Some(help) = &mut self.help_dialog {
+            if help.handle_key(key) {
+                self.help_dialog = None;
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                self.next_field();
+        } else if let Some(palette) = &mut self.palette {
+            match palette.handle_key(key) {
+                PaletteAction::None => {}
+                PaletteAction::Close => self.palette = None,
+                PaletteAction::Run(command) => {
+                    self.palette = None;
+                    self.run_command(command)?;
+                }
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                self.previous_field();
+        } else if let Some(dialog) = &mut self.search_dialog {
+            match dialog.handle_key(key) {
+                SearchAction::None => {}
+                SearchAction::Close => self.search_dialog = None,
+                SearchAction::Jump(index) => {
+                    let entry = dialog.entry(index);
+                    let target = (entry.section, entry.field, entry.hidden_by.clone());
+                    self.search_dialog = None;
+                    self.jump_to_field(target.0, target.1, target.2);
+                }
             }
-            KeyCode::Enter | KeyCode::Char(' ') => {
-                self.activate_current_field()?;
+        } else if let Some(dialog) = &mut self.review_dialog {
+            match dialog.handle_key(key) {
+                ReviewAction::None => {}
+                ReviewAction::Close => self.review_dialog = None,
+                ReviewAction::Save => self.save_reviewed(),
+                ReviewAction::Revert(key) => self.revert(&key),
             }
-            KeyCode::Char('e') => {
-                // Check if current field has external editor action
-                if let Some(field) = self.get_current_field() {
-                    if matches!(field.field_type, crate::schema::FieldType::Path { .. }) {
-                        self.execute_external_editor_for_field()?;
+        } else if let Some(dialog) = &mut self.restore_dialog {
+            match dialog.handle_key(key) {
+                RestoreAction::None => {}
+                RestoreAction::Close => self.restore_dialog = None,
+                RestoreAction::Restore(backup) => {
+                    self.restore_dialog = None;
+                    if let Err(e) = self.restore(&backup) {
+                        self.message = Some(format!("Restore failed: {}", e));
                     }
                 }
             }
-            _ => {}
+        } else if self.edit_mode {
+            self.handle_edit_mode(key)?;
+        } else {
+            return self.handle_navigation_mode(key);
         }
 
-        Ok(())
+        Ok(true)
     }
 
-    fn handle_edit_mode(&mut self, key: KeyEvent) -> Result<()> {
-        if let Some(field_key) = &self.active_field.clone() {
-            if let Some(widget) = self.active_widgets.get_mut(field_key) {
-                match widget.handle_key(key) {
-                    WidgetResult::Confirmed(value) => {
-                        self.fire_change(field_key, value);
-                        self.edit_mode = false;
-                        self.active_field = None;
-                        // Remove widget from cache so it rebuilds with fresh value next time
-                        self.active_widgets.remove(field_key);
-                        self.message = Some(format!("Saved {}", field_key));
-                    }
-                    WidgetResult::Cancelled => {
-                        self.edit_mode = false;
-                        self.active_field = None;
-                        self.message = Some("Cancelled".to_string());
-                    }
-                    WidgetResult::Changed(value) => {
-                        // Live update
-                        self.fire_change(field_key, value);
-                    }
-                    WidgetResult::Continue => {}
-                }
+    fn handle_navigation_mode(&mut self, key: KeyEvent) -> Result<bool> {
+        // Ctrl-C always quits, behind the same unsaved-changes guard
+        let ctrl_c =
+            key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        // A second quit only discards changes if it directly follows the first
+        if !ctrl_c && self.keymap.navigation_action(&key) != Some("nav.quit") {
+            self.quit_armed = false;
+        }
+        if ctrl_c {
+            self.run_command(Command::Quit)?;
+            return Ok(true);
+        }
+        if let Some(index) = self
+            .focused_actions()
+            .iter()
+            .position(|a| KeyBinding::parse(&a.key).is_ok_and(|k| k.matches(&key)))
+        {
+            self.run_command(Command::SchemaAction(index))?;
+            return Ok(true);
+        }
+        if let Some((section, field)) = self.field_for_keybind(&key) {
+            self.jump_to_field(section, field, None);
+            self.activate_current_field()?;
+            return Ok(true);
+        }
+
+        let Some(command) = self
+            .keymap
+            .navigation_action(&key)
+            .and_then(Command::from_action)
+        else {
+            return Ok(false);
+        };
+
+        if self.command_available(&command) {
+            self.run_command(command)?;
+        }
+        Ok(true)
+    }
+
+    /// Whether a command makes sense for the focused field and current setup
+    fn command_available(&self, command: &Command) -> bool {
+        let field = self.get_current_field();
+        match command {
+            Command::Save | Command::Reload | Command::Backups => self.config_path.is_some(),
+            Command::EditField => field.is_some(),
+            Command::OpenEditor => field.is_some_and(|f| {
+                matches!(
+                    f.field_type,
+                    FieldType::String { .. } | FieldType::Path { .. } | FieldType::Array { .. }
+                )
+            }),
+            Command::ResetField | Command::ResetSection | Command::ResetAll => {
+                !self.reset_targets(command).is_empty()
             }
+            Command::Undo => !self.undo_stack.is_empty(),
+            Command::SchemaAction(index) => *index < self.focused_actions().len(),
+            _ => true,
         }
+    }
 
-        Ok(())
+    /// Commands for the palette, in the order they are listed
+    fn palette_entries(&self) -> Vec<PaletteEntry> {
+        let entry = |command: Command, label: &str| PaletteEntry {
+            key: command
+                .action()
+                .map(|action| self.keymap.label(action))
+                .unwrap_or_default(),
+            command,
+            label: label.to_string(),
+        };
+        let field_label = self
+            .get_current_field()
+            .map(|f| f.label.clone())
+            .unwrap_or_default();
+        let section_title = self
+            .get_current_section()
+            .map(|s| s.title.clone())
+            .unwrap_or_default();
+
+        let mut entries = vec![
+            entry(Command::EditField, &format!("Edit {}", field_label)),
+            entry(
+                Command::OpenEditor,
+                &format!("Open {} in $EDITOR", field_label),
+            ),
+            entry(
+                Command::ResetField,
+                &format!("Reset {} to default", field_label),
+            ),
+            entry(
+                Command::ResetSection,
+                &format!("Reset section {} to defaults", section_title),
+            ),
+            entry(Command::ResetAll, "Reset all settings to defaults"),
+            entry(Command::Undo, "Undo last change"),
+            entry(Command::Search, "Search settings"),
+            entry(Command::Review, "Review changes"),
+            entry(Command::Save, "Save"),
+            entry(Command::Reload, "Reload from disk"),
+            entry(Command::Backups, "Restore a backup"),
+            entry(Command::ToggleTheme, "Toggle theme"),
+            entry(Command::Export, "Export as JSON"),
+            entry(Command::EditRaw, "Edit config file in $EDITOR"),
+            entry(Command::Help, "Show key bindings"),
+            entry(Command::Quit, "Quit"),
+        ];
+        for (index, action) in self.focused_actions().iter().enumerate() {
+            entries.push(PaletteEntry {
+                command: Command::SchemaAction(index),
+                label: format!("{}: {}", field_label, action.label),
+                key: KeyBinding::parse(&action.key)
+                    .map(|k| k.to_string())
+                    .unwrap_or_default(),
+            });
+        }
+        for (idx, section) in self.get_visible_sections() {
+            entries.push(entry(
+                Command::JumpToSection(idx),
+                &format!("Go to section: {}", section.title),
+            ));
+        }
+
+        entries.retain(|e| self.command_available(&e.command));
+        entries
     }
 
-    fn get_visible_sections(&self) -> Vec<(usize, &crate::schema::SchemaSection)> {
-        self.schema
-            .sections
+    /// Schema actions of the focused field, then those of its section that
+    /// the field doesn't override
+    fn focused_actions(&self) -> Vec<SchemaAction> {
+        let (Some(section), Some(field)) = (self.get_current_section(), self.get_current_field())
+        else {
+            return Vec::new();
+        };
+        let key_of = |a: &SchemaAction| KeyBinding::parse(&a.key).ok();
+        let mut actions = field.actions.clone();
+        for action in &section.actions {
+            if key_of(action).is_none()
+                || !field.actions.iter().any(|a| key_of(a) == key_of(action))
+            {
+                actions.push(action.clone());
+            }
+        }
+        actions
+    }
+
+    /// The visible field whose `keybind` matches a key
+    fn field_for_keybind(&self, key: &KeyEvent) -> Option<(usize, usize)> {
+        self.get_visible_sections()
+            .into_iter()
+            .find_map(|(section_idx, section)| {
+                let field_idx = section.fields.iter().position(|f| {
+                    f.keybind
+                        .as_deref()
+                        .and_then(|k| KeyBinding::parse(k).ok())
+                        .is_some_and(|k| k.matches(key))
+                })?;
+                Some((section_idx, field_idx))
+            })
+    }
+
+    /// Field values for action commands, as `CONFIG_<SECTION>_<FIELD>`;
+    /// secrets are left out
+    fn action_env(&self) -> Vec<(String, String)> {
+        self.values
             .iter()
-            .enumerate()
-            .filter(|(_, section)| {
-                if let Some(condition) = &section.visible_when {
-                    super::conditions::evaluate_condition(condition, &self.values)
-                } else {
-                    true
-                }
+            .filter(|(key, _)| {
+                !self
+                    .field_by_key(key)
+                    .is_some_and(|f| matches!(f.field_type, FieldType::Secret { .. }))
+            })
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (env_var_name(key), value)
             })
             .collect()
     }
 
-    fn next_section(&mut self) {
-        let visible = self.get_visible_sections();
-        if visible.is_empty() {
+    fn run_schema_action(&mut self, action: &SchemaAction) {
+        let field_action = match &action.kind {
+            ActionKind::Editor { extension } => FieldAction::ExternalEditor {
+                editor: default_editor(),
+                extension: extension.clone().unwrap_or_else(|| "txt".to_string()),
+            },
+            ActionKind::Command { command } => FieldAction::CustomCommand {
+                command: command.clone(),
+            },
+            ActionKind::Run { command } => FieldAction::RunCommand {
+                command: command.clone(),
+            },
+        };
+        self.run_field_action(&action.label, &field_action);
+    }
+
+    /// Run an action on the focused field, validating any new value before
+    /// it replaces the current one
+    fn run_field_action(&mut self, label: &str, field_action: &FieldAction) {
+        let field_key = self.get_current_field_key();
+        let Some(field) = self.field_by_key(&field_key) else {
             return;
+        };
+        let field_type = field.field_type.clone();
+        let single_line = !matches!(field.ui_widget, UIWidget::Multiline)
+            && !matches!(field.field_type, FieldType::Array { .. });
+        let current = match self.editable_value(&field_key) {
+            Ok(value) => value
+                .as_ref()
+                .map(SchemaValidator::format_text)
+                .unwrap_or_default(),
+            Err(e) => {
+                self.message = Some(format!("{}: {}", label, e));
+                return;
+            }
+        };
+
+        let result = field_action.run(&current, &self.action_env());
+        if matches!(field_action, FieldAction::ExternalEditor { .. }) {
+            self.needs_redraw = true;
+        }
+        match result {
+            Ok(ActionOutput::Value(text)) => {
+                let parsed = if single_line && text.contains('\n') {
+                    Err(anyhow::anyhow!("{} must be a single line", field_key))
+                } else {
+                    SchemaValidator::parse_text(&field_type, &text)
+                };
+                match parsed.and_then(|value| self.store_if_secret(&field_key, value)) {
+                    Ok(value) => {
+                        if self.fire_change(&field_key, value) {
+                            self.message = Some(format!("{}: updated {}", label, field_key));
+                        }
+                    }
+                    Err(e) => self.message = Some(format!("{}: {}, edit discarded", label, e)),
+                }
+            }
+            Ok(ActionOutput::Output(output)) => {
+                self.output_dialog = Some(OutputDialog::new(label, output));
+            }
+            Ok(ActionOutput::Unchanged) => {
+                self.message = Some(format!("{}: no changes", label));
+            }
+            Err(e) => self.message = Some(format!("{}: {}", label, e)),
         }
+    }
 
-        // Find current in visible list and go to next
-        let current_pos = visible
-            .iter()
-            .position(|(idx, _)| *idx == self.current_section);
-        if let Some(pos) = current_pos {
-            let next_pos = (pos + 1) % visible.len();
-            self.current_section = visible[next_pos].0;
-        } else {
-            // Current not visible, go to first visible
-            self.current_section = visible[0].0;
+    fn run_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Quit => {
+                if self.save_mode == SaveMode::Explicit
+                    && !self.quit_armed
+                    && !self.pending_changes().is_empty()
+                {
+                    self.quit_armed = true;
+                    self.message = Some(format!(
+                        "Unsaved changes: {} to review and save, {} again to discard",
+                        self.keymap.label("app.save"),
+                        self.keymap.label("nav.quit")
+                    ));
+                } else {
+                    self.should_quit = true;
+                }
+            }
+            Command::NextSection => self.next_section(),
+            Command::PreviousSection => self.previous_section(),
+            Command::NextField => self.next_field(),
+            Command::PreviousField => self.previous_field(),
+            Command::JumpToSection(idx) => {
+                self.current_section = idx;
+                self.current_field = 0;
+                self.list_state.select(Some(0));
+            }
+            Command::EditField => self.activate_current_field()?,
+            Command::ResetField | Command::ResetSection | Command::ResetAll => {
+                self.confirm_reset(command)
+            }
+            Command::Undo => self.undo(),
+            Command::Save => match self.save_mode {
+                SaveMode::Explicit => self.open_review(),
+                SaveMode::Auto => match self.save_config() {
+                    Ok(()) => self.message = Some("Saved".to_string()),
+                    Err(e) => self.message = Some(format!("Failed to save config: {}", e)),
+                },
+            },
+            Command::Reload => {
+                if let Err(e) = self.reload() {
+                    self.message = Some(format!("Failed to reload config: {}", e));
+                }
+            }
+            Command::OpenEditor => self.execute_external_editor_for_field()?,
+            Command::ToggleTheme => {
+                self.theme_index = (self.theme_index + 1) % 3;
+                self.theme = match self.theme_index {
+                    1 => Theme::dark(),
+                    2 => Theme::light(),
+                    _ => self.configured_theme.clone(),
+                };
+            }
+            Command::EditRaw => {
+                if let Err(e) = self.edit_raw_config() {
+                    self.message = Some(format!("Editing config file failed: {}", e));
+                }
+            }
+            Command::Export => {
+                if let Err(e) = self.export_json() {
+                    self.message = Some(format!("Export failed: {}", e));
+                }
+            }
+            Command::Search => self.open_search(),
+            Command::Review => self.open_review(),
+            Command::Backups => self.open_restore_dialog()?,
+            Command::Palette => self.palette = Some(CommandPalette::new(self.palette_entries())),
+            Command::Help => self.help_dialog = Some(HelpDialog::new()),
+            Command::SchemaAction(index) => {
+                if let Some(action) = self.focused_actions().get(index).cloned() {
+                    self.run_schema_action(&action);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Snapshot values so the next change can be undone
+    fn push_undo(&mut self) {
+        if self.undo_stack.len() == UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(self.values.clone());
+    }
+
+    fn undo(&mut self) {
+        let Some(snapshot) = self.undo_stack.pop() else {
+            return;
+        };
+        self.undo_key = None;
+        let updates = Self::value_updates(&self.values, &snapshot);
+        self.apply_values(&updates, ChangeSource::User);
+        self.message = Some(format!("Undid change to {} field(s)", updates.len()));
+        self.save_if_auto();
+    }
+
+    /// Whether a field holds something other than its schema default
+    fn differs_from_default(&self, key: &str) -> bool {
+        self.field_by_key(key).is_some_and(|field| {
+            self.values.get(key) != Self::get_field_default(&field.field_type).as_ref()
+        })
+    }
+
+    /// Keys a reset command would change
+    fn reset_targets(&self, command: &Command) -> Vec<String> {
+        let keys: Vec<String> = match command {
+            Command::ResetField => vec![self.get_current_field_key()],
+            Command::ResetSection => self
+                .get_current_section()
+                .map(|s| {
+                    s.fields
+                        .iter()
+                        .map(|f| format!("{}.{}", s.id, f.id))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            Command::ResetAll => self
+                .schema
+                .sections
+                .iter()
+                .flat_map(|s| s.fields.iter().map(move |f| format!("{}.{}", s.id, f.id)))
+                .collect(),
+            _ => Vec::new(),
+        };
+        keys.into_iter()
+            .filter(|key| self.differs_from_default(key))
+            .collect()
+    }
+
+    /// Ask before a reset command discards values
+    fn confirm_reset(&mut self, command: Command) {
+        let keys = self.reset_targets(&command);
+        if keys.is_empty() {
+            self.message = Some("Already at defaults".to_string());
+            return;
+        }
+        let scope = match command {
+            Command::ResetField => self
+                .get_current_field()
+                .map(|f| f.label.clone())
+                .unwrap_or_default(),
+            Command::ResetSection => format!(
+                "section {}",
+                self.get_current_section()
+                    .map(|s| s.title.as_str())
+                    .unwrap_or_default()
+            ),
+            _ => "all settings".to_string(),
+        };
+        let action = if self.omit_defaults {
+            "remove their values from the config file"
+        } else {
+            "set them to their defaults"
+        };
+        let prompt = format!(
+            "Reset {}? This will {} ({} setting(s)):",
+            scope,
+            action,
+            keys.len()
+        );
+        self.confirm_dialog = Some(ConfirmDialog::new(keys, prompt));
+    }
+
+    /// Put fields back to their schema default, or unset them without one
+    fn reset_fields(&mut self, keys: &[String]) {
+        let updates: Vec<(String, Option<Value>)> = keys
+            .iter()
+            .filter_map(|key| {
+                let default = Self::get_field_default(&self.field_by_key(key)?.field_type);
+                (self.values.get(key) != default.as_ref()).then(|| (key.clone(), default))
+            })
+            .collect();
+        if updates.is_empty() {
+            self.message = Some("Already at defaults".to_string());
+            return;
+        }
+
+        self.push_undo();
+        self.undo_key = None;
+        self.apply_values(&updates, ChangeSource::User);
+        self.message = Some(format!("Reset {} field(s) to defaults", updates.len()));
+        self.save_if_auto();
+    }
+
+    /// Discard in-memory edits and load the config file again
+    fn reload(&mut self) -> Result<()> {
+        let Some(path) = self.config_path.clone() else {
+            return Ok(());
+        };
+        let mut values = ConfigLoader::from_toml_file_with_expansion(&path, false)?.as_flat_map();
+        Self::merge_defaults(&self.schema, &mut values);
+
+        self.push_undo();
+        self.undo_key = None;
+        let updates = Self::value_updates(&self.values, &values);
+        self.apply_values(&updates, ChangeSource::File);
+        self.mark_synced();
+        self.message = Some(format!("Reloaded {}", path.display()));
+        Ok(())
+    }
+
+    /// Write the current values as nested JSON next to the config file
+    fn export_json(&mut self) -> Result<()> {
+        let path = match &self.config_path {
+            Some(path) => path.with_extension("export.json"),
+            None => std::path::PathBuf::from("config.export.json"),
+        };
+        let json = serde_json::to_string_pretty(self.to_store().as_map())?;
+        // May contain inline secrets
+        write_atomic_private(&path, json.as_bytes())?;
+        self.message = Some(format!("Exported to {}", path.display()));
+        Ok(())
+    }
+
+    /// Updates that turn `from` into `to`; `None` removes a key
+    fn value_updates(
+        from: &HashMap<String, Value>,
+        to: &HashMap<String, Value>,
+    ) -> Vec<(String, Option<Value>)> {
+        let keys: std::collections::BTreeSet<&String> = from.keys().chain(to.keys()).collect();
+        keys.into_iter()
+            .filter(|key| from.get(*key) != to.get(*key))
+            .map(|key| (key.clone(), to.get(key).cloned()))
+            .collect()
+    }
+
+    fn save_if_auto(&mut self) {
+        if self.save_mode == SaveMode::Auto {
+            if let Err(e) = self.save_config() {
+                self.message = Some(format!("Failed to save config: {}", e));
+            }
+        }
+    }
+
+    fn handle_edit_mode(&mut self, key: KeyEvent) -> Result<()> {
+        if let Some(field_key) = self.active_field.clone() {
+            if let Some(widget) = self.active_widgets.get_mut(&field_key) {
+                let result = match self.keymap.edit_action(&key) {
+                    Some(action) if !widget.takes_input(&key) => widget.handle_action(action),
+                    // The standard key of an action the keymap moved elsewhere
+                    _ if EditAction::from_key(&key).is_some() && !widget.takes_input(&key) => {
+                        WidgetResult::Continue
+                    }
+                    _ => widget.handle_input(key),
+                };
+                self.apply_widget_result(&field_key, result);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_widget_result(&mut self, field_key: &str, result: WidgetResult) {
+        match result {
+            WidgetResult::Confirmed(value) => {
+                let value = match self
+                    .normalize_field_value(field_key, value)
+                    .map_err(|e| format!("Invalid {}: {}", field_key, e))
+                    .and_then(|value| {
+                        self.store_if_secret(field_key, value)
+                            .map_err(|e| format!("Failed to store {}: {}", field_key, e))
+                    }) {
+                    Ok(value) => value,
+                    Err(message) => {
+                        self.edit_mode = false;
+                        self.active_field = None;
+                        self.active_widgets.remove(field_key);
+                        self.discard_preview(field_key);
+                        self.message = Some(message);
+                        return;
+                    }
+                };
+                let stored = self.fire_change(field_key, value);
+                self.edit_mode = false;
+                self.active_field = None;
+                // Remove widget from cache so it rebuilds with fresh value next time
+                self.active_widgets.remove(field_key);
+                if stored {
+                    self.preview = None;
+                    self.message = Some(format!("Saved {}", field_key));
+                } else {
+                    self.discard_preview(field_key);
+                }
+            }
+            WidgetResult::Cancelled => {
+                self.edit_mode = false;
+                self.active_field = None;
+                self.discard_preview(field_key);
+                self.message = Some("Cancelled".to_string());
+                // Don't keep a secret's plaintext around between edits
+                if self
+                    .field_by_key(field_key)
+                    .is_some_and(|f| matches!(f.field_type, FieldType::Secret { .. }))
+                {
+                    self.active_widgets.remove(field_key);
+                }
+            }
+            WidgetResult::Changed(value) => {
+                // Live update; partial input that doesn't validate
+                // yet is only reported once confirmed
+                if let Ok(value) = self.normalize_field_value(field_key, value) {
+                    self.preview_change(field_key, value);
+                }
+            }
+            WidgetResult::Continue => {}
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<()> {
+        // Dialogs are keyboard-only
+        if self.overlay_open() {
+            return Ok(());
+        }
+        if self.edit_mode {
+            if let Some(field_key) = self.active_field.clone() {
+                if let Some(widget) = self.active_widgets.get_mut(&field_key) {
+                    let result = widget.handle_mouse(mouse);
+                    self.apply_widget_result(&field_key, result);
+                }
+            }
+            return Ok(());
+        }
+
+        let position = (mouse.column, mouse.row).into();
+        let on_tabs = self.hit_areas.tabs.contains(position);
+        let on_fields = self.hit_areas.fields.contains(position);
+        match mouse.kind {
+            MouseEventKind::ScrollDown if on_tabs => self.next_section(),
+            MouseEventKind::ScrollUp if on_tabs => self.previous_section(),
+            MouseEventKind::ScrollDown if on_fields => self.next_field(),
+            MouseEventKind::ScrollUp if on_fields => self.previous_field(),
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(&(_, section)) = self
+                    .hit_areas
+                    .tab_titles
+                    .iter()
+                    .find(|(area, _)| area.contains(position))
+                {
+                    if section != self.current_section {
+                        self.run_command(Command::JumpToSection(section))?;
+                    }
+                } else if let Some(&(_, field)) = self
+                    .hit_areas
+                    .field_rows
+                    .iter()
+                    .find(|(row, _)| *row == mouse.row && on_fields)
+                {
+                    self.click_field(field)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Focus a clicked field; toggles flip on a single click, other
+    /// fields open on a double click
+    fn click_field(&mut self, field: usize) -> Result<()> {
+        let now = Instant::now();
+        let double = self.last_click.is_some_and(|(at, section, clicked)| {
+            section == self.current_section
+                && clicked == field
+                && now.duration_since(at) < DOUBLE_CLICK
+        });
+        self.last_click = (!double).then_some((now, self.current_section, field));
+
+        self.current_field = field;
+        let is_toggle = self
+            .get_current_field()
+            .is_some_and(|f| matches!(f.ui_widget, UIWidget::Toggle));
+        if is_toggle || double {
+            self.activate_current_field()?;
+        }
+        Ok(())
+    }
+
+    fn overlay_open(&self) -> bool {
+        self.live_apply_dialog.is_some()
+            || self.conflict_dialog.is_some()
+            || self.raw_edit_dialog.is_some()
+            || self.confirm_dialog.is_some()
+            || self.output_dialog.is_some()
+            || self.help_dialog.is_some()
+            || self.palette.is_some()
+            || self.search_dialog.is_some()
+            || self.review_dialog.is_some()
+            || self.restore_dialog.is_some()
+    }
+
+    fn get_visible_sections(&self) -> Vec<(usize, &crate::schema::SchemaSection)> {
+        self.schema
+            .sections
+            .iter()
+            .enumerate()
+            .filter(|(_, section)| {
+                if let Some(condition) = &section.visible_when {
+                    super::conditions::evaluate_condition(condition, &self.values)
+                } else {
+                    true
+                }
+            })
+            .collect()
+    }
+
+    fn next_section(&mut self) {
+        let visible = self.get_visible_sections();
+        if visible.is_empty() {
+            return;
+        }
+
+        // Find current in visible list and go to next
+        let current_pos = visible
+            .iter()
+            .position(|(idx, _)| *idx == self.current_section);
+        if let Some(pos) = current_pos {
+            let next_pos = (pos + 1) % visible.len();
+            self.current_section = visible[next_pos].0;
+        } else {
+            // Current not visible, go to first visible
+            self.current_section = visible[0].0;
+        }
+
+        self.current_field = 0;
+        self.list_state.select(Some(0));
+    }
+
+    fn previous_section(&mut self) {
+        let visible = self.get_visible_sections();
+        if visible.is_empty() {
+            return;
+        }
+
+        // Find current in visible list and go to previous
+        let current_pos = visible
+            .iter()
+            .position(|(idx, _)| *idx == self.current_section);
+        if let Some(pos) = current_pos {
+            let prev_pos = if pos == 0 { visible.len() - 1 } else { pos - 1 };
+            self.current_section = visible[prev_pos].0;
+        } else {
+            // Current not visible, go to first visible
+            self.current_section = visible[0].0;
+        }
+
+        self.current_field = 0;
+        self.list_state.select(Some(0));
+    }
+
+    fn next_field(&mut self) {
+        let field_count = self
+            .get_current_section()
+            .map(|s| s.fields.len())
+            .unwrap_or(0);
+        if field_count > 0 {
+            self.current_field = (self.current_field + 1) % field_count;
+            self.list_state.select(Some(self.current_field));
+        }
+    }
+
+    fn previous_field(&mut self) {
+        let field_count = self
+            .get_current_section()
+            .map(|s| s.fields.len())
+            .unwrap_or(0);
+        if field_count > 0 {
+            self.current_field = if self.current_field == 0 {
+                field_count - 1
+            } else {
+                self.current_field - 1
+            };
+            self.list_state.select(Some(self.current_field));
+        }
+    }
+
+    fn open_search(&mut self) {
+        let mut entries = Vec::new();
+        for (section_idx, section) in self.schema.sections.iter().enumerate() {
+            let hidden_by = section
+                .visible_when
+                .as_ref()
+                .filter(|condition| !super::conditions::evaluate_condition(condition, &self.values))
+                .cloned();
+
+            for (field_idx, field) in section.fields.iter().enumerate() {
+                let key = format!("{}.{}", section.id, field.id);
+                let value = self.get_value_display(&key, field);
+                let mut haystack = vec![
+                    (field.label.clone(), 4),
+                    (field.id.clone(), 3),
+                    (field.description.clone(), 1),
+                    (value.clone(), 1),
+                ];
+                if let Some(subsection) = &field.subsection {
+                    haystack.push((subsection.clone(), 2));
+                }
+                entries.push(SearchEntry {
+                    section: section_idx,
+                    field: field_idx,
+                    section_title: section.title.clone(),
+                    label: field.label.clone(),
+                    key,
+                    value,
+                    haystack,
+                    hidden_by: hidden_by.clone(),
+                });
+            }
+        }
+        self.search_dialog = Some(SearchDialog::new(entries));
+    }
+
+    /// Focus a field, switching sections if needed
+    fn jump_to_field(&mut self, section: usize, field: usize, hidden_by: Option<String>) {
+        if let Some(condition) = hidden_by {
+            self.message = Some(format!("Field is hidden until: {}", condition));
+            return;
+        }
+        self.current_section = section;
+        self.current_field = field;
+        self.list_state.select(Some(field));
+    }
+
+    fn activate_current_field(&mut self) -> Result<()> {
+        self.undo_key = None;
+        let field_key = self.get_current_field_key();
+        let widget_type = self.get_current_field().map(|f| f.ui_widget);
+
+        if let Some(wt) = widget_type {
+            // Create widget if not exists
+            if !self.active_widgets.contains_key(&field_key) {
+                let field = self.get_current_field().unwrap();
+                let widget = self.build_widget_for_field(field)?;
+                self.active_widgets.insert(field_key.clone(), widget);
+                self.report_option_warnings();
+            }
+
+            // Secrets in a storage backend are loaded by the widget on reveal or edit
+            let current_value = self
+                .values
+                .get(&field_key)
+                .filter(|v| self.secret_ref(&field_key, v).is_none())
+                .cloned();
+
+            // Activate widget
+            if let Some(widget) = self.active_widgets.get_mut(&field_key) {
+                // Set current value
+                if let Some(value) = current_value {
+                    widget.set_value(value);
+                }
+
+                // Activate the widget (transitions to editing state)
+                widget.activate();
+
+                // Enter edit mode based on widget type
+                match &wt {
+                    UIWidget::Toggle => {
+                        // Toggle activates immediately - get value will be handled below
+                    }
+                    _ => {
+                        self.edit_mode = true;
+                        self.active_field = Some(field_key.clone());
+                    }
+                }
+            }
+
+            // Handle toggle fire change outside of the widget borrow
+            if matches!(wt, UIWidget::Toggle) {
+                if let Some(value) = self.active_widgets.get(&field_key).map(|w| w.get_value()) {
+                    if !self.fire_change(&field_key, value) {
+                        // The toggle flipped; rebuild it from the kept value
+                        self.active_widgets.remove(&field_key);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reference from a secret field's value into its storage backend
+    fn secret_ref(&self, field_key: &str, value: &Value) -> Option<SecretRef> {
+        match &self.field_by_key(field_key)?.field_type {
+            FieldType::Secret { storage, .. } => SecretRef::for_storage(value.as_str()?, storage),
+            _ => None,
+        }
+    }
+
+    /// Current value of a field as handed to an edit action, with secret
+    /// references resolved to the secret itself
+    fn editable_value(&self, field_key: &str) -> Result<Option<Value>> {
+        let value = self.values.get(field_key).cloned();
+        match value.as_ref().and_then(|v| self.secret_ref(field_key, v)) {
+            Some(secret_ref) => Ok(Some(Value::String(secret_ref.resolve()?))),
+            None => Ok(value),
+        }
+    }
+
+    /// Hand a confirmed secret to its storage backend, returning the value to keep
+    fn store_if_secret(&self, field_key: &str, value: Value) -> Result<Value> {
+        match self.field_by_key(field_key).map(|f| &f.field_type) {
+            Some(FieldType::Secret { storage, .. }) => {
+                store_secret(storage, field_key, value.as_str().unwrap_or_default())
+            }
+            _ => Ok(value),
+        }
+    }
+
+    /// Bring a confirmed value into the field's canonical form
+    fn normalize_field_value(&self, field_key: &str, value: Value) -> Result<Value> {
+        match self.field_by_key(field_key) {
+            // Arrays are edited as text, one item per line
+            Some(field) if matches!(field.field_type, FieldType::Array { .. }) => match value {
+                Value::String(text) => SchemaValidator::parse_text(&field.field_type, &text),
+                value => SchemaValidator::normalize_value(&field.field_type, &value),
+            },
+            Some(field) => SchemaValidator::normalize_value(&field.field_type, &value),
+            None => Ok(value),
+        }
+    }
+
+    /// Store a value the user entered, unless a `before_change` hook
+    /// rejects it; returns whether it was stored
+    fn fire_change(&mut self, key: &str, value: Value) -> bool {
+        let value = match self.check_before_change(key, value) {
+            Ok(value) => value,
+            Err(reason) => {
+                self.message = Some(format!("{} rejected: {}", key, reason));
+                return false;
+            }
+        };
+
+        let old = self.store_value(key, value.clone());
+        self.invalidate_dependent_fields(key, ChangeSource::User);
+
+        if self.save_mode == SaveMode::Auto {
+            if let Err(e) = self.save_config() {
+                self.message = Some(format!("Failed to save config: {}", e));
+                // Most likely changed on disk under us; merge and retry
+                if let Err(e) = self.check_external_changes(true) {
+                    self.message = Some(format!("Failed to reload config: {}", e));
+                }
+            }
+        }
+
+        self.notify(key, old, Some(value), ChangeSource::User);
+        true
+    }
+
+    /// Show a value that is still being edited; the `before_change` hooks
+    /// check it, it is saved and its dependent fields are re-checked only
+    /// once the edit is confirmed
+    fn preview_change(&mut self, key: &str, value: Value) {
+        if self.preview.as_ref().is_none_or(|(k, _)| k != key) {
+            self.preview = Some((key.to_string(), self.values.get(key).cloned()));
+        }
+        let old = self.store_value(key, value.clone());
+        self.notify(key, old, Some(value), ChangeSource::User);
+    }
+
+    /// Put back the value a cancelled or rejected live edit started from
+    fn discard_preview(&mut self, key: &str) {
+        let Some((_, before)) = self.preview.take_if(|(k, _)| k == key) else {
+            return;
+        };
+        if self.values.get(key) != before.as_ref() {
+            self.apply_values(&[(key.to_string(), before)], ChangeSource::User);
+        }
+        // Nothing changed, so there is nothing to apply
+        self.pending_effects.retain(|(k, _)| k != key);
+    }
+
+    fn store_value(&mut self, key: &str, value: Value) -> Option<Value> {
+        // Live edits of one field undo together
+        if !(self.edit_mode && self.undo_key.as_deref() == Some(key)) {
+            self.push_undo();
+        }
+        self.undo_key = self.edit_mode.then(|| key.to_string());
+
+        let old = self.values.insert(key.to_string(), value);
+        self.invalid_fields.remove(key);
+        old
+    }
+
+    /// Run the `before_change` hooks, returning the value to store or why
+    /// it was rejected
+    fn check_before_change(&self, key: &str, mut value: Value) -> Result<Value, String> {
+        for hook in &self.before_hooks {
+            match hook(key, &value) {
+                BeforeChange::Accept => {}
+                BeforeChange::Reject(reason) => return Err(reason),
+                BeforeChange::Replace(replacement) => {
+                    value = self
+                        .normalize_field_value(key, replacement)
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    /// Tell handlers and subscribers that a setting changed
+    fn notify(&mut self, key: &str, old: Option<Value>, new: Option<Value>, source: ChangeSource) {
+        if source != ChangeSource::File && !self.pending_effects.iter().any(|(k, _)| k == key) {
+            self.pending_effects.push((key.to_string(), old.clone()));
+        }
+        let value = new.clone().unwrap_or(Value::Null);
+        for handler in &self.change_handlers {
+            handler(key, &value);
+        }
+        // Nobody listening is fine
+        let _ = self.change_tx.send(ChangeEvent {
+            key: key.to_string(),
+            old,
+            new,
+            source,
+        });
+    }
+
+    /// Run live-apply commands, after-change hooks and schema `on_change`
+    /// commands for changes the user is done editing and that are saved
+    ///
+    /// Each distinct command runs once, however many of its fields changed.
+    fn run_change_effects(&mut self) {
+        let mut statuses = Vec::new();
+        // Values restored after a failed apply are applied in another round
+        while self.run_effects_round(&mut statuses) {}
+        if !statuses.is_empty() {
+            self.message = Some(statuses.join("; "));
+        }
+    }
+
+    /// Returns true when live-applied changes were reverted
+    fn run_effects_round(&mut self, statuses: &mut Vec<String>) -> bool {
+        let editing = self.active_field.clone().filter(|_| self.edit_mode);
+        // Effects wait for the change to be saved, which in auto-save mode
+        // only fails on errors such as an unwritable file
+        let saved_to_file = self.save_mode == SaveMode::Explicit || self.config_path.is_some();
+        let (mut changes, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_effects)
+            .into_iter()
+            .partition(|(key, _)| {
+                Some(key) != editing.as_ref()
+                    && !(saved_to_file && self.base_values.get(key) != self.values.get(key))
+            });
+        self.pending_effects = pending;
+        if changes.is_empty() {
+            return false;
+        }
+
+        let rolled_back = self.live_apply(&changes, statuses);
+        changes.retain(|(key, _)| !rolled_back.contains(key));
+
+        let mut commands: Vec<(String, String)> = Vec::new();
+        for (key, _) in &changes {
+            let value = self.values.get(key).cloned().unwrap_or(Value::Null);
+            for hook in &self.after_hooks {
+                match hook(key, &value) {
+                    AfterChange::Ok => {}
+                    AfterChange::Message(message) => statuses.push(message),
+                    AfterChange::Failed(e) => {
+                        statuses.push(format!("Failed to apply {}: {}", key, e))
+                    }
+                }
+            }
+
+            let field_command = self.field_by_key(key).and_then(|f| f.on_change.clone());
+            let section_command = self.section_by_key(key).and_then(|s| s.on_change.clone());
+            for command in field_command.into_iter().chain(section_command) {
+                if !commands.iter().any(|(c, _)| *c == command) {
+                    commands.push((command, key.clone()));
+                }
+            }
+        }
+        for (command, key) in commands {
+            if let Err(e) = self.run_hook_command(&command, &key) {
+                statuses.push(format!("on_change for {} failed: {}", key, e));
+            }
+        }
+
+        !rolled_back.is_empty()
+    }
+
+    /// Run `apply_command`s for changed fields and start the countdown for
+    /// those with a `confirm_timeout`
+    ///
+    /// Returns the keys reverted because applying them failed.
+    fn live_apply(
+        &mut self,
+        changes: &[(String, Option<Value>)],
+        statuses: &mut Vec<String>,
+    ) -> Vec<String> {
+        let mut commands: Vec<(String, String)> = Vec::new();
+        let mut applied = Vec::new();
+        let mut confirm = Vec::new();
+        let mut timeout = None;
+        for (key, old) in changes {
+            let field = self.field_by_key(key);
+            let section = self.section_by_key(key);
+            let Some(command) = field
+                .and_then(|f| f.apply_command.clone())
+                .or_else(|| section.and_then(|s| s.apply_command.clone()))
+            else {
+                continue;
+            };
+            // A field's 0 opts out of its section's countdown
+            let seconds = field
+                .and_then(|f| f.confirm_timeout)
+                .or_else(|| section.and_then(|s| s.confirm_timeout))
+                .filter(|&seconds| seconds > 0);
+
+            // A restored value is applied without asking again
+            if !self.reverting.remove(key) {
+                if old.as_ref() == self.values.get(key) {
+                    continue;
+                }
+                applied.push((key.clone(), old.clone()));
+                if seconds.is_some() {
+                    timeout = timeout.max(seconds);
+                    confirm.push((key.clone(), old.clone()));
+                }
+            }
+            if !commands.iter().any(|(c, _)| *c == command) {
+                commands.push((command, key.clone()));
+            }
+        }
+
+        let mut failed = false;
+        for (command, key) in commands {
+            if let Err(e) = self.run_hook_command(&command, &key) {
+                statuses.push(format!("Failed to apply {}: {}", key, e));
+                failed = true;
+            }
+        }
+
+        if failed && !applied.is_empty() {
+            let keys: Vec<String> = applied.iter().map(|(key, _)| key.clone()).collect();
+            self.roll_back(applied);
+            statuses.push(format!("reverted {}", keys.join(", ")));
+            return keys;
+        }
+        if let Some(seconds) = timeout {
+            let timeout = Duration::from_secs(seconds);
+            match &mut self.live_apply_dialog {
+                Some(dialog) => dialog.extend(confirm, timeout),
+                None => self.live_apply_dialog = Some(LiveApplyDialog::new(confirm, timeout)),
+            }
+        }
+        Vec::new()
+    }
+
+    /// Put live-applied settings back to their previous values; their
+    /// apply commands run again on the next round of change effects
+    fn roll_back(&mut self, previous: Vec<(String, Option<Value>)>) {
+        let keys: Vec<String> = previous.iter().map(|(key, _)| key.clone()).collect();
+        self.reverting.extend(keys.iter().cloned());
+        self.apply_values(&previous, ChangeSource::User);
+        if let Err(e) = self.save_keys(&keys) {
+            self.message = Some(format!("Failed to save config: {}", e));
+        }
+    }
+
+    /// Run a schema hook command with the changed field and every other
+    /// field in the environment
+    fn run_hook_command(&self, command: &str, key: &str) -> Result<ActionOutput> {
+        let is_secret = self
+            .field_by_key(key)
+            .is_some_and(|f| matches!(f.field_type, FieldType::Secret { .. }));
+        let current = match self.values.get(key) {
+            Some(_) if is_secret => String::new(),
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+        let mut env = self.action_env();
+        env.push(("CHANGED_KEY".to_string(), key.to_string()));
+        let action = FieldAction::RunCommand {
+            command: command.to_string(),
+        };
+        action.run(&current, &env)
+    }
+
+    /// Dependencies of an option source: schema `depends_on` plus whatever
+    /// a registered provider declares
+    fn option_dependencies(&self, source: &OptionSource) -> Vec<String> {
+        let mut deps = source.depends_on().to_vec();
+        match source {
+            OptionSource::Function { name, .. } => {
+                deps.extend(self.option_resolver.provider_dependencies(name))
+            }
+            OptionSource::Provider { provider, .. } => {
+                deps.extend(self.option_resolver.provider_dependencies(provider))
+            }
+            _ => {}
+        }
+        deps
+    }
+
+    /// Drop cached widgets of fields depending on `changed_key` and re-check
+    /// their values against the refreshed options
+    ///
+    /// Options are resolved in the background; a check still running for a
+    /// field is cancelled, and the results are applied by `tick`.
+    fn invalidate_dependent_fields(&mut self, changed_key: &str, source: ChangeSource) {
+        let mut dependents = Vec::new();
+        for section in &self.schema.sections {
+            for field in &section.fields {
+                let Some(options) = field.field_type.option_source() else {
+                    continue;
+                };
+                if self
+                    .option_dependencies(options)
+                    .iter()
+                    .any(|k| k == changed_key)
+                {
+                    // Only enum values must be one of the options; other
+                    // fields (e.g. color palettes) just need a fresh widget
+                    let revalidate = matches!(field.field_type, FieldType::Enum { .. });
+                    dependents.push((
+                        format!("{}.{}", section.id, field.id),
+                        options.clone(),
+                        revalidate,
+                    ));
+                }
+            }
+        }
+
+        for (field_key, options, revalidate) in dependents {
+            self.option_resolver.cancel_pending(&field_key);
+            self.active_widgets.remove(&field_key);
+            if !revalidate {
+                continue;
+            }
+
+            let generation = self
+                .option_generations
+                .entry(field_key.clone())
+                .or_default();
+            *generation += 1;
+            let job = OptionJob {
+                check: OptionCheck {
+                    field_key,
+                    generation: *generation,
+                    source,
+                    options: Ok(Vec::new()),
+                },
+                source: options,
+                values: self.values.clone(),
+            };
+            // The worker only stops once the editor is dropped
+            let _ = self.option_worker().send(job);
+        }
+    }
+
+    /// Queue for background option checks, starting the thread that runs
+    /// them on first use
+    ///
+    /// Each check is a task on the thread's own runtime, so a check that is
+    /// cancelled, e.g. by a newer one for the same field, stops at once.
+    fn option_worker(&mut self) -> &mpsc::UnboundedSender<OptionJob> {
+        self.option_jobs.get_or_insert_with(|| {
+            let (jobs_tx, mut jobs) = mpsc::unbounded_channel::<OptionJob>();
+            let resolver = Arc::clone(&self.option_resolver);
+            let checks = self.option_checks_tx.clone();
+            std::thread::spawn(move || {
+                let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                else {
+                    return;
+                };
+                runtime.block_on(async move {
+                    while let Some(OptionJob {
+                        check,
+                        source,
+                        values,
+                    }) = jobs.recv().await
+                    {
+                        let resolver = Arc::clone(&resolver);
+                        let checks = checks.clone();
+                        tokio::spawn(async move {
+                            let options = resolver
+                                .resolve_source(&source, &check.field_key, &values, true)
+                                .await;
+                            // The editor may be gone by now
+                            let _ = checks.send(OptionCheck { options, ..check });
+                        });
+                    }
+                });
+            });
+            jobs_tx
+        })
+    }
+
+    /// Apply finished background option checks, flagging or resetting
+    /// values that are no longer among their field's options
+    fn apply_option_checks(&mut self) {
+        while let Ok(check) = self.option_checks_rx.try_recv() {
+            let OptionCheck {
+                field_key,
+                generation,
+                source,
+                options,
+            } = check;
+            // Superseded by a later change, or cancelled
+            if self.option_generations.get(&field_key) != Some(&generation) {
+                continue;
+            }
+            let Ok(options) = options else {
+                continue;
+            };
+            let Some(FieldType::Enum {
+                default,
+                on_dependency_change,
+                ..
+            }) = self.field_by_key(&field_key).map(|f| f.field_type.clone())
+            else {
+                continue;
+            };
+            let Some(current) = self
+                .values
+                .get(&field_key)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+            else {
+                continue;
+            };
+            if options.is_empty() || options.contains(&current) {
+                self.invalid_fields.remove(&field_key);
+                continue;
+            }
+
+            match on_dependency_change {
+                DependencyChangePolicy::Flag => {
+                    self.message = Some(format!(
+                        "{}: '{}' is no longer a valid option",
+                        field_key, current
+                    ));
+                    self.invalid_fields
+                        .insert(field_key, format!("'{}' is not available", current));
+                }
+                DependencyChangePolicy::Reset => {
+                    let replacement = default
+                        .filter(|d| options.contains(d))
+                        .or_else(|| options.first().cloned())
+                        .unwrap_or_default();
+                    let value = Value::String(replacement.clone());
+                    self.apply_values(&[(field_key.clone(), Some(value))], source);
+                    self.save_if_auto();
+                    self.message = Some(format!(
+                        "{} reset from '{}' to '{}'",
+                        field_key, current, replacement
+                    ));
+                    // Fields depending on this one may need resetting too
+                    self.invalidate_dependent_fields(&field_key, source);
+                }
+            }
+        }
+    }
+
+    fn report_option_warnings(&mut self) {
+        let warnings = self.option_resolver.take_warnings();
+        self.report_warnings(&warnings);
+    }
+
+    /// Show the first of `warnings` in the status line
+    pub(crate) fn report_warnings(&mut self, warnings: &[String]) {
+        if let Some(first) = warnings.first() {
+            self.message = Some(if warnings.len() > 1 {
+                format!("{} (+{} more warnings)", first, warnings.len() - 1)
+            } else {
+                first.clone()
+            });
+        }
+    }
+
+    /// Options for a field's widget
+    fn resolve_options(&self, field_key: &str, source: &OptionSource) -> Vec<String> {
+        self.option_resolver
+            .resolve_source_blocking(source, field_key, &self.values, false)
+            .unwrap_or_default()
+    }
+
+    /// Save only these keys, leaving other unsaved edits pending
+    fn save_keys(&mut self, keys: &[String]) -> Result<()> {
+        let mut saved = self.base_values.clone();
+        for key in keys {
+            match self.values.get(key) {
+                Some(value) => saved.insert(key.clone(), value.clone()),
+                None => saved.remove(key),
+            };
+        }
+        let unsaved = std::mem::replace(&mut self.values, saved);
+        let result = self.save_config();
+        self.values = unsaved;
+        result
+    }
+
+    fn save_config(&mut self) -> Result<()> {
+        if let Some(ref path) = self.config_path {
+            // Never overwrite edits made by someone else that haven't been merged
+            if let Some(watcher) = &self.watcher {
+                if watcher.is_modified()? {
+                    anyhow::bail!("{} was changed by another program", path.display());
+                }
+            }
+
+            self.backup_once(path)?;
+            ConfigSaver::save_toml(&self.to_store(), &self.schema, path)?;
+            self.mark_synced();
+        }
+        Ok(())
+    }
+
+    /// Back up the config file before it is first changed this session
+    fn backup_once(&self, path: &Path) -> Result<()> {
+        if let (Some(policy), false) = (&self.backup_policy, self.backed_up.get()) {
+            create_backup(path, policy)?;
+            self.backed_up.set(true);
+        }
+        Ok(())
+    }
+
+    /// Config file edited in place by [`Self::edit_raw_config`]; in explicit
+    /// save mode a temp copy is edited instead
+    fn raw_edit_path(&self) -> Option<PathBuf> {
+        self.config_path
+            .clone()
+            .filter(|_| self.save_mode == SaveMode::Auto)
+    }
+
+    /// Open the whole config in `$EDITOR` and load it back once it validates
+    fn edit_raw_config(&mut self) -> Result<()> {
+        let original = match self.raw_edit_path() {
+            Some(path) => {
+                // Start from the file as it is, with nothing left unsaved
+                self.check_external_changes(true)?;
+                if self.conflict_dialog.is_some() {
+                    return Ok(());
+                }
+                if !path.exists() || self.values != self.base_values {
+                    self.save_config()?;
+                }
+                std::fs::read_to_string(&path)?
+            }
+            None => ConfigSaver::to_toml_string(&self.to_store(), &self.schema)?,
+        };
+        self.open_raw_editor(original.clone(), original, None)
+    }
+
+    fn open_raw_editor(
+        &mut self,
+        original: String,
+        text: String,
+        line: Option<usize>,
+    ) -> Result<()> {
+        self.needs_redraw = true;
+        let editor = default_editor();
+        let edited = match self.raw_edit_path() {
+            Some(path) => {
+                self.backup_once(&path)?;
+                edit_file_in_editor(&path, &editor, line)?;
+                std::fs::read_to_string(&path)?
+            }
+            None => edit_in_editor(&text, &editor, "toml", line)?.unwrap_or(text),
+        };
+        if edited == original {
+            self.message = Some("Config file: no changes".to_string());
+            return Ok(());
         }
 
-        self.current_field = 0;
-        self.list_state.select(Some(0));
+        match check_config_text(&self.schema, &edited) {
+            (Some(values), errors) if errors.is_empty() => self.accept_raw_edit(values, &errors),
+            (values, errors) => {
+                self.raw_edit_dialog = Some(RawEditDialog::new(
+                    original,
+                    edited,
+                    errors,
+                    values.is_some(),
+                ));
+            }
+        }
+        Ok(())
     }
 
-    fn previous_section(&mut self) {
-        let visible = self.get_visible_sections();
-        if visible.is_empty() {
-            return;
+    fn finish_raw_edit(&mut self, action: RawEditAction, dialog: RawEditDialog) -> Result<()> {
+        match action {
+            RawEditAction::None => {}
+            RawEditAction::Reopen(line) => {
+                self.open_raw_editor(dialog.original, dialog.text, line)?
+            }
+            RawEditAction::Discard => {
+                if let Some(path) = self.raw_edit_path() {
+                    write_atomic(&path, dialog.original.as_bytes())?;
+                    if let Some(watcher) = &mut self.watcher {
+                        watcher.mark_synced();
+                    }
+                }
+                self.message = Some("Discarded edits to the config file".to_string());
+            }
+            RawEditAction::Accept => {
+                if let (Some(values), errors) = check_config_text(&self.schema, &dialog.text) {
+                    self.accept_raw_edit(values, &errors);
+                }
+            }
         }
+        Ok(())
+    }
 
-        // Find current in visible list and go to previous
-        let current_pos = visible
+    /// Take over hand-edited values and summarize what changed; settings
+    /// with `errors` are kept but flagged
+    fn accept_raw_edit(&mut self, mut values: HashMap<String, Value>, errors: &[RawError]) {
+        Self::merge_defaults(&self.schema, &mut values);
+        let updates = Self::value_updates(&self.values, &values);
+        let summary: Vec<String> = updates
             .iter()
-            .position(|(idx, _)| *idx == self.current_section);
-        if let Some(pos) = current_pos {
-            let prev_pos = if pos == 0 { visible.len() - 1 } else { pos - 1 };
-            self.current_section = visible[prev_pos].0;
-        } else {
-            // Current not visible, go to first visible
-            self.current_section = visible[0].0;
+            .map(|(key, new)| {
+                format!(
+                    "{}: {} → {}",
+                    key,
+                    self.value_label(key, self.values.get(key)),
+                    self.value_label(key, new.as_ref())
+                )
+            })
+            .collect();
+
+        if !updates.is_empty() {
+            self.push_undo();
+            self.undo_key = None;
+            self.apply_values(&updates, ChangeSource::User);
+        }
+        for error in errors {
+            if let Some(key) = error
+                .key
+                .as_ref()
+                .filter(|k| self.field_by_key(k).is_some())
+            {
+                self.invalid_fields
+                    .insert(key.clone(), error.message.clone());
+            }
+        }
+        if self.raw_edit_path().is_some() {
+            self.mark_synced();
         }
 
-        self.current_field = 0;
-        self.list_state.select(Some(0));
+        if updates.is_empty() {
+            self.message = Some("Config file: no settings changed".to_string());
+        } else {
+            self.output_dialog = Some(OutputDialog::new(
+                format!("Config file: {} change(s)", updates.len()),
+                summary.join("\n"),
+            ));
+        }
     }
 
-    fn next_field(&mut self) {
-        let field_count = self
-            .get_current_section()
-            .map(|s| s.fields.len())
-            .unwrap_or(0);
-        if field_count > 0 {
-            self.current_field = (self.current_field + 1) % field_count;
-            self.list_state.select(Some(self.current_field));
+    /// Convert the flat map ("general.wallpaper") to a nested structure
+    fn to_store(&self) -> ConfigStore {
+        let mut store = ConfigStore::new();
+        for (key, value) in &self.values {
+            // Null keeps the saver from writing the schema default instead
+            if self.omit_defaults
+                && !self.differs_from_default(key)
+                && self.field_by_key(key).is_some()
+            {
+                store.set_nested(key, Value::Null);
+                continue;
+            }
+            store.set_nested(key, value.clone());
         }
+        store
     }
 
-    fn previous_field(&mut self) {
-        let field_count = self
-            .get_current_section()
-            .map(|s| s.fields.len())
-            .unwrap_or(0);
-        if field_count > 0 {
-            self.current_field = if self.current_field == 0 {
-                field_count - 1
-            } else {
-                self.current_field - 1
-            };
-            self.list_state.select(Some(self.current_field));
-        }
+    fn open_review(&mut self) {
+        self.review_dialog = Some(ReviewDialog::new(self.save_mode == SaveMode::Explicit));
+        self.refresh_review();
     }
 
-    fn activate_current_field(&mut self) -> Result<()> {
-        let field_key = self.get_current_field_key();
-        let widget_type = self.get_current_field().map(|f| f.ui_widget);
+    fn refresh_review(&mut self) {
+        let changes = self.pending_changes();
+        let labels = changes
+            .iter()
+            .map(|c| {
+                (
+                    self.value_label(&c.key, c.old.as_ref()),
+                    self.value_label(&c.key, c.new.as_ref()),
+                )
+            })
+            .collect();
+        let diff = match self.review_diff() {
+            Ok(diff) => diff,
+            Err(e) => vec![DiffLine::Same(e.to_string())],
+        };
+        if let Some(dialog) = &mut self.review_dialog {
+            dialog.set_changes(changes, labels, diff);
+        }
+    }
 
-        if let Some(wt) = widget_type {
-            // Create widget if not exists
-            if !self.active_widgets.contains_key(&field_key) {
-                let field = self.get_current_field().unwrap();
-                let widget = self.build_widget_for_field(field)?;
-                self.active_widgets.insert(field_key.clone(), widget);
-            }
+    /// Diff of the config file against what saving would write, secrets masked
+    fn review_diff(&self) -> Result<Vec<DiffLine>> {
+        let Some(path) = &self.config_path else {
+            return Ok(Vec::new());
+        };
+        let current = std::fs::read_to_string(path).unwrap_or_default();
+        let new = ConfigSaver::to_toml_string(&self.to_store(), &self.schema)?;
 
-            // Activate widget
-            if let Some(widget) = self.active_widgets.get_mut(&field_key) {
-                // Set current value
-                if let Some(value) = self.values.get(&field_key) {
-                    widget.set_value(value.clone());
-                }
+        let secrets: Vec<&str> = self
+            .schema
+            .sections
+            .iter()
+            .flat_map(|section| {
+                section
+                    .fields
+                    .iter()
+                    .filter(|f| matches!(f.field_type, FieldType::Secret { .. }))
+                    .map(move |f| format!("{}.{}", section.id, f.id))
+            })
+            .flat_map(|key| {
+                [self.values.get(&key), self.base_values.get(&key)]
+                    .into_iter()
+                    .flatten()
+                    .filter(move |v| self.secret_ref(&key, v).is_none())
+            })
+            .filter_map(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let mask = |text: &str| {
+            secrets.iter().fold(text.to_string(), |text, secret| {
+                text.replace(secret, "••••••••")
+            })
+        };
 
-                // Activate the widget (transitions to editing state)
-                widget.activate();
+        Ok(diff_lines(&mask(&current), &mask(&new)))
+    }
 
-                // Enter edit mode based on widget type
-                match &wt {
-                    UIWidget::TextInput
-                    | UIWidget::NumberInput
-                    | UIWidget::Dropdown
-                    | UIWidget::DropdownSearchable => {
-                        self.edit_mode = true;
-                        self.active_field = Some(field_key.clone());
-                    }
-                    UIWidget::Toggle => {
-                        // Toggle activates immediately - get value will be handled below
-                    }
-                    _ => {}
+    fn save_reviewed(&mut self) {
+        let count = self.pending_changes().len();
+        match self.save_config() {
+            Ok(()) => {
+                self.review_dialog = None;
+                self.message = Some(format!("Saved {} change(s)", count));
+            }
+            Err(e) => {
+                self.message = Some(format!("Failed to save config: {}", e));
+                if let Err(e) = self.check_external_changes(true) {
+                    self.message = Some(format!("Failed to reload config: {}", e));
                 }
+                self.refresh_review();
             }
+        }
+    }
 
-            // Handle toggle fire change outside of the widget borrow
-            if matches!(wt, UIWidget::Toggle) {
-                if let Some(widget) = self.active_widgets.get(&field_key) {
-                    self.fire_change(&field_key, widget.get_value());
-                }
+    /// Put a field back to its value in the config file
+    fn revert(&mut self, key: &str) {
+        let old = self.base_values.get(key).cloned();
+        self.push_undo();
+        self.undo_key = None;
+        self.apply_values(&[(key.to_string(), old)], ChangeSource::User);
+        self.message = Some(format!("Reverted {}", key));
+        if self.save_mode == SaveMode::Auto {
+            if let Err(e) = self.save_config() {
+                self.message = Some(format!("Failed to save config: {}", e));
             }
         }
+        self.refresh_review();
+    }
 
-        Ok(())
+    /// Record the file as matching the in-memory values
+    fn mark_synced(&mut self) {
+        if let Some(watcher) = &mut self.watcher {
+            watcher.mark_synced();
+        }
+        self.base_values = self.values.clone();
     }
 
-    fn fire_change(&mut self, key: &str, value: Value) {
-        self.values.insert(key.to_string(), value.clone());
+    /// Merge changes other programs made to the config file
+    ///
+    /// Keys changed only on disk are taken over; keys changed both here and
+    /// on disk open the conflict dialog. `force` skips the poll interval.
+    fn check_external_changes(&mut self, force: bool) -> Result<()> {
+        // The file is being edited by hand until the raw edit is resolved
+        if self.conflict_dialog.is_some() || self.raw_edit_dialog.is_some() {
+            return Ok(());
+        }
+        let Some(watcher) = &mut self.watcher else {
+            return Ok(());
+        };
+        let content = if force {
+            watcher.check()?
+        } else {
+            watcher.poll()?
+        };
+        let Some(content) = content else {
+            return Ok(());
+        };
 
-        self.invalidate_dependent_fields(key);
+        let mut theirs =
+            ConfigLoader::from_toml_string_with_expansion(&content, false)?.as_flat_map();
+        Self::merge_defaults(&self.schema, &mut theirs);
+        let outcome = merge_values(&self.base_values, &self.values, &theirs);
 
-        if let Err(e) = self.save_config() {
-            eprintln!("Failed to save config: {}", e);
+        self.apply_values(&outcome.updated, ChangeSource::File);
+        if let Some(watcher) = &mut self.watcher {
+            watcher.mark_synced();
         }
-
-        for handler in &self.change_handlers {
-            handler(key, &value);
+        self.base_values = theirs;
+
+        if outcome.conflicts.is_empty() {
+            self.message = Some(format!(
+                "Reloaded {} change(s) from disk",
+                outcome.updated.len()
+            ));
+            if self.save_mode == SaveMode::Auto && self.values != self.base_values {
+                self.save_config()?;
+            }
+        } else {
+            let labels = outcome
+                .conflicts
+                .iter()
+                .map(|c| {
+                    (
+                        self.value_label(&c.key, c.ours.as_ref()),
+                        self.value_label(&c.key, c.theirs.as_ref()),
+                    )
+                })
+                .collect();
+            self.conflict_dialog = Some(ConflictDialog::new(outcome.conflicts, labels));
         }
+        Ok(())
     }
 
-    fn invalidate_dependent_fields(&mut self, changed_key: &str) {
-        let mut fields_to_invalidate = Vec::new();
+    fn resolve_conflicts(&mut self, resolved: Vec<(String, Option<Value>)>) {
+        self.apply_values(&resolved, ChangeSource::File);
+        if self.save_mode == SaveMode::Explicit {
+            self.message = Some("Merged changes from disk".to_string());
+            return;
+        }
+        match self.save_config() {
+            Ok(()) => self.message = Some("Merged changes from disk".to_string()),
+            Err(e) => self.message = Some(format!("Failed to save config: {}", e)),
+        }
+    }
 
-        for section in &self.schema.sections {
-            for field in &section.fields {
-                if let FieldType::Enum {
-                    options_source: crate::schema::OptionSource::Script { depends_on, .. },
-                    ..
-                } = &field.field_type
-                {
-                    if depends_on.contains(&changed_key.to_string()) {
-                        let field_key = format!("{}.{}", section.id, field.id);
-                        fields_to_invalidate.push(field_key);
+    /// Set or remove values that changed outside the editor and notify handlers
+    fn apply_values(&mut self, updates: &[(String, Option<Value>)], source: ChangeSource) {
+        for (key, value) in updates {
+            if self.preview.as_ref().is_some_and(|(k, _)| k == key) {
+                self.preview = None;
+            }
+            let old = match value {
+                Some(value) => self.values.insert(key.clone(), value.clone()),
+                None => self.values.remove(key),
+            };
+            self.invalid_fields.remove(key);
+
+            // An open editor shows the new value; cached widgets are rebuilt,
+            // as are secret editors so a new backend reference isn't resolved
+            let editing = self.edit_mode && self.active_field.as_deref() == Some(key);
+            let secret = self
+                .field_by_key(key)
+                .is_some_and(|f| matches!(f.field_type, FieldType::Secret { .. }));
+            match value {
+                Some(value) if editing && !secret => {
+                    if let Some(widget) = self.active_widgets.get_mut(key) {
+                        widget.set_value(value.clone());
+                    }
+                }
+                _ => {
+                    if editing {
+                        self.edit_mode = false;
+                        self.active_field = None;
                     }
+                    self.active_widgets.remove(key);
                 }
             }
-        }
 
-        for field_key in fields_to_invalidate {
-            self.active_widgets.remove(&field_key);
+            self.notify(key, old, value.clone(), source);
         }
     }
 
-    fn save_config(&self) -> Result<()> {
-        if let Some(ref path) = self.config_path {
-            use crate::config::{ConfigSaver, ConfigStore};
+    fn value_label(&self, key: &str, value: Option<&Value>) -> String {
+        let is_secret = self
+            .field_by_key(key)
+            .is_some_and(|f| matches!(f.field_type, FieldType::Secret { .. }));
+        match value {
+            None => "(unset)".to_string(),
+            Some(_) if is_secret => "••••••••".to_string(),
+            Some(Value::String(s)) => s.clone(),
+            Some(v) => v.to_string(),
+        }
+    }
 
-            // Convert flat map ("general.wallpaper") to nested structure
-            let mut store = ConfigStore::new();
-            for (key, value) in &self.values {
-                store.set_nested(key, value.clone());
-            }
+    fn open_restore_dialog(&mut self) -> Result<()> {
+        let Some(path) = &self.config_path else {
+            return Ok(());
+        };
+        let policy = self.backup_policy.clone().unwrap_or_default();
+        let backups = list_backups(path, &policy)?;
+        let current = std::fs::read_to_string(path).unwrap_or_default();
+        self.restore_dialog = Some(RestoreDialog::new(backups, current));
+        Ok(())
+    }
 
-            ConfigSaver::save_toml(&store, &self.schema, path)?;
+    /// Replace the config with a backup and reload every value from it
+    fn restore(&mut self, backup: &crate::config::Backup) -> Result<()> {
+        let Some(path) = self.config_path.clone() else {
+            return Ok(());
+        };
+        let policy = self.backup_policy.clone().unwrap_or_default();
+        restore_backup(&path, backup, &policy)?;
+
+        let mut values = ConfigLoader::from_toml_file_with_expansion(&path, false)?.as_flat_map();
+        Self::merge_defaults(&self.schema, &mut values);
+
+        let changed = Self::value_updates(&self.values, &values);
+        let old = std::mem::replace(&mut self.values, values);
+        self.active_widgets.clear();
+        self.invalid_fields.clear();
+        self.mark_synced();
+        for (key, value) in changed {
+            let previous = old.get(&key).cloned();
+            self.notify(&key, previous, value, ChangeSource::File);
         }
+
+        self.message = Some(format!("Restored backup from {}", backup.timestamp));
         Ok(())
     }
 
     fn execute_external_editor_for_field(&mut self) -> Result<()> {
-        let field_key = self.get_current_field_key();
-        let current_value = self
-            .values
-            .get(&field_key)
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        // Get editor from env or use default
-        let editor = std::env::var("EDITOR").unwrap_or_else(|_| "nano".to_string());
-
-        // Determine file extension based on field type
-        let extension = if let Some(field) = self.get_current_field() {
-            match &field.field_type {
-                crate::schema::FieldType::Path { file_type, .. } => match file_type {
-                    Some(crate::schema::FileTypeFilter::Json) => "json",
-                    Some(crate::schema::FileTypeFilter::Image) => "png",
-                    _ => "txt",
-                },
-                _ => "txt",
-            }
-        } else {
-            "txt"
+        // Give the temp file an extension editors recognise
+        let extension = match self.get_current_field().map(|f| &f.field_type) {
+            Some(FieldType::Path {
+                file_type: Some(FileTypeFilter::Json),
+                ..
+            }) => "json",
+            _ => "txt",
         };
-
-        let action = super::actions::FieldAction::ExternalEditor {
-            editor,
+        let action = FieldAction::ExternalEditor {
+            editor: default_editor(),
             extension: extension.to_string(),
         };
-
-        if let Ok(Some(new_value)) = action.execute(&current_value) {
-            self.fire_change(&field_key, Value::String(new_value.trim().to_string()));
-            self.message = Some(format!("Updated {} from external editor", field_key));
-        } else {
-            self.message = Some("External editor cancelled or no changes".to_string());
-        }
-
+        self.run_field_action("$EDITOR", &action);
         Ok(())
     }
 
@@ -469,6 +2580,19 @@ impl SchemaTUI {
             .and_then(|s| s.fields.get(self.current_field))
     }
 
+    fn field_by_key(&self, field_key: &str) -> Option<&SchemaField> {
+        let (_, field_id) = field_key.split_once('.')?;
+        self.section_by_key(field_key)?
+            .fields
+            .iter()
+            .find(|f| f.id == field_id)
+    }
+
+    fn section_by_key(&self, field_key: &str) -> Option<&SchemaSection> {
+        let (section_id, _) = field_key.split_once('.')?;
+        self.schema.sections.iter().find(|s| s.id == section_id)
+    }
+
     fn get_current_field_key(&self) -> String {
         if let Some(section) = self.get_current_section() {
             if let Some(field) = self.get_current_field() {
@@ -492,7 +2616,10 @@ impl SchemaTUI {
                     .and_then(|v| v.as_str())
                     .or(default.as_deref())
                     .unwrap_or("");
-                Box::new(TextInput::new(&field.label, initial))
+                match &field.ui_widget {
+                    UIWidget::Multiline => Box::new(MultilineEditor::new(&field.label, initial)),
+                    _ => Box::new(TextInput::new(&field.label, initial)),
+                }
             }
 
             FieldType::Boolean { default } => {
@@ -504,14 +2631,25 @@ impl SchemaTUI {
                 Box::new(Toggle::new(&field.label, initial))
             }
 
-            FieldType::Number { default, min, max } => {
+            FieldType::Number {
+                default,
+                min,
+                max,
+                unit,
+            } => {
                 let initial = self
                     .values
                     .get(&field_key)
                     .and_then(|v| v.as_i64())
                     .or(*default)
                     .unwrap_or(0);
-                Box::new(NumberInput::new(&field.label, initial, *min, *max))
+                let label = Self::label_with_unit(&field.label, unit.as_deref());
+                match (&field.ui_widget, min, max) {
+                    (UIWidget::Slider, Some(min), Some(max)) => Box::new(
+                        Slider::new(label, initial as f64, *min as f64, *max as f64, 1.0).integer(),
+                    ),
+                    _ => Box::new(NumberInput::new(label, initial, *min, *max)),
+                }
             }
 
             FieldType::Float {
@@ -519,6 +2657,7 @@ impl SchemaTUI {
                 min,
                 max,
                 step,
+                unit,
             } => {
                 let initial = self
                     .values
@@ -526,36 +2665,34 @@ impl SchemaTUI {
                     .and_then(|v| v.as_f64())
                     .or(*default)
                     .unwrap_or(0.0);
-                Box::new(FloatInput::new(&field.label, initial, *min, *max, *step))
+                let label = Self::label_with_unit(&field.label, unit.as_deref());
+                match (&field.ui_widget, min, max) {
+                    (UIWidget::Slider, Some(min), Some(max)) => {
+                        // Without an explicit step, move in hundredths of the range
+                        let step = step.unwrap_or((max - min) / 100.0);
+                        Box::new(Slider::new(label, initial, *min, *max, step))
+                    }
+                    _ => Box::new(FloatInput::new(label, initial, *min, *max, *step)),
+                }
+            }
+
+            FieldType::Duration { default, .. } | FieldType::ByteSize { default, .. } => {
+                let initial = match (field.field_type.quantity(), self.values.get(&field_key)) {
+                    (Some(quantity), Some(value)) => quantity
+                        .from_value(value)
+                        .map(|amount| quantity.canonical(amount))
+                        .unwrap_or_else(|_| value.as_str().unwrap_or_default().to_string()),
+                    _ => default.clone().unwrap_or_default(),
+                };
+                Box::new(TextInput::new(&field.label, initial))
             }
 
             FieldType::Enum {
                 options_source,
                 default,
+                ..
             } => {
-                let options = match options_source {
-                    crate::schema::OptionSource::Static { values } => values.clone(),
-                    crate::schema::OptionSource::Function { name } => self
-                        .option_resolver
-                        .resolve_from_provider(name)
-                        .unwrap_or_default(),
-                    crate::schema::OptionSource::Provider { provider } => self
-                        .option_resolver
-                        .resolve_from_provider(provider)
-                        .unwrap_or_default(),
-                    crate::schema::OptionSource::Script { command, .. } => self
-                        .option_resolver
-                        .resolve_from_script_sync(command, &self.values)
-                        .unwrap_or_default(),
-                    crate::schema::OptionSource::FileList {
-                        directory,
-                        pattern,
-                        extract,
-                    } => self
-                        .option_resolver
-                        .resolve_from_file_list(directory, pattern, extract.as_deref())
-                        .unwrap_or_default(),
-                };
+                let options = self.resolve_options(&field_key, options_source);
 
                 let initial = self
                     .values
@@ -580,12 +2717,59 @@ impl SchemaTUI {
                     .unwrap_or("");
                 Box::new(TextInput::new(&field.label, initial))
             }
+
+            FieldType::Color {
+                default,
+                format,
+                palette,
+            } => {
+                let initial = self
+                    .values
+                    .get(&field_key)
+                    .and_then(|v| v.as_str())
+                    .or(default.as_deref())
+                    .unwrap_or("#000000");
+                let palette = palette
+                    .as_ref()
+                    .map(|source| self.resolve_options(&field_key, source))
+                    .unwrap_or_default();
+                Box::new(ColorPicker::new(&field.label, initial, *format, palette))
+            }
+
+            FieldType::Array { default, .. } => {
+                let initial = self
+                    .values
+                    .get(&field_key)
+                    .cloned()
+                    .or_else(|| default.clone().map(Value::Array))
+                    .map(|value| SchemaValidator::format_text(&value))
+                    .unwrap_or_default();
+                Box::new(MultilineEditor::new(&field.label, &initial))
+            }
+
+            // Inline secrets are filled in on activation; secrets in a backend
+            // are only fetched when revealed or edited
+            FieldType::Secret { .. } => {
+                let widget = SecretInput::new(&field.label, "");
+                match self
+                    .values
+                    .get(&field_key)
+                    .and_then(|v| self.secret_ref(&field_key, v))
+                {
+                    Some(secret_ref) => Box::new(
+                        widget.with_stored(secret_ref.backend_name(), move || secret_ref.resolve()),
+                    ),
+                    None => Box::new(widget),
+                }
+            }
         };
 
         Ok(widget)
     }
 
-    fn render(&mut self, frame: &mut Frame) {
+    /// Draw the editor into `area`, e.g. one pane of a host application
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        self.hit_areas.pane = area;
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
@@ -594,12 +2778,46 @@ impl SchemaTUI {
                 Constraint::Min(0),
                 Constraint::Length(4),
             ])
-            .split(frame.area());
+            .split(area);
 
         self.render_header(frame, chunks[0]);
-        self.render_tabs(frame, chunks[1]);
+        self.hit_areas.tabs = chunks[1];
+        self.hit_areas.tab_titles = self.render_tabs(frame, chunks[1]);
+        self.hit_areas.fields = chunks[2];
+        self.hit_areas.field_rows.clear();
         self.render_content(frame, chunks[2]);
         self.render_footer(frame, chunks[3]);
+
+        if let Some(dialog) = &self.restore_dialog {
+            dialog.render(frame, area, &self.theme);
+        }
+        if let Some(dialog) = &self.review_dialog {
+            dialog.render(frame, area, &self.theme);
+        }
+        if let Some(dialog) = &self.search_dialog {
+            dialog.render(frame, area, &self.theme);
+        }
+        if let Some(palette) = &self.palette {
+            palette.render(frame, area, &self.theme);
+        }
+        if let Some(help) = &self.help_dialog {
+            help.render(frame, area, &self.theme, &self.keymap);
+        }
+        if let Some(output) = &self.output_dialog {
+            output.render(frame, area, &self.theme);
+        }
+        if let Some(dialog) = &self.raw_edit_dialog {
+            dialog.render(frame, area, &self.theme);
+        }
+        if let Some(dialog) = &self.confirm_dialog {
+            dialog.render(frame, area, &self.theme);
+        }
+        if let Some(dialog) = &self.conflict_dialog {
+            dialog.render(frame, area, &self.theme);
+        }
+        if let Some(dialog) = &self.live_apply_dialog {
+            dialog.render(frame, area, &self.theme);
+        }
     }
 
     fn render_header(&self, frame: &mut Frame, area: Rect) {
@@ -620,8 +2838,10 @@ impl SchemaTUI {
         frame.render_widget(header, area);
     }
 
-    fn render_tabs(&self, frame: &mut Frame, area: Rect) {
+    /// Draw the section tabs, returning the area of each visible tab
+    fn render_tabs(&self, frame: &mut Frame, area: Rect) -> Vec<(Rect, usize)> {
         let visible = self.get_visible_sections();
+        let sections: Vec<usize> = visible.iter().map(|(idx, _)| *idx).collect();
 
         let all_titles: Vec<String> = visible
             .iter()
@@ -635,7 +2855,7 @@ impl SchemaTUI {
             .collect();
 
         if all_titles.is_empty() {
-            return;
+            return Vec::new();
         }
 
         // Find position of current section in visible list
@@ -654,6 +2874,7 @@ impl SchemaTUI {
 
         // If all tabs fit, show them all
         if total_width <= available_width {
+            let areas = Self::tab_areas(area, &all_titles, &sections);
             let tabs = Tabs::new(all_titles)
                 .block(Block::default().borders(Borders::ALL).title("Sections"))
                 .select(selected)
@@ -665,7 +2886,7 @@ impl SchemaTUI {
                 );
 
             frame.render_widget(tabs, area);
-            return;
+            return areas;
         }
 
         // Need to scroll - calculate viewport to center selected tab
@@ -726,6 +2947,7 @@ impl SchemaTUI {
             "Sections"
         };
 
+        let areas = Self::tab_areas(area, &visible_titles, &sections[start_idx..end_idx]);
         let tabs = Tabs::new(visible_titles)
             .block(
                 Block::default()
@@ -741,6 +2963,26 @@ impl SchemaTUI {
             );
 
         frame.render_widget(tabs, area);
+        areas
+    }
+
+    /// Screen area of each tab title, laid out the way `Tabs` draws them
+    fn tab_areas(area: Rect, titles: &[String], sections: &[usize]) -> Vec<(Rect, usize)> {
+        let mut x = area.x + 1;
+        let mut areas = Vec::new();
+        for (title, &section) in titles.iter().zip(sections) {
+            // One column of padding either side, then a one-column divider
+            let width = Line::from(title.as_str()).width() as u16 + 2;
+            let tab = Rect {
+                x,
+                y: area.y + 1,
+                width,
+                height: 1,
+            };
+            areas.push((tab.intersection(area), section));
+            x = x.saturating_add(width + 1);
+        }
+        areas
     }
 
     fn render_content(&mut self, frame: &mut Frame, area: Rect) {
@@ -798,13 +3040,35 @@ impl SchemaTUI {
                     Style::default().fg(self.theme.text)
                 };
 
-                let content = Line::from(vec![
+                // Marks values that differ from the schema default
+                let marker = if self.differs_from_default(&field_key) {
+                    "● "
+                } else {
+                    "  "
+                };
+                let mut spans = vec![
+                    Span::styled(marker, Style::default().fg(self.theme.accent)),
                     Span::styled(
                         format!("{}: ", field.label),
                         Style::default().add_modifier(Modifier::BOLD),
                     ),
-                    Span::styled(value_display, style),
-                ]);
+                ];
+                if let FieldType::Color { .. } = field.field_type {
+                    if let Ok(color) = ColorValue::parse(&value_display) {
+                        spans.push(Span::styled(
+                            "██ ",
+                            Style::default().fg(Color::Rgb(color.r, color.g, color.b)),
+                        ));
+                    }
+                }
+                spans.push(Span::styled(value_display, style));
+                if let Some(reason) = self.invalid_fields.get(&field_key) {
+                    spans.push(Span::styled(
+                        format!("  ⚠ {}", reason),
+                        Style::default().fg(self.theme.error),
+                    ));
+                }
+                let content = Line::from(spans);
 
                 items.push(ListItem::new(content));
 
@@ -820,10 +3084,8 @@ impl SchemaTUI {
             self.list_state.select(Some(visual_index));
 
             let list = List::new(items)
-                .block(Block::default().borders(Borders::ALL).title(format!(
-                    "{} (↑↓ navigate, Enter edit, Space toggle)",
-                    section_title
-                )))
+                // Key hints live in the footer, built from the keymap
+                .block(Block::default().borders(Borders::ALL).title(section_title))
                 .highlight_style(
                     Style::default()
                         .bg(self.theme.highlight_bg)
@@ -834,6 +3096,16 @@ impl SchemaTUI {
 
             frame.render_stateful_widget(list, area, &mut self.list_state);
 
+            // Rows the fields ended up on after scrolling, for mouse clicks
+            let offset = self.list_state.offset();
+            let rows = area.height.saturating_sub(2) as usize;
+            self.hit_areas.field_rows = field_to_visual_map
+                .iter()
+                .enumerate()
+                .filter(|(_, &visual)| visual >= offset && visual - offset < rows)
+                .map(|(field_idx, &visual)| (area.y + 1 + (visual - offset) as u16, field_idx))
+                .collect();
+
             // Render active widget if editing
             if self.edit_mode {
                 if let Some(field_key) = &self.active_field {
@@ -842,7 +3114,8 @@ impl SchemaTUI {
                         let visual_pos = field_to_visual_map
                             .get(self.current_field)
                             .copied()
-                            .unwrap_or(0);
+                            .unwrap_or(0)
+                            .saturating_sub(self.list_state.offset());
                         let widget_y = area.y + 2 + visual_pos as u16;
                         let widget_area = Rect {
                             x: area.x + 1,
@@ -868,7 +3141,7 @@ impl SchemaTUI {
                         }
 
                         // Render widget at the specific field position
-                        widget.render(frame, widget_area, true, &self.theme);
+                        widget.render(frame, widget_area, self.hit_areas.pane, true, &self.theme);
                     }
                 }
             }
@@ -876,26 +3149,55 @@ impl SchemaTUI {
     }
 
     fn render_footer(&self, frame: &mut Frame, area: Rect) {
-        let mut help_spans = vec![
-            Span::styled("Tab/←→", Style::default().fg(self.theme.primary)),
-            Span::raw(" sections  "),
-            Span::styled("↑↓", Style::default().fg(self.theme.primary)),
-            Span::raw(" fields  "),
-            Span::styled("Enter", Style::default().fg(self.theme.primary)),
-            Span::raw(" edit  "),
-        ];
+        let hints: Vec<(&str, String)> = if self.edit_mode {
+            vec![
+                ("edit.confirm", "confirm".to_string()),
+                ("edit.cancel", "cancel".to_string()),
+            ]
+        } else {
+            let pending = self.pending_changes().len();
+            [
+                (Command::NextSection, "sections".to_string()),
+                (Command::NextField, "fields".to_string()),
+                (Command::EditField, "edit".to_string()),
+                (Command::OpenEditor, "$EDITOR".to_string()),
+                (Command::Palette, "commands".to_string()),
+                (Command::Search, "search".to_string()),
+                (Command::Review, "changes".to_string()),
+                (Command::Save, format!("save ({})", pending)),
+                (Command::Backups, "backups".to_string()),
+                (Command::Help, "keys".to_string()),
+                (Command::Quit, "quit".to_string()),
+            ]
+            .into_iter()
+            .filter(|(command, _)| {
+                self.command_available(command)
+                    && (*command != Command::Save || self.save_mode == SaveMode::Explicit)
+            })
+            .filter_map(|(command, text)| Some((command.action()?, text)))
+            .collect()
+        };
 
-        // Add 'e' for external editor if current field is a path
-        if let Some(field) = self.get_current_field() {
-            if matches!(field.field_type, crate::schema::FieldType::Path { .. }) && !self.edit_mode
-            {
-                help_spans.push(Span::styled("e", Style::default().fg(self.theme.primary)));
-                help_spans.push(Span::raw(" $EDITOR  "));
+        let mut help_spans = Vec::new();
+        if !self.edit_mode {
+            for action in self.focused_actions() {
+                if let Ok(key) = KeyBinding::parse(&action.key) {
+                    help_spans.push(Span::styled(
+                        key.to_string(),
+                        Style::default().fg(self.theme.accent),
+                    ));
+                    help_spans.push(Span::raw(format!(" {}  ", action.label)));
+                }
             }
         }
-
-        help_spans.push(Span::styled("q", Style::default().fg(self.theme.primary)));
-        help_spans.push(Span::raw(" quit"));
+        for (action, text) in hints {
+            let keys = self.keymap.label(action);
+            if keys.is_empty() {
+                continue;
+            }
+            help_spans.push(Span::styled(keys, Style::default().fg(self.theme.primary)));
+            help_spans.push(Span::raw(format!(" {}  ", text)));
+        }
 
         let mut lines = vec![Line::from(help_spans)];
 
@@ -921,11 +3223,43 @@ impl SchemaTUI {
     }
 
     fn get_value_display(&self, key: &str, field: &SchemaField) -> String {
-        if let Some(value) = self.values.get(key) {
+        if let FieldType::Secret { storage, .. } = &field.field_type {
+            return match self.values.get(key).and_then(|v| v.as_str()) {
+                Some(s) => match SecretRef::for_storage(s, storage) {
+                    Some(secret_ref) => format!("•••••••• ({})", secret_ref.backend_name()),
+                    None if s.is_empty() => String::new(),
+                    None => "••••••••".to_string(),
+                },
+                None => String::new(),
+            };
+        }
+
+        if let Some(quantity) = field.field_type.quantity() {
+            let value = self
+                .values
+                .get(key)
+                .cloned()
+                .or_else(|| Self::get_field_default(&field.field_type));
+            return match value {
+                Some(value) => match quantity.from_value(&value) {
+                    Ok(amount) => quantity.humanize(amount),
+                    Err(_) => value.as_str().unwrap_or_default().to_string(),
+                },
+                None => String::new(),
+            };
+        }
+
+        let display = if let Some(value) = self.values.get(key) {
             match value {
-                Value::String(s) => s.clone(),
+                Value::String(s) => match s.split_once('\n') {
+                    Some((first, rest)) => {
+                        format!("{} … (+{} lines)", first, rest.matches('\n').count() + 1)
+                    }
+                    None => s.clone(),
+                },
                 Value::Number(n) => n.to_string(),
                 Value::Bool(b) => if *b { "✓ true" } else { "✗ false" }.to_string(),
+                Value::Array(items) => Self::array_display(items),
                 _ => value.to_string(),
             }
         } else {
@@ -938,9 +3272,40 @@ impl SchemaTUI {
                 FieldType::Boolean { default } => {
                     if *default { "✓ true" } else { "✗ false" }.to_string()
                 }
-                FieldType::Enum { default, .. } => default.clone().unwrap_or_default(),
+                FieldType::Enum { default, .. } | FieldType::Color { default, .. } => {
+                    default.clone().unwrap_or_default()
+                }
+                FieldType::Array { default, .. } => default
+                    .as_deref()
+                    .map(Self::array_display)
+                    .unwrap_or_default(),
                 _ => String::new(),
             }
+        };
+
+        match &field.field_type {
+            FieldType::Number {
+                unit: Some(unit), ..
+            }
+            | FieldType::Float {
+                unit: Some(unit), ..
+            } if !display.is_empty() => format!("{} {}", display, unit),
+            _ => display,
+        }
+    }
+
+    fn array_display(items: &[Value]) -> String {
+        items
+            .iter()
+            .map(SchemaValidator::format_text)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn label_with_unit(label: &str, unit: Option<&str>) -> String {
+        match unit {
+            Some(unit) => format!("{} ({})", label, unit),
+            None => label.to_string(),
         }
     }
 }
@@ -1,7 +1,8 @@
-use super::app::SchemaTUI;
+use super::app::{SaveMode, SchemaTUI};
+use super::keymap::Keymap;
 use super::theme::Theme;
-use crate::config::ConfigLoader;
-use crate::options::{OptionProvider, OptionResolver};
+use crate::config::{BackupPolicy, ConfigLoader};
+use crate::options::{AsyncOptionProvider, OptionProvider, OptionResolver};
 use crate::schema::{ConfigSchema, SchemaParser};
 use anyhow::Result;
 use serde_json::Value;
@@ -12,8 +13,15 @@ pub struct SchemaTUIBuilder {
     schema: Option<ConfigSchema>,
     initial_values: Option<HashMap<String, Value>>,
     option_providers: Vec<(String, Box<dyn OptionProvider>)>,
+    async_option_providers: Vec<(String, Box<dyn AsyncOptionProvider>)>,
+    builtin_providers: bool,
     theme: Theme,
     config_path: Option<std::path::PathBuf>,
+    backup_policy: Option<BackupPolicy>,
+    save_mode: SaveMode,
+    omit_defaults: bool,
+    mouse: bool,
+    keymap: Keymap,
 }
 
 impl SchemaTUIBuilder {
@@ -22,8 +30,15 @@ impl SchemaTUIBuilder {
             schema: None,
             initial_values: None,
             option_providers: Vec::new(),
+            async_option_providers: Vec::new(),
+            builtin_providers: false,
             theme: Theme::default(),
             config_path: None,
+            backup_policy: None,
+            save_mode: SaveMode::default(),
+            omit_defaults: false,
+            mouse: true,
+            keymap: Keymap::default(),
         }
     }
 
@@ -62,30 +77,107 @@ impl SchemaTUIBuilder {
         self
     }
 
+    pub fn register_async_option_provider(
+        mut self,
+        name: impl Into<String>,
+        provider: Box<dyn AsyncOptionProvider>,
+    ) -> Self {
+        self.async_option_providers.push((name.into(), provider));
+        self
+    }
+
+    /// Register the built-in system providers (timezones, locales, ...)
+    ///
+    /// Providers registered explicitly under the same name take precedence.
+    pub fn builtin_providers(mut self) -> Self {
+        self.builtin_providers = true;
+        self
+    }
+
+    /// Keep timestamped backups of the config file
+    ///
+    /// The file is snapshotted before the first save of each session.
+    pub fn backups(mut self, policy: BackupPolicy) -> Self {
+        self.backup_policy = Some(policy);
+        self
+    }
+
+    /// Save on every change (default) or only from the review screen
+    pub fn save_mode(mut self, mode: SaveMode) -> Self {
+        self.save_mode = mode;
+        self
+    }
+
+    /// Leave settings that equal their schema default out of the saved file
+    ///
+    /// Resetting a field then removes its key instead of writing the default,
+    /// so later changes to the schema default take effect.
+    pub fn omit_defaults(mut self) -> Self {
+        self.omit_defaults = true;
+        self
+    }
+
+    /// Capture the mouse for clicking and scrolling (on by default)
+    ///
+    /// Turn it off to keep the terminal's own text selection.
+    pub fn mouse(mut self, enabled: bool) -> Self {
+        self.mouse = enabled;
+        self
+    }
+
+    /// Key bindings, e.g. `Keymap::emacs()`; defaults to the vim preset
+    pub fn keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Load key bindings from a TOML keymap file
+    pub fn keymap_file(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        self.keymap = Keymap::from_file(path)?;
+        Ok(self)
+    }
+
     pub fn theme(mut self, theme: Theme) -> Self {
         self.theme = theme;
         self
     }
 
     pub fn build(self) -> Result<SchemaTUI> {
-        let schema = self
+        let mut schema = self
             .schema
             .ok_or_else(|| anyhow::anyhow!("Schema not provided"))?;
 
         let initial_values = self.initial_values.unwrap_or_default();
+        self.keymap.validate()?;
+        let warnings = self.keymap.validate_schema(&mut schema)?;
 
         let mut option_resolver = OptionResolver::new();
+        if self.builtin_providers {
+            option_resolver.register_builtin_providers();
+        }
         for (name, provider) in self.option_providers {
             option_resolver.register_provider(name, provider);
         }
+        for (name, provider) in self.async_option_providers {
+            option_resolver.register_async_provider(name, provider);
+        }
 
-        Ok(SchemaTUI::new(
+        let mut tui = SchemaTUI::new(
             schema,
             initial_values,
             option_resolver,
             self.theme,
             self.config_path,
-        ))
+        );
+        if let Some(policy) = self.backup_policy {
+            tui.set_backup_policy(policy);
+        }
+        tui.set_save_mode(self.save_mode);
+        tui.set_omit_defaults(self.omit_defaults);
+        tui.set_mouse(self.mouse);
+        tui.set_keymap(self.keymap);
+        tui.report_warnings(&warnings);
+        Ok(tui)
     }
 }
 
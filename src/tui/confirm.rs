@@ -0,0 +1,85 @@
+use super::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Yes/no overlay shown before a reset discards values
+pub(crate) struct ConfirmDialog {
+    /// Keys the reset will change
+    pub(crate) keys: Vec<String>,
+    prompt: String,
+}
+
+impl ConfirmDialog {
+    pub(crate) fn new(keys: Vec<String>, prompt: impl Into<String>) -> Self {
+        Self {
+            keys,
+            prompt: prompt.into(),
+        }
+    }
+
+    /// `Some(true)` to go ahead, `Some(false)` to cancel
+    ///
+    /// Only `y` confirms; Enter cancels so a stray keypress keeps the values.
+    pub(crate) fn handle_key(&self, key: KeyEvent) -> Option<bool> {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Some(true),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc | KeyCode::Enter => Some(false),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let screen = area;
+        // Prompt, blank line, up to a handful of keys, blank line, hint
+        let shown = self.keys.len().min(8);
+        let width = screen.width.saturating_sub(screen.width / 3).max(40);
+        let height = (shown as u16 + 8).min(screen.height);
+        let area = Rect {
+            x: screen.x + screen.width.saturating_sub(width) / 2,
+            y: screen.y + screen.height.saturating_sub(height) / 2,
+            width: width.min(screen.width),
+            height,
+        };
+        frame.render_widget(Clear, area);
+
+        let mut lines = vec![Line::from(self.prompt.clone()), Line::from("")];
+        for key in &self.keys[..shown] {
+            lines.push(Line::from(Span::styled(
+                format!("  {}", key),
+                Style::default().fg(theme.text_dim),
+            )));
+        }
+        if self.keys.len() > shown {
+            lines.push(Line::from(Span::styled(
+                format!("  … and {} more", self.keys.len() - shown),
+                Style::default().fg(theme.text_dim),
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("y", Style::default().fg(theme.primary)),
+            Span::raw(" confirm   "),
+            Span::styled("n", Style::default().fg(theme.primary)),
+            Span::raw(" cancel"),
+        ]));
+
+        let dialog = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Confirm")
+                .border_style(
+                    Style::default()
+                        .fg(theme.warning)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .style(Style::default().bg(theme.popup_bg).fg(theme.popup_fg)),
+        );
+        frame.render_widget(dialog, area);
+    }
+}
@@ -0,0 +1,123 @@
+use super::theme::Theme;
+use crate::config::MergeConflict;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+
+/// What the user chose in the conflict dialog
+pub(crate) enum ConflictAction {
+    None,
+    /// Final value for every conflicting key; `None` removes the key
+    Resolve(Vec<(String, Option<Value>)>),
+}
+
+/// Overlay to pick a side for keys changed both in the TUI and on disk
+pub(crate) struct ConflictDialog {
+    conflicts: Vec<MergeConflict>,
+    // Display strings for (ours, theirs), with secrets masked
+    labels: Vec<(String, String)>,
+    take_theirs: Vec<bool>,
+    selected: usize,
+}
+
+impl ConflictDialog {
+    pub(crate) fn new(conflicts: Vec<MergeConflict>, labels: Vec<(String, String)>) -> Self {
+        let take_theirs = vec![false; conflicts.len()];
+        Self {
+            conflicts,
+            labels,
+            take_theirs,
+            selected: 0,
+        }
+    }
+
+    fn resolution(&self) -> Vec<(String, Option<Value>)> {
+        self.conflicts
+            .iter()
+            .zip(&self.take_theirs)
+            .map(|(c, &theirs)| {
+                let value = if theirs { &c.theirs } else { &c.ours };
+                (c.key.clone(), value.clone())
+            })
+            .collect()
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> ConflictAction {
+        let last = self.conflicts.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Enter => return ConflictAction::Resolve(self.resolution()),
+            // Leaving without choosing keeps what was entered in the TUI
+            KeyCode::Esc => {
+                self.take_theirs.fill(false);
+                return ConflictAction::Resolve(self.resolution());
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(last),
+            KeyCode::Left | KeyCode::Char('h') => self.take_theirs[self.selected] = false,
+            KeyCode::Right | KeyCode::Char('l') => self.take_theirs[self.selected] = true,
+            KeyCode::Char(' ') => {
+                self.take_theirs[self.selected] = !self.take_theirs[self.selected];
+            }
+            KeyCode::Char('o') => self.take_theirs.fill(false),
+            KeyCode::Char('t') => self.take_theirs.fill(true),
+            _ => {}
+        }
+        ConflictAction::None
+    }
+
+    pub(crate) fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let screen = area;
+        let height = (self.conflicts.len() as u16 * 2 + 3).min(screen.height.saturating_sub(2));
+        let width = screen.width.saturating_sub(screen.width / 5);
+        let area = Rect {
+            x: screen.x + (screen.width - width) / 2,
+            y: screen.y + (screen.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        };
+
+        let chosen = Style::default()
+            .fg(theme.highlight_fg)
+            .add_modifier(Modifier::BOLD);
+        let other = Style::default().fg(theme.text_dim);
+
+        let mut lines = vec![Line::from(Span::styled(
+            "Changed here and on disk. ← mine  → file  o/t all mine/file",
+            Style::default().fg(theme.text_dim),
+        ))];
+        for (i, (conflict, (ours, theirs))) in self.conflicts.iter().zip(&self.labels).enumerate() {
+            let marker = if i == self.selected { "» " } else { "  " };
+            let take_theirs = self.take_theirs[i];
+            lines.push(Line::from(Span::styled(
+                format!("{}{}", marker, conflict.key),
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            lines.push(Line::from(vec![
+                Span::raw("    mine: "),
+                Span::styled(ours.clone(), if take_theirs { other } else { chosen }),
+                Span::raw("   file: "),
+                Span::styled(theirs.clone(), if take_theirs { chosen } else { other }),
+            ]));
+        }
+
+        frame.render_widget(Clear, area);
+        let dialog = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Config changed on disk (Enter apply, Esc keep mine)")
+                .border_style(
+                    Style::default()
+                        .fg(theme.warning)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .style(Style::default().bg(theme.popup_bg).fg(theme.popup_fg)),
+        );
+        frame.render_widget(dialog, area);
+    }
+}
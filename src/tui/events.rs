@@ -0,0 +1,57 @@
+use serde_json::Value;
+
+/// Where a change to a setting came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeSource {
+    /// Edited, reset or undone in the editor
+    User,
+    /// Reloaded or restored from the config file
+    File,
+    /// Pushed by the host through [`HostCommand::SetValue`]
+    Host,
+}
+
+/// A setting changed value
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeEvent {
+    pub key: String,
+    /// `None` when the setting wasn't set
+    pub old: Option<Value>,
+    /// `None` when the setting was removed
+    pub new: Option<Value>,
+    pub source: ChangeSource,
+}
+
+/// Requests a host can send to a running editor, see [`SchemaTUI::command_sender`]
+///
+/// [`SchemaTUI::command_sender`]: super::SchemaTUI::command_sender
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostCommand {
+    /// Set a value as if the user had confirmed it
+    SetValue { key: String, value: Value },
+    /// Show a message in the status line
+    Message(String),
+    /// Close the editor
+    Quit,
+}
+
+/// What a `before_change` hook decided about a new value
+#[derive(Debug, Clone, PartialEq)]
+pub enum BeforeChange {
+    Accept,
+    /// Keep the old value and show the reason
+    Reject(String),
+    /// Store this value instead, e.g. a normalized form
+    Replace(Value),
+}
+
+/// How applying a change went, reported by an `after_change` hook
+#[derive(Debug, Clone, PartialEq)]
+pub enum AfterChange {
+    /// Nothing to report
+    Ok,
+    /// Shown in the status line
+    Message(String),
+    /// Shown in the status line as a failure to apply the setting
+    Failed(String),
+}
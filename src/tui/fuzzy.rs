@@ -0,0 +1,64 @@
+/// Score `text` against `pattern` as a case-insensitive subsequence match
+///
+/// Returns `None` when not every pattern character appears in order. Higher
+/// scores mean better matches: consecutive characters, matches at word
+/// starts and an early first match all count.
+pub(crate) fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let pattern: Vec<char> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    let text: Vec<char> = text.chars().collect();
+
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for (i, c) in text.iter().enumerate() {
+        if next == pattern.len() {
+            break;
+        }
+        if !c.to_lowercase().eq(std::iter::once(pattern[next])) {
+            continue;
+        }
+
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == i) {
+            score += 5;
+        }
+        let word_start = i == 0 || !text[i - 1].is_alphanumeric() || {
+            // camelCase boundary
+            text[i - 1].is_lowercase() && c.is_uppercase()
+        };
+        if word_start {
+            score += 8;
+        }
+        if previous.is_none() {
+            score -= i.min(10) as i64;
+        }
+        previous = Some(i);
+        next += 1;
+    }
+
+    (next == pattern.len()).then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert!(fuzzy_score("xyz", "font size").is_none());
+        assert!(fuzzy_score("fs", "font size").is_some());
+
+        // Contiguous and word-start matches rank higher
+        assert!(fuzzy_score("font", "Font size") > fuzzy_score("font", "frontend"));
+        assert!(fuzzy_score("fs", "font size") > fuzzy_score("fs", "buffers"));
+        assert!(fuzzy_score("size", "size") > fuzzy_score("size", "font size"));
+    }
+}
@@ -0,0 +1,89 @@
+use super::keymap::{Keymap, ACTIONS};
+use super::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Overlay listing every action and the keys bound to it
+pub(crate) struct HelpDialog {
+    scroll: u16,
+}
+
+impl HelpDialog {
+    pub(crate) fn new() -> Self {
+        Self { scroll: 0 }
+    }
+
+    /// Returns true once the dialog should close
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            _ => return true,
+        }
+        false
+    }
+
+    pub(crate) fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme, keymap: &Keymap) {
+        let screen = area;
+        let area = Rect {
+            x: screen.x + screen.width / 6,
+            y: screen.y + screen.height / 10,
+            width: screen.width - screen.width / 3,
+            height: screen.height - screen.height / 5,
+        };
+        frame.render_widget(Clear, area);
+
+        let heading = Style::default()
+            .fg(theme.secondary)
+            .add_modifier(Modifier::BOLD);
+        let mut lines = Vec::new();
+        for (title, editing) in [("Navigation", false), ("Editing", true)] {
+            if !lines.is_empty() {
+                lines.push(Line::from(""));
+            }
+            lines.push(Line::from(Span::styled(title, heading)));
+            for (action, description) in ACTIONS {
+                if action.starts_with("edit.") != editing {
+                    continue;
+                }
+                let keys: Vec<String> = keymap.keys(action).iter().map(|k| k.to_string()).collect();
+                let keys = if keys.is_empty() {
+                    "—".to_string()
+                } else {
+                    keys.join(", ")
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("  {:<18}", keys),
+                        Style::default().fg(theme.primary),
+                    ),
+                    Span::raw(format!("{:<28}", description)),
+                    Span::styled(*action, Style::default().fg(theme.text_dim)),
+                ]));
+            }
+        }
+
+        let help = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Key bindings (↑↓ scroll, any other key closes)")
+                    .border_style(
+                        Style::default()
+                            .fg(theme.popup_border)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .style(Style::default().bg(theme.popup_bg).fg(theme.popup_fg)),
+            )
+            .scroll((self.scroll, 0));
+        frame.render_widget(help, area);
+    }
+}
@@ -0,0 +1,572 @@
+use super::widgets::EditAction;
+use crate::schema::ConfigSchema;
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+/// Every bindable action with its help text
+///
+/// `edit.*` actions apply while a field is being edited and are handed to the
+/// widget as an [`EditAction`]; the rest apply while navigating.
+pub const ACTIONS: &[(&str, &str)] = &[
+    ("nav.quit", "Quit"),
+    ("nav.next_section", "Next section"),
+    ("nav.previous_section", "Previous section"),
+    ("nav.next_field", "Next field"),
+    ("nav.previous_field", "Previous field"),
+    ("field.edit", "Edit field"),
+    ("field.open_editor", "Open field in $EDITOR"),
+    ("field.reset", "Reset field to default"),
+    ("section.reset", "Reset section to defaults"),
+    ("app.reset_all", "Reset all settings to defaults"),
+    ("app.undo", "Undo last change"),
+    ("app.save", "Save"),
+    ("app.reload", "Reload from disk"),
+    ("app.search", "Search settings"),
+    ("app.review", "Review changes"),
+    ("app.backups", "Restore a backup"),
+    ("app.palette", "Command palette"),
+    ("app.toggle_theme", "Toggle theme"),
+    ("app.export", "Export as JSON"),
+    ("app.edit_raw", "Edit config file in $EDITOR"),
+    ("app.help", "Show key bindings"),
+    ("edit.confirm", "Confirm edit"),
+    ("edit.cancel", "Cancel edit"),
+    ("edit.up", "Up"),
+    ("edit.down", "Down"),
+    ("edit.left", "Left"),
+    ("edit.right", "Right"),
+    ("edit.home", "Start"),
+    ("edit.end", "End"),
+    ("edit.page_up", "Page up"),
+    ("edit.page_down", "Page down"),
+];
+
+/// A key with modifiers, written like `ctrl-s`, `shift-tab`, `pgup` or `q`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }.normalized()
+    }
+
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s.trim();
+        // A lone "-" is the minus key, not a separator
+        while let Some((prefix, tail)) = rest.split_once('-').filter(|(_, t)| !t.is_empty()) {
+            modifiers |= match prefix.to_lowercase().as_str() {
+                "ctrl" | "c" => KeyModifiers::CONTROL,
+                "alt" | "meta" | "m" => KeyModifiers::ALT,
+                "shift" | "s" => KeyModifiers::SHIFT,
+                _ => bail!("Unknown modifier '{}' in key '{}'", prefix, s),
+            };
+            rest = tail;
+        }
+
+        let code = match rest.to_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pgup" | "pageup" => KeyCode::PageUp,
+            "pgdn" | "pagedown" => KeyCode::PageDown,
+            f if f.len() > 1 && f.starts_with('f') && f[1..].parse::<u8>().is_ok() => {
+                KeyCode::F(f[1..].parse()?)
+            }
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => bail!("Unknown key '{}'", s),
+                }
+            }
+        };
+        Ok(Self::new(code, modifiers))
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        *self == Self::new(key.code, key.modifiers)
+    }
+
+    // Shift is already part of an uppercase char or BackTab; terminals
+    // disagree on whether they report it separately
+    fn normalized(mut self) -> Self {
+        if let KeyCode::Char(c) = self.code {
+            if self.modifiers.contains(KeyModifiers::SHIFT) && c.is_ascii_lowercase() {
+                self.code = KeyCode::Char(c.to_ascii_uppercase());
+            }
+            if self.modifiers.contains(KeyModifiers::CONTROL) {
+                self.code = KeyCode::Char(c.to_ascii_lowercase());
+            }
+        }
+        if matches!(self.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            self.modifiers.remove(KeyModifiers::SHIFT);
+        }
+        self
+    }
+
+    fn to_event(self) -> KeyEvent {
+        KeyEvent::new(self.code, self.modifiers)
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::BackTab => write!(f, "Shift-Tab"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// A key bound to more than one action in the same mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyConflict {
+    pub key: KeyBinding,
+    pub actions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct KeymapFile {
+    #[serde(default)]
+    preset: Option<String>,
+    #[serde(default)]
+    keys: HashMap<String, KeyList>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Keys bound to each named action
+///
+/// Start from a preset (`vim`, the default, `emacs` or `arrows`) and
+/// override single actions with [`bind`](Self::bind) or a TOML file:
+///
+/// ```toml
+/// preset = "emacs"
+///
+/// [keys]
+/// "app.save" = "ctrl-s"
+/// "nav.next_field" = ["ctrl-n", "down"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: BTreeMap<String, Vec<KeyBinding>>,
+}
+
+impl Keymap {
+    /// hjkl and arrow navigation; the built-in default
+    pub fn vim() -> Self {
+        Self::from_table(&[
+            ("nav.quit", &["q", "esc"]),
+            ("nav.next_section", &["tab", "l", "right"]),
+            ("nav.previous_section", &["shift-tab", "h", "left"]),
+            ("nav.next_field", &["j", "down"]),
+            ("nav.previous_field", &["k", "up"]),
+            ("field.edit", &["enter", "space"]),
+            ("field.open_editor", &["e"]),
+            ("field.reset", &["r"]),
+            ("section.reset", &["R"]),
+            ("app.undo", &["u"]),
+            ("app.save", &["ctrl-s"]),
+            ("app.reload", &["ctrl-r"]),
+            ("app.search", &["/"]),
+            ("app.review", &["d"]),
+            ("app.backups", &["b"]),
+            ("app.palette", &["ctrl-p"]),
+            ("app.toggle_theme", &["T"]),
+            ("app.edit_raw", &["E"]),
+            ("app.help", &["?"]),
+            ("edit.confirm", &["enter"]),
+            ("edit.cancel", &["esc"]),
+        ])
+    }
+
+    /// Ctrl-based movement as in Emacs
+    pub fn emacs() -> Self {
+        Self::from_table(&[
+            ("nav.quit", &["q", "ctrl-g"]),
+            ("nav.next_section", &["ctrl-f", "tab", "right"]),
+            ("nav.previous_section", &["ctrl-b", "shift-tab", "left"]),
+            ("nav.next_field", &["ctrl-n", "down"]),
+            ("nav.previous_field", &["ctrl-p", "up"]),
+            ("field.edit", &["enter"]),
+            ("field.open_editor", &["ctrl-x"]),
+            ("app.undo", &["ctrl-_", "u"]),
+            ("app.save", &["alt-s"]),
+            ("app.reload", &["alt-r"]),
+            ("app.search", &["ctrl-s", "/"]),
+            ("app.review", &["alt-d"]),
+            ("app.backups", &["alt-b"]),
+            ("app.palette", &["alt-x"]),
+            ("app.toggle_theme", &["alt-t"]),
+            ("app.edit_raw", &["alt-e"]),
+            ("app.help", &["ctrl-h", "?"]),
+            ("edit.confirm", &["enter", "ctrl-j"]),
+            ("edit.cancel", &["esc", "ctrl-g"]),
+            ("edit.up", &["up", "ctrl-p"]),
+            ("edit.down", &["down", "ctrl-n"]),
+            ("edit.left", &["left", "ctrl-b"]),
+            ("edit.right", &["right", "ctrl-f"]),
+            ("edit.page_up", &["pgup", "alt-v"]),
+            ("edit.page_down", &["pgdn", "ctrl-v"]),
+        ])
+    }
+
+    /// Arrows, Tab and Enter only for navigation, for terminals or users
+    /// without letter-key habits
+    pub fn arrows() -> Self {
+        let mut keymap = Self::vim();
+        for (action, keys) in [
+            ("nav.next_section", &["tab", "right"][..]),
+            ("nav.previous_section", &["shift-tab", "left"]),
+            ("nav.next_field", &["down"]),
+            ("nav.previous_field", &["up"]),
+            ("field.edit", &["enter"]),
+        ] {
+            keymap.bindings.insert(
+                action.to_string(),
+                keys.iter().map(|k| KeyBinding::parse(k).unwrap()).collect(),
+            );
+        }
+        keymap
+    }
+
+    pub fn preset(name: &str) -> Result<Self> {
+        match name {
+            "vim" => Ok(Self::vim()),
+            "emacs" => Ok(Self::emacs()),
+            "arrows" => Ok(Self::arrows()),
+            _ => bail!("Unknown keymap preset '{}'", name),
+        }
+    }
+
+    /// Bindings from `table`; editing actions not in it keep their standard key
+    fn from_table(table: &[(&str, &[&str])]) -> Self {
+        let mut bindings: BTreeMap<String, Vec<KeyBinding>> = ACTIONS
+            .iter()
+            .map(|(action, _)| (action.to_string(), Vec::new()))
+            .collect();
+        for action in EditAction::ALL {
+            let key = KeyBinding::new(action.standard_key(), KeyModifiers::NONE);
+            bindings.insert(action.name().to_string(), vec![key]);
+        }
+        for (action, keys) in table {
+            bindings.insert(
+                action.to_string(),
+                keys.iter().map(|k| KeyBinding::parse(k).unwrap()).collect(),
+            );
+        }
+        Self { bindings }
+    }
+
+    /// Load a keymap file: an optional `preset` plus per-action `[keys]`
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_toml_str(&content)
+    }
+
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        let file: KeymapFile = toml::from_str(content)?;
+        let mut keymap = Self::preset(file.preset.as_deref().unwrap_or("vim"))?;
+        for (action, keys) in file.keys {
+            let keys = match keys {
+                KeyList::One(key) => vec![key],
+                KeyList::Many(keys) => keys,
+            };
+            let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+            keymap = keymap.bind(&action, &keys)?;
+        }
+        keymap.validate()?;
+        Ok(keymap)
+    }
+
+    /// Replace the keys of one action; an empty list unbinds it
+    pub fn bind(mut self, action: &str, keys: &[&str]) -> Result<Self> {
+        if !self.bindings.contains_key(action) {
+            bail!("Unknown action '{}'", action);
+        }
+        let keys = keys
+            .iter()
+            .map(|k| KeyBinding::parse(k))
+            .collect::<Result<Vec<_>>>()?;
+        self.bindings.insert(action.to_string(), keys);
+        Ok(self)
+    }
+
+    pub fn keys(&self, action: &str) -> &[KeyBinding] {
+        self.bindings.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Up to two keys of an action for hints, e.g. `Tab/l`
+    pub fn label(&self, action: &str) -> String {
+        self.keys(action)
+            .iter()
+            .take(2)
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// The navigation action bound to a key
+    pub fn navigation_action(&self, key: &KeyEvent) -> Option<&str> {
+        self.action_for(key, false)
+    }
+
+    fn action_for(&self, key: &KeyEvent, editing: bool) -> Option<&str> {
+        self.bindings
+            .iter()
+            .filter(|(action, _)| action.starts_with("edit.") == editing)
+            .find(|(_, keys)| keys.iter().any(|k| k.matches(key)))
+            .map(|(action, _)| action.as_str())
+    }
+
+    /// The editing action bound to a key pressed while editing
+    pub fn edit_action(&self, key: &KeyEvent) -> Option<EditAction> {
+        self.action_for(key, true).and_then(EditAction::from_name)
+    }
+
+    /// Keys bound to several actions that are active at the same time
+    pub fn conflicts(&self) -> Vec<KeyConflict> {
+        let mut by_key: HashMap<(bool, KeyBinding), Vec<String>> = HashMap::new();
+        for (action, keys) in &self.bindings {
+            for key in keys {
+                by_key
+                    .entry((action.starts_with("edit."), *key))
+                    .or_default()
+                    .push(action.clone());
+            }
+        }
+        let mut conflicts: Vec<KeyConflict> = by_key
+            .into_iter()
+            .filter(|(_, actions)| actions.len() > 1)
+            .map(|((_, key), actions)| KeyConflict { key, actions })
+            .collect();
+        conflicts.sort_by(|a, b| a.actions.cmp(&b.actions));
+        conflicts
+    }
+
+    /// Check the schema's field `keybind`s and action keys: they must parse
+    /// and keybinds must be unique. Keys that would shadow a navigation
+    /// binding are unbound, with one warning each; their actions stay in the
+    /// command palette.
+    pub fn validate_schema(&self, schema: &mut ConfigSchema) -> Result<Vec<String>> {
+        let mut keybinds: HashMap<KeyBinding, String> = HashMap::new();
+        let mut warnings = Vec::new();
+        let mut check = |key: &mut String, owner: String| -> Result<()> {
+            let binding =
+                KeyBinding::parse(key).map_err(|e| anyhow!("Invalid key of {}: {}", owner, e))?;
+            if let Some(action) = self.navigation_action(&binding.to_event()) {
+                warnings.push(format!(
+                    "{} of {} is already bound to {}; ignored",
+                    binding, owner, action
+                ));
+                key.clear();
+            }
+            Ok(())
+        };
+
+        for section in &mut schema.sections {
+            for action in &mut section.actions {
+                let owner = format!("action '{}' of section {}", action.label, section.id);
+                check(&mut action.key, owner)?;
+            }
+            for field in &mut section.fields {
+                let field_key = format!("{}.{}", section.id, field.id);
+                for action in &mut field.actions {
+                    let owner = format!("action '{}' of {}", action.label, field_key);
+                    check(&mut action.key, owner)?;
+                }
+                if let Some(keybind) = &mut field.keybind {
+                    let binding = KeyBinding::parse(keybind)
+                        .map_err(|e| anyhow!("Invalid keybind of {}: {}", field_key, e))?;
+                    if let Some(other) = keybinds.insert(binding, field_key.clone()) {
+                        bail!(
+                            "{} is the keybind of both {} and {}",
+                            binding,
+                            other,
+                            field_key
+                        );
+                    }
+                    check(keybind, format!("keybind of {}", field_key))?;
+                    if keybind.is_empty() {
+                        field.keybind = None;
+                    }
+                }
+            }
+        }
+        Ok(warnings)
+    }
+
+    /// Fail if any key is bound twice in the same mode
+    pub fn validate(&self) -> Result<()> {
+        let conflicts = self.conflicts();
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+        let details: Vec<String> = conflicts
+            .iter()
+            .map(|c| format!("{} is bound to {}", c.key, c.actions.join(" and ")))
+            .collect();
+        Err(anyhow!("Conflicting key bindings: {}", details.join("; ")))
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::vim()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_bindings() {
+        let ctrl_s = KeyBinding::parse("ctrl-s").unwrap();
+        assert!(ctrl_s.matches(&KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)));
+        assert_eq!(ctrl_s.to_string(), "Ctrl-S");
+
+        let back_tab = KeyBinding::parse("shift-tab").unwrap();
+        assert!(back_tab.matches(&KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT)));
+
+        // Shift may or may not be reported with uppercase letters
+        let upper = KeyBinding::parse("T").unwrap();
+        assert!(upper.matches(&KeyEvent::new(KeyCode::Char('T'), KeyModifiers::SHIFT)));
+        assert!(upper.matches(&KeyEvent::new(KeyCode::Char('T'), KeyModifiers::NONE)));
+
+        assert_eq!(KeyBinding::parse("-").unwrap().code, KeyCode::Char('-'));
+        assert!(KeyBinding::parse("hyper-x").is_err());
+        assert!(KeyBinding::parse("nope").is_err());
+    }
+
+    #[test]
+    fn test_presets_have_no_conflicts() {
+        for keymap in [Keymap::vim(), Keymap::emacs(), Keymap::arrows()] {
+            assert_eq!(keymap.conflicts(), Vec::new());
+        }
+    }
+
+    #[test]
+    fn test_keymap_file_overrides_preset() {
+        let keymap = Keymap::from_toml_str(
+            r#"
+            preset = "emacs"
+
+            [keys]
+            "app.save" = "ctrl-w"
+            "edit.home" = ["ctrl-a"]
+            "#,
+        )
+        .unwrap();
+
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        assert_eq!(keymap.navigation_action(&ctrl('w')), Some("app.save"));
+        assert_eq!(keymap.navigation_action(&ctrl('n')), Some("nav.next_field"));
+        assert_eq!(keymap.edit_action(&ctrl('a')), Some(EditAction::Home));
+        assert_eq!(keymap.edit_action(&ctrl('n')), Some(EditAction::Down));
+        // Rebinding an editing action drops its standard key
+        let home = KeyEvent::new(KeyCode::Home, KeyModifiers::NONE);
+        assert_eq!(keymap.edit_action(&home), None);
+        let down = KeyEvent::new(KeyCode::Down, KeyModifiers::NONE);
+        assert_eq!(keymap.edit_action(&down), Some(EditAction::Down));
+        // Unbound keys are input for the widget
+        assert_eq!(keymap.edit_action(&ctrl('k')), None);
+
+        let conflict = Keymap::from_toml_str("[keys]\n\"app.review\" = \"j\"\n").unwrap_err();
+        assert!(conflict
+            .to_string()
+            .contains("app.review and nav.next_field"));
+        assert!(Keymap::from_toml_str("[keys]\n\"app.nope\" = \"x\"\n").is_err());
+    }
+
+    #[test]
+    fn test_schema_keys_shadowing_navigation_are_ignored() {
+        let schema = |field_extra: &str| {
+            crate::schema::SchemaParser::from_string(&format!(
+                r#"{{"version": "1.0", "sections": [{{"id": "s", "title": "S", "fields": [
+                    {{"id": "a", "label": "A", "description": "", "type": "string", {}}},
+                    {{"id": "b", "label": "B", "description": "", "type": "string", "keybind": "ctrl-b"}}
+                ]}}]}}"#,
+                field_extra
+            ))
+            .unwrap()
+        };
+        let keymap = Keymap::vim();
+
+        let mut ok =
+            schema(r#""actions": [{"key": "x", "label": "X", "type": "run", "command": "true"}]"#);
+        assert!(keymap.validate_schema(&mut ok).unwrap().is_empty());
+        assert_eq!(ok.sections[0].fields[0].actions[0].key, "x");
+
+        // Shadowing keys are unbound with a warning instead of failing
+        let mut shadowing = schema(
+            r#""keybind": "k", "actions": [{"key": "j", "label": "J", "type": "run", "command": "true"}]"#,
+        );
+        let warnings = keymap.validate_schema(&mut shadowing).unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("nav.next_field"));
+        let field = &shadowing.sections[0].fields[0];
+        assert!(field.actions[0].key.is_empty());
+        assert_eq!(field.keybind, None);
+        assert_eq!(
+            shadowing.sections[0].fields[1].keybind.as_deref(),
+            Some("ctrl-b")
+        );
+
+        let mut duplicate = schema(r#""keybind": "ctrl-b""#);
+        assert!(keymap.validate_schema(&mut duplicate).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use super::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Countdown shown after a live-applied change; the change is reverted
+/// unless kept before the deadline
+pub(crate) struct LiveApplyDialog {
+    /// Values from before the change, restored on revert
+    pub(crate) previous: Vec<(String, Option<Value>)>,
+    deadline: Instant,
+}
+
+impl LiveApplyDialog {
+    pub(crate) fn new(previous: Vec<(String, Option<Value>)>, timeout: Duration) -> Self {
+        Self {
+            previous,
+            deadline: Instant::now() + timeout,
+        }
+    }
+
+    /// Add more applied changes, restarting the countdown
+    pub(crate) fn extend(&mut self, previous: Vec<(String, Option<Value>)>, timeout: Duration) {
+        for (key, value) in previous {
+            // The oldest value is the one to go back to
+            if !self.previous.iter().any(|(k, _)| *k == key) {
+                self.previous.push((key, value));
+            }
+        }
+        self.deadline = Instant::now() + timeout;
+    }
+
+    pub(crate) fn expired(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+
+    /// `Some(true)` to keep the change, `Some(false)` to revert it now
+    pub(crate) fn handle_key(&self, key: KeyEvent) -> Option<bool> {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => Some(true),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => Some(false),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let screen = area;
+        let shown = self.previous.len().min(8);
+        let width = screen.width.saturating_sub(screen.width / 3).max(40);
+        let height = (shown as u16 + 8).min(screen.height);
+        let area = Rect {
+            x: screen.x + screen.width.saturating_sub(width) / 2,
+            y: screen.y + screen.height.saturating_sub(height) / 2,
+            width: width.min(screen.width),
+            height,
+        };
+        frame.render_widget(Clear, area);
+
+        // Round up so the dialog never shows 0 while still open
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        let seconds = (left.as_millis() as u64).div_ceil(1000);
+        let mut lines = vec![
+            Line::from(format!("Keep these settings? Reverting in {}s", seconds)),
+            Line::from(""),
+        ];
+        for (key, _) in &self.previous[..shown] {
+            lines.push(Line::from(Span::styled(
+                format!("  {}", key),
+                Style::default().fg(theme.text_dim),
+            )));
+        }
+        if self.previous.len() > shown {
+            lines.push(Line::from(Span::styled(
+                format!("  … and {} more", self.previous.len() - shown),
+                Style::default().fg(theme.text_dim),
+            )));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("y", Style::default().fg(theme.primary)),
+            Span::raw(" keep   "),
+            Span::styled("n", Style::default().fg(theme.primary)),
+            Span::raw(" revert"),
+        ]));
+
+        let dialog = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Applied")
+                .border_style(
+                    Style::default()
+                        .fg(theme.warning)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .style(Style::default().bg(theme.popup_bg).fg(theme.popup_fg)),
+        );
+        frame.render_widget(dialog, area);
+    }
+}
@@ -2,6 +2,19 @@ mod actions;
 mod app;
 mod builder;
 mod conditions;
+mod confirm;
+mod conflict;
+mod events;
+mod fuzzy;
+mod help;
+mod keymap;
+mod live_apply;
+mod output;
+mod palette;
+mod raw_edit;
+mod restore;
+mod review;
+mod search;
 mod theme;
 mod widgets;
 
@@ -9,5 +22,8 @@ pub use actions::*;
 pub use app::*;
 pub use builder::*;
 pub use conditions::*;
+pub use events::*;
+pub use keymap::*;
+pub use review::PendingChange;
 pub use theme::*;
 pub use widgets::*;
@@ -0,0 +1,69 @@
+use super::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Overlay showing the output of a schema `run` action
+pub(crate) struct OutputDialog {
+    title: String,
+    text: String,
+    scroll: u16,
+}
+
+impl OutputDialog {
+    pub(crate) fn new(title: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            text: text.into(),
+            scroll: 0,
+        }
+    }
+
+    /// Returns true once the dialog should close
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            _ => return true,
+        }
+        false
+    }
+
+    pub(crate) fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let screen = area;
+        let area = Rect {
+            x: screen.x + screen.width / 10,
+            y: screen.y + screen.height / 6,
+            width: screen.width - screen.width / 5,
+            height: screen.height - screen.height / 3,
+        };
+        frame.render_widget(Clear, area);
+
+        let text = if self.text.trim().is_empty() {
+            "(no output)"
+        } else {
+            self.text.trim_end()
+        };
+        let output = Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("{} (↑↓ scroll, any other key closes)", self.title))
+                    .border_style(
+                        Style::default()
+                            .fg(theme.popup_border)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .style(Style::default().bg(theme.popup_bg).fg(theme.popup_fg)),
+            );
+        frame.render_widget(output, area);
+    }
+}
@@ -0,0 +1,218 @@
+use super::fuzzy::fuzzy_score;
+use super::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+/// An action the TUI can perform from navigation mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Command {
+    Quit,
+    NextSection,
+    PreviousSection,
+    NextField,
+    PreviousField,
+    JumpToSection(usize),
+    EditField,
+    ResetField,
+    ResetSection,
+    ResetAll,
+    Undo,
+    Save,
+    Reload,
+    OpenEditor,
+    ToggleTheme,
+    Export,
+    EditRaw,
+    Search,
+    Review,
+    Backups,
+    Palette,
+    Help,
+    /// Index into the focused field's schema actions
+    SchemaAction(usize),
+}
+
+// Keymap action name of each command that can be bound to a key
+const COMMAND_ACTIONS: &[(Command, &str)] = &[
+    (Command::Quit, "nav.quit"),
+    (Command::NextSection, "nav.next_section"),
+    (Command::PreviousSection, "nav.previous_section"),
+    (Command::NextField, "nav.next_field"),
+    (Command::PreviousField, "nav.previous_field"),
+    (Command::EditField, "field.edit"),
+    (Command::OpenEditor, "field.open_editor"),
+    (Command::ResetField, "field.reset"),
+    (Command::ResetSection, "section.reset"),
+    (Command::ResetAll, "app.reset_all"),
+    (Command::Undo, "app.undo"),
+    (Command::Save, "app.save"),
+    (Command::Reload, "app.reload"),
+    (Command::Search, "app.search"),
+    (Command::Review, "app.review"),
+    (Command::Backups, "app.backups"),
+    (Command::Palette, "app.palette"),
+    (Command::ToggleTheme, "app.toggle_theme"),
+    (Command::Export, "app.export"),
+    (Command::EditRaw, "app.edit_raw"),
+    (Command::Help, "app.help"),
+];
+
+impl Command {
+    pub(crate) fn from_action(action: &str) -> Option<Command> {
+        COMMAND_ACTIONS
+            .iter()
+            .find(|(_, name)| *name == action)
+            .map(|(command, _)| command.clone())
+    }
+
+    /// Keymap action name, `None` for commands that only exist in the palette
+    pub(crate) fn action(&self) -> Option<&'static str> {
+        COMMAND_ACTIONS
+            .iter()
+            .find(|(command, _)| command == self)
+            .map(|(_, name)| *name)
+    }
+}
+
+/// A command offered by the palette, with its label and current key
+pub(crate) struct PaletteEntry {
+    pub(crate) command: Command,
+    pub(crate) label: String,
+    pub(crate) key: String,
+}
+
+/// What the user chose in the command palette
+pub(crate) enum PaletteAction {
+    None,
+    Close,
+    Run(Command),
+}
+
+/// `Ctrl-P` overlay listing every command available in the current context
+pub(crate) struct CommandPalette {
+    entries: Vec<PaletteEntry>,
+    query: String,
+    // Indices into `entries`, best match first
+    results: Vec<usize>,
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub(crate) fn new(entries: Vec<PaletteEntry>) -> Self {
+        let mut palette = Self {
+            entries,
+            query: String::new(),
+            results: Vec::new(),
+            selected: 0,
+        };
+        palette.update_results();
+        palette
+    }
+
+    fn update_results(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| Some((fuzzy_score(&self.query, &entry.label)?, i)))
+            .collect();
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        self.results = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> PaletteAction {
+        match key.code {
+            KeyCode::Esc => return PaletteAction::Close,
+            KeyCode::Enter => {
+                return match self.results.get(self.selected) {
+                    Some(&i) => PaletteAction::Run(self.entries[i].command.clone()),
+                    None => PaletteAction::None,
+                };
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.results.len() => self.selected += 1,
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_results();
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.update_results();
+            }
+            _ => {}
+        }
+        PaletteAction::None
+    }
+
+    pub(crate) fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let screen = area;
+        let width = screen.width.saturating_sub(screen.width / 3).max(40);
+        let height = (self.entries.len() as u16 + 4).min(screen.height.saturating_sub(4));
+        let area = Rect {
+            x: screen.x + screen.width.saturating_sub(width) / 2,
+            y: screen.y + screen.height / 8,
+            width: width.min(screen.width),
+            height,
+        };
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Commands (Enter run, Esc close)")
+            .border_style(
+                Style::default()
+                    .fg(theme.popup_border)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .style(Style::default().bg(theme.popup_bg).fg(theme.popup_fg));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(0)])
+            .split(inner);
+
+        let input = Paragraph::new(Line::from(vec![
+            Span::styled("> ", Style::default().fg(theme.primary)),
+            Span::raw(self.query.clone()),
+            Span::styled("█", Style::default().fg(theme.primary)),
+        ]))
+        .block(Block::default().borders(Borders::BOTTOM));
+        frame.render_widget(input, chunks[0]);
+
+        // Right-align keys against the label column
+        let label_width = chunks[1].width.saturating_sub(2) as usize;
+        let items: Vec<ListItem> = self
+            .results
+            .iter()
+            .map(|&i| {
+                let entry = &self.entries[i];
+                let pad = label_width
+                    .saturating_sub(entry.label.chars().count() + entry.key.chars().count());
+                ListItem::new(Line::from(vec![
+                    Span::raw(entry.label.clone()),
+                    Span::raw(" ".repeat(pad)),
+                    Span::styled(entry.key.clone(), Style::default().fg(theme.text_dim)),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .fg(theme.highlight_fg)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+        let mut state = ListState::default();
+        state.select((!self.results.is_empty()).then_some(self.selected));
+        frame.render_stateful_widget(list, chunks[1], &mut state);
+    }
+}
@@ -0,0 +1,274 @@
+use super::theme::Theme;
+use crate::config::ConfigLoader;
+use crate::schema::{ConfigSchema, SchemaValidator};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A problem found in a hand-edited config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RawError {
+    /// 1-based line the problem is on, if it could be located
+    pub(crate) line: Option<usize>,
+    /// Setting the problem is about; `None` for syntax errors
+    pub(crate) key: Option<String>,
+    pub(crate) message: String,
+}
+
+/// Parse edited config text and validate every key against the schema
+///
+/// Returns the flat values when the text is valid TOML, along with every
+/// unknown key and invalid value found in it.
+pub(crate) fn check_config_text(
+    schema: &ConfigSchema,
+    text: &str,
+) -> (Option<HashMap<String, Value>>, Vec<RawError>) {
+    if let Err(e) = toml::from_str::<toml::Table>(text) {
+        let line = e
+            .span()
+            .map(|span| text[..span.start].matches('\n').count() + 1);
+        let error = RawError {
+            line,
+            key: None,
+            message: e.message().to_string(),
+        };
+        return (None, vec![error]);
+    }
+    let values = match ConfigLoader::from_toml_string_with_expansion(text, false) {
+        Ok(store) => store.as_flat_map(),
+        Err(e) => {
+            let error = RawError {
+                line: None,
+                key: None,
+                message: e.to_string(),
+            };
+            return (None, vec![error]);
+        }
+    };
+
+    let fields: HashMap<String, _> = schema
+        .sections
+        .iter()
+        .flat_map(|section| {
+            section
+                .fields
+                .iter()
+                .map(move |field| (format!("{}.{}", section.id, field.id), &field.field_type))
+        })
+        .collect();
+
+    let mut errors: Vec<RawError> = values
+        .iter()
+        .filter_map(|(key, value)| {
+            let message = match fields.get(key) {
+                None => "unknown setting".to_string(),
+                Some(field_type) => SchemaValidator::validate_value(field_type, value)
+                    .err()?
+                    .to_string(),
+            };
+            Some(RawError {
+                line: key_line(text, key),
+                key: Some(key.clone()),
+                message,
+            })
+        })
+        .collect();
+    errors.sort_by_key(|e| (e.line.unwrap_or(usize::MAX), e.key.clone()));
+    (Some(values), errors)
+}
+
+/// 1-based line on which a dot-notation key is assigned
+fn key_line(text: &str, key: &str) -> Option<usize> {
+    let mut table = String::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            if !header.starts_with('[') {
+                let header = header.split(']').next().unwrap_or_default();
+                table = unquote_key(header);
+            }
+            continue;
+        }
+        let Some((name, _)) = line.split_once('=') else {
+            continue;
+        };
+        let name = unquote_key(name);
+        let full = if table.is_empty() {
+            name
+        } else {
+            format!("{}.{}", table, name)
+        };
+        if full == key {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+// `"a" . b` → `a.b`
+fn unquote_key(key: &str) -> String {
+    key.split('.')
+        .map(|part| part.trim().trim_matches(|c| c == '"' || c == '\''))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// What the user chose after an edit of the whole file failed validation
+pub(crate) enum RawEditAction {
+    None,
+    /// Open the editor again at this line
+    Reopen(Option<usize>),
+    Discard,
+    Accept,
+}
+
+/// Overlay listing the problems in a hand-edited config file
+pub(crate) struct RawEditDialog {
+    /// File content before editing, restored on discard
+    pub(crate) original: String,
+    /// The edited content
+    pub(crate) text: String,
+    errors: Vec<RawError>,
+    // False when the text isn't valid TOML and can't be loaded at all
+    can_accept: bool,
+    selected: usize,
+}
+
+impl RawEditDialog {
+    pub(crate) fn new(
+        original: String,
+        text: String,
+        errors: Vec<RawError>,
+        can_accept: bool,
+    ) -> Self {
+        Self {
+            original,
+            text,
+            errors,
+            can_accept,
+            selected: 0,
+        }
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> RawEditAction {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if self.selected + 1 < self.errors.len() => {
+                self.selected += 1
+            }
+            KeyCode::Enter | KeyCode::Char('r') => {
+                let line = self.errors.get(self.selected).and_then(|e| e.line);
+                return RawEditAction::Reopen(line);
+            }
+            KeyCode::Esc | KeyCode::Char('d') => return RawEditAction::Discard,
+            KeyCode::Char('a') if self.can_accept => return RawEditAction::Accept,
+            _ => {}
+        }
+        RawEditAction::None
+    }
+
+    pub(crate) fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let screen = area;
+        let area = Rect {
+            x: screen.x + screen.width / 10,
+            y: screen.y + screen.height / 6,
+            width: screen.width - screen.width / 5,
+            height: screen.height - screen.height / 3,
+        };
+        frame.render_widget(Clear, area);
+
+        let items: Vec<ListItem> = self
+            .errors
+            .iter()
+            .map(|error| {
+                let location = match (error.line, &error.key) {
+                    (Some(line), Some(key)) => format!("line {} {}: ", line, key),
+                    (Some(line), None) => format!("line {}: ", line),
+                    (None, Some(key)) => format!("{}: ", key),
+                    (None, None) => String::new(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(location, Style::default().fg(theme.text_dim)),
+                    Span::styled(error.message.clone(), Style::default().fg(theme.error)),
+                ]))
+            })
+            .collect();
+
+        let title = if self.can_accept {
+            "Invalid config (Enter re-open at line, a accept anyway, d discard)"
+        } else {
+            "Invalid TOML (Enter re-open at line, d discard)"
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .border_style(
+                        Style::default()
+                            .fg(theme.popup_border)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .style(Style::default().bg(theme.popup_bg).fg(theme.popup_fg)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol("» ");
+        let mut state = ListState::default();
+        state.select(Some(self.selected));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::SchemaParser;
+
+    #[test]
+    fn test_check_config_text_locates_errors() {
+        let schema = SchemaParser::from_string(
+            r#"{
+                "title": "Test",
+                "version": "1.0",
+                "sections": [{
+                    "id": "general",
+                    "title": "General",
+                    "fields": [
+                        {"id": "size", "label": "Size", "description": "Size", "type": "number", "min": 1, "max": 10},
+                        {"id": "name", "label": "Name", "description": "Name", "type": "string"}
+                    ]
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let text = "# settings\n[general]\nname = \"x\"\nsize = 42\n\"colour\" = 1\n";
+        let (values, errors) = check_config_text(&schema, text);
+        assert_eq!(values.unwrap()["general.size"], 42);
+        let found: Vec<_> = errors
+            .iter()
+            .map(|e| (e.line, e.key.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [(Some(4), "general.size"), (Some(5), "general.colour")]
+        );
+
+        let (values, errors) = check_config_text(&schema, "[general]\nsize = \n");
+        assert!(values.is_none());
+        assert_eq!(errors[0].line, Some(2));
+
+        let (_, errors) = check_config_text(&schema, "general.size = 3\n");
+        assert!(errors.is_empty());
+    }
+}
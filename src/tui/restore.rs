@@ -0,0 +1,147 @@
+use super::theme::Theme;
+use crate::config::{diff_lines, Backup, DiffLine};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+/// What the user chose in the restore dialog
+pub(crate) enum RestoreAction {
+    None,
+    Close,
+    Restore(Backup),
+}
+
+/// Overlay listing config backups with a diff against the current file
+pub(crate) struct RestoreDialog {
+    backups: Vec<Backup>,
+    current: String,
+    selected: usize,
+    // Diff of the selected backup, recomputed when the selection moves
+    diff: Vec<DiffLine>,
+    scroll: u16,
+}
+
+impl RestoreDialog {
+    pub(crate) fn new(backups: Vec<Backup>, current: String) -> Self {
+        let mut dialog = Self {
+            backups,
+            current,
+            selected: 0,
+            diff: Vec::new(),
+            scroll: 0,
+        };
+        dialog.refresh_diff();
+        dialog
+    }
+
+    fn refresh_diff(&mut self) {
+        self.scroll = 0;
+        self.diff = match self.backups.get(self.selected).map(Backup::read) {
+            Some(Ok(backup)) => diff_lines(&self.current, &backup),
+            Some(Err(e)) => vec![DiffLine::Same(e.to_string())],
+            None => Vec::new(),
+        };
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> RestoreAction {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return RestoreAction::Close,
+            KeyCode::Enter => {
+                return match self.backups.get(self.selected) {
+                    Some(backup) => RestoreAction::Restore(backup.clone()),
+                    None => RestoreAction::Close,
+                };
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.selected > 0 => {
+                self.selected -= 1;
+                self.refresh_diff();
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.selected + 1 < self.backups.len() => {
+                self.selected += 1;
+                self.refresh_diff();
+            }
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            _ => {}
+        }
+        RestoreAction::None
+    }
+
+    pub(crate) fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let screen = area;
+        let area = Rect {
+            x: screen.x + screen.width / 10,
+            y: screen.y + screen.height / 10,
+            width: screen.width - screen.width / 5,
+            height: screen.height - screen.height / 5,
+        };
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Restore backup (Enter restore, PgUp/PgDn scroll diff, Esc close)")
+            .border_style(
+                Style::default()
+                    .fg(theme.popup_border)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .style(Style::default().bg(theme.popup_bg).fg(theme.popup_fg));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if self.backups.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No backups found").style(Style::default().fg(theme.text_dim)),
+                inner,
+            );
+            return;
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(24), Constraint::Min(0)])
+            .split(inner);
+
+        let items: Vec<ListItem> = self
+            .backups
+            .iter()
+            .map(|b| ListItem::new(b.timestamp.clone()))
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::RIGHT).title("Snapshots"))
+            .highlight_style(
+                Style::default()
+                    .fg(theme.highlight_fg)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+        let mut state = ListState::default();
+        state.select(Some(self.selected));
+        frame.render_stateful_widget(list, chunks[0], &mut state);
+
+        let lines: Vec<Line> = self
+            .diff
+            .iter()
+            .map(|line| match line {
+                DiffLine::Same(l) => Line::from(Span::raw(format!("  {}", l))),
+                DiffLine::Removed(l) => Line::from(Span::styled(
+                    format!("- {}", l),
+                    Style::default().fg(theme.error),
+                )),
+                DiffLine::Added(l) => Line::from(Span::styled(
+                    format!("+ {}", l),
+                    Style::default().fg(theme.success),
+                )),
+            })
+            .collect();
+        let diff = Paragraph::new(lines)
+            .block(Block::default().title(" current → backup"))
+            .scroll((self.scroll, 0));
+        frame.render_widget(diff, chunks[1]);
+    }
+}
@@ -0,0 +1,263 @@
+use super::theme::Theme;
+use crate::config::DiffLine;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+
+// Unchanged lines shown around each change in the file diff
+const DIFF_CONTEXT: usize = 2;
+
+/// A value that differs from what the config file holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingChange {
+    /// Dot-notation key, e.g. `general.theme`
+    pub key: String,
+    /// Title of the section the field belongs to
+    pub section: String,
+    /// Value in the file, `None` if unset
+    pub old: Option<Value>,
+    /// Value that will be written, `None` if removed
+    pub new: Option<Value>,
+}
+
+/// What the user chose in the review dialog
+pub(crate) enum ReviewAction {
+    None,
+    Close,
+    Save,
+    Revert(String),
+}
+
+/// Overlay listing pending changes, with an optional diff of the TOML file
+pub(crate) struct ReviewDialog {
+    changes: Vec<PendingChange>,
+    // Display strings for (old, new), with secrets masked
+    labels: Vec<(String, String)>,
+    diff: Vec<DiffLine>,
+    show_diff: bool,
+    selected: usize,
+    scroll: u16,
+    // Enter saves; false when changes are written as they are made
+    can_save: bool,
+}
+
+impl ReviewDialog {
+    pub(crate) fn new(can_save: bool) -> Self {
+        Self {
+            changes: Vec::new(),
+            labels: Vec::new(),
+            diff: Vec::new(),
+            show_diff: false,
+            selected: 0,
+            scroll: 0,
+            can_save,
+        }
+    }
+
+    pub(crate) fn set_changes(
+        &mut self,
+        changes: Vec<PendingChange>,
+        labels: Vec<(String, String)>,
+        diff: Vec<DiffLine>,
+    ) {
+        self.selected = self.selected.min(changes.len().saturating_sub(1));
+        self.changes = changes;
+        self.labels = labels;
+        self.diff = diff;
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> ReviewAction {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return ReviewAction::Close,
+            KeyCode::Enter if self.can_save && !self.changes.is_empty() => {
+                return ReviewAction::Save
+            }
+            KeyCode::Char('r') | KeyCode::Backspace | KeyCode::Delete => {
+                if let Some(change) = self.changes.get(self.selected) {
+                    return ReviewAction::Revert(change.key.clone());
+                }
+            }
+            KeyCode::Char('d') => {
+                self.show_diff = !self.show_diff;
+                self.scroll = 0;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if self.selected + 1 < self.changes.len() => {
+                self.selected += 1;
+            }
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            _ => {}
+        }
+        ReviewAction::None
+    }
+
+    pub(crate) fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let screen = area;
+        let area = Rect {
+            x: screen.x + screen.width / 10,
+            y: screen.y + screen.height / 10,
+            width: screen.width - screen.width / 5,
+            height: screen.height - screen.height / 5,
+        };
+        frame.render_widget(Clear, area);
+
+        let title = if self.can_save {
+            "Review changes (Enter save, r revert, d diff, Esc back)"
+        } else {
+            "Review changes (r revert, d diff, Esc close)"
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(
+                Style::default()
+                    .fg(theme.popup_border)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .style(Style::default().bg(theme.popup_bg).fg(theme.popup_fg));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if self.changes.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No unsaved changes").style(Style::default().fg(theme.text_dim)),
+                inner,
+            );
+            return;
+        }
+
+        let chunks = if self.show_diff {
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .split(inner)
+        } else {
+            Layout::default()
+                .constraints([Constraint::Min(0)])
+                .split(inner)
+        };
+
+        let items: Vec<ListItem> = self
+            .changes
+            .iter()
+            .zip(&self.labels)
+            .map(|(change, (old, new))| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("[{}] ", change.section),
+                        Style::default().fg(theme.text_dim),
+                    ),
+                    Span::styled(
+                        change.key.clone(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw("  "),
+                    Span::styled(old.clone(), Style::default().fg(theme.error)),
+                    Span::raw(" → "),
+                    Span::styled(new.clone(), Style::default().fg(theme.success)),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .fg(theme.highlight_fg)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+        let mut state = ListState::default();
+        state.select(Some(self.selected));
+        frame.render_stateful_widget(list, chunks[0], &mut state);
+
+        if self.show_diff {
+            let lines: Vec<Line> = unified(&self.diff, DIFF_CONTEXT)
+                .into_iter()
+                .map(|line| match line {
+                    Some(DiffLine::Removed(l)) => Line::from(Span::styled(
+                        format!("- {}", l),
+                        Style::default().fg(theme.error),
+                    )),
+                    Some(DiffLine::Added(l)) => Line::from(Span::styled(
+                        format!("+ {}", l),
+                        Style::default().fg(theme.success),
+                    )),
+                    Some(DiffLine::Same(l)) => Line::from(format!("  {}", l)),
+                    None => Line::from(Span::styled("…", Style::default().fg(theme.text_dim))),
+                })
+                .collect();
+            let diff = Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .borders(Borders::TOP)
+                        .title(" file on disk → new"),
+                )
+                .scroll((self.scroll, 0));
+            frame.render_widget(diff, chunks[1]);
+        }
+    }
+}
+
+/// Changed lines with up to `context` unchanged lines around them; `None`
+/// marks skipped lines
+fn unified(diff: &[DiffLine], context: usize) -> Vec<Option<&DiffLine>> {
+    let changed: Vec<usize> = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+    let near_change = |i: usize| {
+        changed
+            .iter()
+            .any(|&c| i + context >= c && i <= c + context)
+    };
+
+    let mut out = Vec::new();
+    let mut skipped = false;
+    for (i, line) in diff.iter().enumerate() {
+        if near_change(i) {
+            if skipped {
+                out.push(None);
+            }
+            skipped = false;
+            out.push(Some(line));
+        } else {
+            skipped = true;
+        }
+    }
+    if skipped && !out.is_empty() {
+        out.push(None);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_keeps_context_around_changes() {
+        let same = |s: &str| DiffLine::Same(s.to_string());
+        let mut diff: Vec<DiffLine> = (0..8).map(|i| same(&i.to_string())).collect();
+        diff.insert(4, DiffLine::Added("new".to_string()));
+
+        let shown: Vec<Option<&DiffLine>> = unified(&diff, 1);
+        assert_eq!(
+            shown,
+            vec![
+                None,
+                Some(&same("3")),
+                Some(&DiffLine::Added("new".to_string())),
+                Some(&same("4")),
+                None
+            ]
+        );
+    }
+}
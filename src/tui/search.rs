@@ -0,0 +1,186 @@
+use super::fuzzy::fuzzy_score;
+use super::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+/// A field as seen by the search overlay
+pub(crate) struct SearchEntry {
+    pub(crate) section: usize,
+    pub(crate) field: usize,
+    pub(crate) section_title: String,
+    pub(crate) label: String,
+    pub(crate) key: String,
+    /// Displayed value, secrets already masked
+    pub(crate) value: String,
+    /// Text to match with its weight: label, id, subsection, description, value
+    pub(crate) haystack: Vec<(String, i64)>,
+    /// `visible_when` condition that currently hides the field's section
+    pub(crate) hidden_by: Option<String>,
+}
+
+/// What the user chose in the search overlay
+pub(crate) enum SearchAction {
+    None,
+    Close,
+    Jump(usize),
+}
+
+/// `/` overlay that fuzzy-finds fields across all sections
+pub(crate) struct SearchDialog {
+    entries: Vec<SearchEntry>,
+    query: String,
+    // Indices into `entries`, best match first
+    results: Vec<usize>,
+    selected: usize,
+}
+
+impl SearchDialog {
+    pub(crate) fn new(entries: Vec<SearchEntry>) -> Self {
+        let mut dialog = Self {
+            entries,
+            query: String::new(),
+            results: Vec::new(),
+            selected: 0,
+        };
+        dialog.update_results();
+        dialog
+    }
+
+    pub(crate) fn entry(&self, index: usize) -> &SearchEntry {
+        &self.entries[index]
+    }
+
+    fn update_results(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                let best = entry
+                    .haystack
+                    .iter()
+                    .filter_map(|(text, weight)| Some(fuzzy_score(&self.query, text)? * weight))
+                    .max()?;
+                // Fields that can't be shown right now sink below visible ones
+                let penalty = if entry.hidden_by.is_some() { 1000 } else { 0 };
+                Some((best - penalty, i))
+            })
+            .collect();
+        // Stable sort keeps schema order among equal scores
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        self.results = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> SearchAction {
+        match key.code {
+            KeyCode::Esc => return SearchAction::Close,
+            KeyCode::Enter => {
+                return match self.results.get(self.selected) {
+                    Some(&index) => SearchAction::Jump(index),
+                    None => SearchAction::None,
+                };
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.results.len() => self.selected += 1,
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_results();
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.update_results();
+            }
+            _ => {}
+        }
+        SearchAction::None
+    }
+
+    pub(crate) fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let screen = area;
+        let area = Rect {
+            x: screen.x + screen.width / 10,
+            y: screen.y + screen.height / 10,
+            width: screen.width - screen.width / 5,
+            height: screen.height - screen.height / 5,
+        };
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Search settings (Enter jump, Esc close)")
+            .border_style(
+                Style::default()
+                    .fg(theme.popup_border)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .style(Style::default().bg(theme.popup_bg).fg(theme.popup_fg));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(0)])
+            .split(inner);
+
+        let input = Paragraph::new(Line::from(vec![
+            Span::styled("/", Style::default().fg(theme.primary)),
+            Span::raw(self.query.clone()),
+            Span::styled("█", Style::default().fg(theme.primary)),
+            Span::styled(
+                format!("  {} match(es)", self.results.len()),
+                Style::default().fg(theme.text_dim),
+            ),
+        ]))
+        .block(Block::default().borders(Borders::BOTTOM));
+        frame.render_widget(input, chunks[0]);
+
+        let items: Vec<ListItem> = self
+            .results
+            .iter()
+            .map(|&i| {
+                let entry = &self.entries[i];
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{} › ", entry.section_title),
+                        Style::default().fg(theme.text_dim),
+                    ),
+                    Span::styled(
+                        entry.label.clone(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!("  ({})", entry.key),
+                        Style::default().fg(theme.text_dim),
+                    ),
+                ];
+                if !entry.value.is_empty() {
+                    spans.push(Span::raw(format!("  = {}", entry.value)));
+                }
+                if let Some(condition) = &entry.hidden_by {
+                    spans.push(Span::styled(
+                        format!("  [hidden: {}]", condition),
+                        Style::default().fg(theme.warning),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .fg(theme.highlight_fg)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+        let mut state = ListState::default();
+        state.select((!self.results.is_empty()).then_some(self.selected));
+        frame.render_stateful_widget(list, chunks[1], &mut state);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::tui::theme::Theme;
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
 use ratatui::{layout::Rect, Frame};
 use serde_json::Value;
 
@@ -16,13 +16,120 @@ pub enum WidgetResult {
     Cancelled,
 }
 
+/// Editing command bound to keys by the keymap's `edit.*` actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditAction {
+    Confirm,
+    Cancel,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+}
+
+impl EditAction {
+    pub const ALL: [EditAction; 10] = [
+        EditAction::Confirm,
+        EditAction::Cancel,
+        EditAction::Up,
+        EditAction::Down,
+        EditAction::Left,
+        EditAction::Right,
+        EditAction::Home,
+        EditAction::End,
+        EditAction::PageUp,
+        EditAction::PageDown,
+    ];
+
+    /// Keymap action name, e.g. `edit.confirm`
+    pub fn name(self) -> &'static str {
+        match self {
+            EditAction::Confirm => "edit.confirm",
+            EditAction::Cancel => "edit.cancel",
+            EditAction::Up => "edit.up",
+            EditAction::Down => "edit.down",
+            EditAction::Left => "edit.left",
+            EditAction::Right => "edit.right",
+            EditAction::Home => "edit.home",
+            EditAction::End => "edit.end",
+            EditAction::PageUp => "edit.page_up",
+            EditAction::PageDown => "edit.page_down",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    /// The key this action is bound to unless a keymap says otherwise
+    pub fn standard_key(self) -> KeyCode {
+        match self {
+            EditAction::Confirm => KeyCode::Enter,
+            EditAction::Cancel => KeyCode::Esc,
+            EditAction::Up => KeyCode::Up,
+            EditAction::Down => KeyCode::Down,
+            EditAction::Left => KeyCode::Left,
+            EditAction::Right => KeyCode::Right,
+            EditAction::Home => KeyCode::Home,
+            EditAction::End => KeyCode::End,
+            EditAction::PageUp => KeyCode::PageUp,
+            EditAction::PageDown => KeyCode::PageDown,
+        }
+    }
+
+    /// The action whose standard key `code` is, whatever the modifiers
+    pub fn for_code(code: KeyCode) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.standard_key() == code)
+    }
+
+    /// The action whose standard key was pressed, without modifiers
+    pub fn from_key(key: &KeyEvent) -> Option<Self> {
+        if key.modifiers.is_empty() {
+            Self::for_code(key.code)
+        } else {
+            None
+        }
+    }
+}
+
 /// Base trait for all interactive widgets
+///
+/// Editing commands arrive as [`EditAction`]s resolved by the keymap, all
+/// other keys as input, so a rebound action's standard key doesn't act too.
 pub trait Widget {
     /// Render the widget to the frame
-    fn render(&self, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme);
+    ///
+    /// `bounds` is the editor's pane; popups stay inside it.
+    fn render(&self, frame: &mut Frame, area: Rect, bounds: Rect, focused: bool, theme: &Theme);
+
+    /// Handle an editing command such as confirm or move up
+    fn handle_action(&mut self, action: EditAction) -> WidgetResult;
+
+    /// Handle a key that isn't an editing command, e.g. typed text
+    fn handle_input(&mut self, key: KeyEvent) -> WidgetResult;
+
+    /// Whether `key` is input for this widget even though it is the standard
+    /// key of an action, like Enter in a multi-line editor
+    fn takes_input(&self, _key: &KeyEvent) -> bool {
+        false
+    }
 
-    /// Handle a key event, returning the result
-    fn handle_key(&mut self, key: KeyEvent) -> WidgetResult;
+    /// Handle a key with the standard key of each [`EditAction`]
+    fn handle_key(&mut self, key: KeyEvent) -> WidgetResult {
+        match EditAction::from_key(&key) {
+            Some(action) if !self.takes_input(&key) => self.handle_action(action),
+            _ => self.handle_input(key),
+        }
+    }
+
+    /// Handle a mouse event; widgets without mouse support ignore it
+    fn handle_mouse(&mut self, _mouse: MouseEvent) -> WidgetResult {
+        WidgetResult::Continue
+    }
 
     /// Get the current value
     fn get_value(&self) -> Value;
@@ -44,3 +151,29 @@ pub enum WidgetState {
     Focused,
     Editing,
 }
+
+/// Index of the item under a screen position in a bordered list of
+/// one-line items
+///
+/// The list is assumed to scroll just far enough to show `selected`, as
+/// ratatui does when rendering with a fresh `ListState`.
+pub(crate) fn list_item_at(
+    area: Rect,
+    selected: usize,
+    len: usize,
+    column: u16,
+    row: u16,
+) -> Option<usize> {
+    let inner = Rect {
+        x: area.x + 1,
+        y: area.y + 1,
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(2),
+    };
+    if column < inner.x || column >= inner.right() || row < inner.y || row >= inner.bottom() {
+        return None;
+    }
+    let offset = (selected + 1).saturating_sub(inner.height as usize);
+    let index = offset + (row - inner.y) as usize;
+    (index < len).then_some(index)
+}
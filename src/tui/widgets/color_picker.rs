@@ -0,0 +1,432 @@
+use super::{EditAction, Widget, WidgetResult, WidgetState};
+use crate::schema::{ColorFormat, ColorValue};
+use crate::tui::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+
+const SLIDER_WIDTH: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SliderMode {
+    Rgb,
+    Hsv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Row {
+    Hex,
+    Channel(usize),
+    Palette,
+}
+
+/// Color picker with a truecolor swatch, RGB/HSV sliders, hex input and palette
+pub struct ColorPicker {
+    color: ColorValue,
+    // Kept separately so hue survives passing through greys
+    hsv: (f64, f64, f64),
+    format: ColorFormat,
+    palette: Vec<(String, ColorValue)>,
+    palette_index: usize,
+    hex_buffer: String,
+    mode: SliderMode,
+    row: usize,
+    state: WidgetState,
+    label: String,
+}
+
+impl ColorPicker {
+    pub fn new(
+        label: impl Into<String>,
+        initial_value: &str,
+        format: ColorFormat,
+        palette: Vec<String>,
+    ) -> Self {
+        let color = ColorValue::parse(initial_value).unwrap_or(ColorValue::rgb(0, 0, 0));
+        let palette = palette
+            .into_iter()
+            .filter_map(|entry| ColorValue::parse(&entry).ok().map(|c| (entry, c)))
+            .collect();
+
+        Self {
+            color,
+            hsv: color.to_hsv(),
+            format,
+            palette,
+            palette_index: 0,
+            hex_buffer: color.format(Self::hex_format(format)),
+            mode: SliderMode::Rgb,
+            row: 0,
+            state: WidgetState::Normal,
+            label: label.into(),
+        }
+    }
+
+    fn hex_format(format: ColorFormat) -> ColorFormat {
+        if format.has_alpha() {
+            ColorFormat::HexAlpha
+        } else {
+            ColorFormat::Hex
+        }
+    }
+
+    fn channel_count(&self) -> usize {
+        if self.format.has_alpha() {
+            4
+        } else {
+            3
+        }
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![Row::Hex];
+        rows.extend((0..self.channel_count()).map(Row::Channel));
+        if !self.palette.is_empty() {
+            rows.push(Row::Palette);
+        }
+        rows
+    }
+
+    fn current_row(&self) -> Row {
+        self.rows()[self.row]
+    }
+
+    fn set_color(&mut self, color: ColorValue) {
+        self.color = color;
+        self.hsv = color.to_hsv();
+        self.hex_buffer = color.format(Self::hex_format(self.format));
+    }
+
+    /// (label, current value, maximum) for a slider channel
+    fn channel(&self, index: usize) -> (&'static str, f64, f64) {
+        match (self.mode, index) {
+            (_, 3) => ("A", self.color.a as f64, 255.0),
+            (SliderMode::Rgb, 0) => ("R", self.color.r as f64, 255.0),
+            (SliderMode::Rgb, 1) => ("G", self.color.g as f64, 255.0),
+            (SliderMode::Rgb, _) => ("B", self.color.b as f64, 255.0),
+            (SliderMode::Hsv, 0) => ("H", self.hsv.0, 360.0),
+            (SliderMode::Hsv, 1) => ("S", self.hsv.1, 100.0),
+            (SliderMode::Hsv, _) => ("V", self.hsv.2, 100.0),
+        }
+    }
+
+    fn adjust_channel(&mut self, index: usize, delta: f64) {
+        let byte = |v: u8| (v as f64 + delta).clamp(0.0, 255.0).round() as u8;
+
+        match (self.mode, index) {
+            (_, 3) => self.color.a = byte(self.color.a),
+            (SliderMode::Rgb, 0) => self.color.r = byte(self.color.r),
+            (SliderMode::Rgb, 1) => self.color.g = byte(self.color.g),
+            (SliderMode::Rgb, _) => self.color.b = byte(self.color.b),
+            (SliderMode::Hsv, _) => {
+                let (mut h, mut s, mut v) = self.hsv;
+                match index {
+                    0 => h = (h + delta).rem_euclid(360.0),
+                    1 => s = (s + delta).clamp(0.0, 100.0),
+                    _ => v = (v + delta).clamp(0.0, 100.0),
+                }
+                self.color = ColorValue::from_hsv(h, s, v, self.color.a);
+                self.hsv = (h, s, v);
+                self.hex_buffer = self.color.format(Self::hex_format(self.format));
+                return;
+            }
+        }
+
+        let color = self.color;
+        self.set_color(color);
+    }
+
+    fn select_palette(&mut self, forward: bool) {
+        let len = self.palette.len();
+        if len == 0 {
+            return;
+        }
+        self.palette_index = if forward {
+            (self.palette_index + 1) % len
+        } else {
+            (self.palette_index + len - 1) % len
+        };
+        let color = self.palette[self.palette_index].1;
+        self.set_color(color);
+    }
+
+    fn formatted(&self) -> String {
+        self.color.format(self.format)
+    }
+
+    fn swatch(color: ColorValue) -> Color {
+        Color::Rgb(color.r, color.g, color.b)
+    }
+
+    fn slider_bar(value: f64, max: f64) -> String {
+        let filled = ((value / max) * SLIDER_WIDTH as f64).round() as usize;
+        let filled = filled.min(SLIDER_WIDTH);
+        format!(
+            "{}{}",
+            "█".repeat(filled),
+            "░".repeat(SLIDER_WIDTH - filled)
+        )
+    }
+
+    /// Move the focused slider, or through the palette, one way
+    fn adjust(&mut self, forward: bool, step: f64) {
+        match self.current_row() {
+            Row::Channel(index) => self.adjust_channel(index, if forward { step } else { -step }),
+            Row::Palette => self.select_palette(forward),
+            Row::Hex => {}
+        }
+    }
+
+    fn changed_from(&self, before: ColorValue) -> WidgetResult {
+        if self.color != before {
+            WidgetResult::Changed(self.get_value())
+        } else {
+            WidgetResult::Continue
+        }
+    }
+}
+
+impl Widget for ColorPicker {
+    fn render(&self, frame: &mut Frame, area: Rect, bounds: Rect, focused: bool, theme: &Theme) {
+        if self.state == WidgetState::Editing {
+            self.render_picker(frame, area, bounds, theme);
+        } else {
+            self.render_compact(frame, area, focused, theme);
+        }
+    }
+
+    fn handle_action(&mut self, action: EditAction) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        let before = self.color;
+        match action {
+            EditAction::Confirm => {
+                self.state = WidgetState::Normal;
+                return WidgetResult::Confirmed(self.get_value());
+            }
+            EditAction::Cancel => {
+                self.state = WidgetState::Normal;
+                return WidgetResult::Cancelled;
+            }
+            EditAction::Up => self.row = self.row.saturating_sub(1),
+            EditAction::Down => self.row = (self.row + 1).min(self.rows().len() - 1),
+            EditAction::Left => self.adjust(false, 1.0),
+            EditAction::Right => self.adjust(true, 1.0),
+            _ => {}
+        }
+        self.changed_from(before)
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        let before = self.color;
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Tab => {
+                self.mode = match self.mode {
+                    SliderMode::Rgb => SliderMode::Hsv,
+                    SliderMode::Hsv => SliderMode::Rgb,
+                };
+            }
+            // Shift moves ten times as far
+            KeyCode::Left if shift => self.adjust(false, 10.0),
+            KeyCode::Right if shift => self.adjust(true, 10.0),
+            KeyCode::Char(c)
+                if self.current_row() == Row::Hex && (c.is_ascii_hexdigit() || c == '#') =>
+            {
+                if self.hex_buffer.len() < 9 {
+                    self.hex_buffer.push(c);
+                }
+                if let Ok(color) = ColorValue::parse(&self.hex_buffer) {
+                    self.color = color;
+                    self.hsv = color.to_hsv();
+                }
+            }
+            KeyCode::Backspace if self.current_row() == Row::Hex => {
+                self.hex_buffer.pop();
+                if let Ok(color) = ColorValue::parse(&self.hex_buffer) {
+                    self.color = color;
+                    self.hsv = color.to_hsv();
+                }
+            }
+            _ => {}
+        }
+        self.changed_from(before)
+    }
+
+    fn get_value(&self) -> Value {
+        Value::String(self.formatted())
+    }
+
+    fn set_value(&mut self, value: Value) {
+        if let Some(color) = value.as_str().and_then(|s| ColorValue::parse(s).ok()) {
+            self.set_color(color);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state = WidgetState::Normal;
+        self.row = 0;
+    }
+
+    fn activate(&mut self) {
+        self.state = WidgetState::Editing;
+        self.row = 0;
+    }
+}
+
+impl ColorPicker {
+    fn render_compact(&self, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+        let style = if focused {
+            Style::default()
+                .fg(theme.focused)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+
+        let content = Line::from(vec![
+            Span::styled(
+                format!("{}: ", self.label),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("██ ", Style::default().fg(Self::swatch(self.color))),
+            Span::styled(self.formatted(), style),
+        ]);
+
+        frame.render_widget(Paragraph::new(content), area);
+    }
+
+    fn render_picker(&self, frame: &mut Frame, area: Rect, bounds: Rect, theme: &Theme) {
+        let rows = self.rows();
+        let height = rows.len() as u16 + 6;
+        let width = (SLIDER_WIDTH as u16 + 20).max(self.label.len() as u16 + 30);
+
+        let popup_area = Rect {
+            x: area.x,
+            y: area.y.saturating_add(1),
+            width: width.min(bounds.right().saturating_sub(area.x)),
+            height: height.min(bounds.bottom().saturating_sub(area.y + 1)),
+        };
+
+        let selected = |row: Row| rows.get(self.row) == Some(&row);
+        let marker = |row: Row| if selected(row) { "» " } else { "  " };
+        let row_style = |row: Row| {
+            if selected(row) {
+                Style::default()
+                    .fg(theme.highlight_fg)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.popup_fg)
+            }
+        };
+
+        let swatch = "█".repeat(SLIDER_WIDTH + 8);
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!("  {}", swatch),
+                Style::default().fg(Self::swatch(self.color)),
+            )),
+            Line::from(Span::styled(
+                format!("  {}", swatch),
+                Style::default().fg(Self::swatch(self.color)),
+            )),
+        ];
+
+        let hex_text = if selected(Row::Hex) {
+            format!("{}█", self.hex_buffer)
+        } else {
+            self.hex_buffer.clone()
+        };
+        let hex_valid = ColorValue::parse(&self.hex_buffer).is_ok();
+        lines.push(Line::from(vec![
+            Span::styled(marker(Row::Hex), row_style(Row::Hex)),
+            Span::styled("Hex ", row_style(Row::Hex)),
+            Span::styled(
+                hex_text,
+                if hex_valid {
+                    row_style(Row::Hex)
+                } else {
+                    Style::default().fg(theme.error)
+                },
+            ),
+        ]));
+
+        for index in 0..self.channel_count() {
+            let row = Row::Channel(index);
+            let (name, value, max) = self.channel(index);
+            lines.push(Line::from(vec![
+                Span::styled(marker(row), row_style(row)),
+                Span::styled(format!("{}   ", name), row_style(row)),
+                Span::styled(Self::slider_bar(value, max), row_style(row)),
+                Span::styled(format!(" {:>3}", value.round() as i64), row_style(row)),
+            ]));
+        }
+
+        if !self.palette.is_empty() {
+            let mut spans = vec![
+                Span::styled(marker(Row::Palette), row_style(Row::Palette)),
+                Span::styled("Palette ", row_style(Row::Palette)),
+            ];
+            for (i, (_, color)) in self.palette.iter().enumerate() {
+                let (open, close) = if i == self.palette_index && selected(Row::Palette) {
+                    ("[", "]")
+                } else {
+                    (" ", " ")
+                };
+                spans.push(Span::styled(open, row_style(Row::Palette)));
+                spans.push(Span::styled(
+                    "██",
+                    Style::default().fg(Self::swatch(*color)),
+                ));
+                spans.push(Span::styled(close, row_style(Row::Palette)));
+            }
+            lines.push(Line::from(spans));
+        }
+
+        let mode = match self.mode {
+            SliderMode::Rgb => "RGB",
+            SliderMode::Hsv => "HSV",
+        };
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  {}  ↑↓ row  ←→ adjust (Shift ×10)  Tab {}",
+                self.formatted(),
+                if self.mode == SliderMode::Rgb {
+                    "HSV"
+                } else {
+                    "RGB"
+                }
+            ),
+            Style::default().fg(theme.text_dim),
+        )));
+
+        frame.render_widget(Clear, popup_area);
+        let picker = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "{} ({}, Enter confirm, Esc cancel)",
+                    self.label, mode
+                ))
+                .border_style(
+                    Style::default()
+                        .fg(theme.popup_border)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .style(Style::default().bg(theme.popup_bg).fg(theme.popup_fg)),
+        );
+        frame.render_widget(picker, popup_area);
+    }
+}
@@ -1,6 +1,6 @@
-use super::{Widget, WidgetResult, WidgetState};
+use super::{list_item_at, EditAction, Widget, WidgetResult, WidgetState};
 use crate::tui::theme::Theme;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     layout::Rect,
     style::{Modifier, Style},
@@ -9,6 +9,7 @@ use ratatui::{
     Frame,
 };
 use serde_json::Value;
+use std::cell::Cell;
 
 pub struct Dropdown {
     options: Vec<String>,
@@ -16,6 +17,8 @@ pub struct Dropdown {
     state: WidgetState,
     label: String,
     list_state: ListState,
+    // Where the option list was last drawn, for mouse clicks
+    popup_area: Cell<Rect>,
 }
 
 impl Dropdown {
@@ -39,6 +42,7 @@ impl Dropdown {
             state: WidgetState::Normal,
             label: label.into(),
             list_state,
+            popup_area: Cell::new(Rect::default()),
         }
     }
 
@@ -76,36 +80,84 @@ impl Dropdown {
 }
 
 impl Widget for Dropdown {
-    fn render(&self, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+    fn render(&self, frame: &mut Frame, area: Rect, bounds: Rect, focused: bool, theme: &Theme) {
         if self.state == WidgetState::Editing {
-            self.render_dropdown(frame, area, theme);
+            self.render_dropdown(frame, area, bounds, theme);
         } else {
             self.render_compact(frame, area, focused, theme);
         }
     }
 
-    fn handle_key(&mut self, key: KeyEvent) -> WidgetResult {
+    fn handle_action(&mut self, action: EditAction) -> WidgetResult {
         if self.state != WidgetState::Editing {
             return WidgetResult::Continue;
         }
 
-        match key.code {
-            KeyCode::Enter => {
+        match action {
+            EditAction::Confirm => {
                 self.state = WidgetState::Normal;
-                WidgetResult::Confirmed(self.get_value())
+                return WidgetResult::Confirmed(self.get_value());
             }
-            KeyCode::Esc => {
+            EditAction::Cancel => {
                 self.state = WidgetState::Normal;
-                WidgetResult::Cancelled
+                return WidgetResult::Cancelled;
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            EditAction::Down => self.select_next(),
+            EditAction::Up => self.select_previous(),
+            _ => {}
+        }
+        WidgetResult::Continue
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        match key.code {
+            KeyCode::Char('j') => self.select_next(),
+            KeyCode::Char('k') => self.select_previous(),
+            _ => {}
+        }
+        WidgetResult::Continue
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown => {
                 self.select_next();
                 WidgetResult::Continue
             }
-            KeyCode::Up | KeyCode::Char('k') => {
+            MouseEventKind::ScrollUp => {
                 self.select_previous();
                 WidgetResult::Continue
             }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let popup = self.popup_area.get();
+                let item = list_item_at(
+                    popup,
+                    self.selected_index,
+                    self.options.len(),
+                    mouse.column,
+                    mouse.row,
+                );
+                if let Some(index) = item {
+                    self.selected_index = index;
+                    self.list_state.select(Some(index));
+                    self.state = WidgetState::Normal;
+                    WidgetResult::Confirmed(self.get_value())
+                } else if popup.contains((mouse.column, mouse.row).into()) {
+                    WidgetResult::Continue
+                } else {
+                    // Clicking elsewhere closes the list
+                    self.state = WidgetState::Normal;
+                    WidgetResult::Cancelled
+                }
+            }
             _ => WidgetResult::Continue,
         }
     }
@@ -158,8 +210,7 @@ impl Dropdown {
         frame.render_widget(paragraph, area);
     }
 
-    fn render_dropdown(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
-        let frame_size = frame.area();
+    fn render_dropdown(&self, frame: &mut Frame, area: Rect, bounds: Rect, theme: &Theme) {
         let dropdown_height = (self.options.len() + 2).min(15) as u16;
         let dropdown_width = self
             .options
@@ -173,13 +224,14 @@ impl Dropdown {
         let popup_area = Rect {
             x: area.x,
             y: area.y.saturating_add(1),
-            width: dropdown_width.min(frame_size.width.saturating_sub(2)),
-            height: dropdown_height.min(frame_size.height.saturating_sub(area.y + 2)),
+            width: dropdown_width.min(bounds.right().saturating_sub(area.x)),
+            height: dropdown_height.min(bounds.bottom().saturating_sub(area.y + 2)),
         };
 
         use ratatui::style::Color;
         use ratatui::widgets::Clear;
         frame.render_widget(Clear, popup_area);
+        self.popup_area.set(popup_area);
         let bg = Block::default().style(Style::default().bg(Color::Black).fg(Color::White));
         frame.render_widget(bg, popup_area);
 
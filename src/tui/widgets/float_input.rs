@@ -1,4 +1,5 @@
-use super::{Widget, WidgetResult, WidgetState};
+use super::slider::{decimals_of, format_stepped, step_value};
+use super::{EditAction, Widget, WidgetResult, WidgetState};
 use crate::tui::theme::Theme;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
@@ -77,6 +78,22 @@ impl FloatInput {
         Some(num)
     }
 
+    /// Nudge the value by `steps` times `step` (1.0 if unset), within the limits
+    fn increment(&mut self, steps: f64) -> WidgetResult {
+        let Ok(current) = self.buffer.parse::<f64>() else {
+            return WidgetResult::Continue;
+        };
+        let step = self.step.unwrap_or(1.0);
+        let new_val = step_value(current, steps, self.min, self.max, step);
+        if new_val == current {
+            return WidgetResult::Continue;
+        }
+
+        self.buffer = format_stepped(new_val, step, decimals_of(&self.buffer));
+        self.cursor_pos = self.buffer.len();
+        WidgetResult::Changed(self.get_value())
+    }
+
     fn get_display_text(&self) -> String {
         if self.state == WidgetState::Editing {
             let mut display = self.buffer.clone();
@@ -89,7 +106,7 @@ impl FloatInput {
 }
 
 impl Widget for FloatInput {
-    fn render(&self, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+    fn render(&self, frame: &mut Frame, area: Rect, _bounds: Rect, focused: bool, theme: &Theme) {
         let is_valid = self.validate().is_some();
 
         let style = if self.state == WidgetState::Editing {
@@ -149,24 +166,37 @@ impl Widget for FloatInput {
         frame.render_widget(paragraph, area);
     }
 
-    fn handle_key(&mut self, key: KeyEvent) -> WidgetResult {
+    fn handle_action(&mut self, action: EditAction) -> WidgetResult {
         if self.state != WidgetState::Editing {
             return WidgetResult::Continue;
         }
 
-        match key.code {
-            KeyCode::Enter => {
+        match action {
+            EditAction::Confirm => {
                 if let Some(num) = self.validate() {
                     self.state = WidgetState::Normal;
-                    WidgetResult::Confirmed(Value::from(num))
-                } else {
-                    WidgetResult::Continue
+                    return WidgetResult::Confirmed(Value::from(num));
                 }
             }
-            KeyCode::Esc => {
+            EditAction::Cancel => {
                 self.state = WidgetState::Normal;
-                WidgetResult::Cancelled
+                return WidgetResult::Cancelled;
             }
+            EditAction::Left => self.cursor_pos = self.cursor_pos.saturating_sub(1),
+            EditAction::Right => self.cursor_pos = (self.cursor_pos + 1).min(self.buffer.len()),
+            EditAction::Up => return self.increment(1.0),
+            EditAction::Down => return self.increment(-1.0),
+            _ => {}
+        }
+        WidgetResult::Continue
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        match key.code {
             KeyCode::Char(c) => {
                 self.insert_char(c);
                 WidgetResult::Changed(self.get_value())
@@ -175,40 +205,6 @@ impl Widget for FloatInput {
                 self.delete_char();
                 WidgetResult::Changed(self.get_value())
             }
-            KeyCode::Left => {
-                if self.cursor_pos > 0 {
-                    self.cursor_pos -= 1;
-                }
-                WidgetResult::Continue
-            }
-            KeyCode::Right => {
-                if self.cursor_pos < self.buffer.len() {
-                    self.cursor_pos += 1;
-                }
-                WidgetResult::Continue
-            }
-            KeyCode::Up => {
-                if let (Some(current), Some(step)) = (self.validate(), self.step) {
-                    let new_val = current + step;
-                    if self.max.is_none_or(|max| new_val <= max) {
-                        self.buffer = new_val.to_string();
-                        self.cursor_pos = self.buffer.len();
-                        return WidgetResult::Changed(self.get_value());
-                    }
-                }
-                WidgetResult::Continue
-            }
-            KeyCode::Down => {
-                if let (Some(current), Some(step)) = (self.validate(), self.step) {
-                    let new_val = current - step;
-                    if self.min.is_none_or(|min| new_val >= min) {
-                        self.buffer = new_val.to_string();
-                        self.cursor_pos = self.buffer.len();
-                        return WidgetResult::Changed(self.get_value());
-                    }
-                }
-                WidgetResult::Continue
-            }
             _ => WidgetResult::Continue,
         }
     }
@@ -238,3 +234,56 @@ impl Widget for FloatInput {
         self.cursor_pos = self.buffer.len();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn press(input: &mut FloatInput, code: KeyCode) -> WidgetResult {
+        input.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_arrow_keys_step_within_limits() {
+        let mut input = FloatInput::new("Scale", 0.8, Some(0.5), Some(1.0), Some(0.1));
+        input.activate();
+
+        assert!(matches!(press(&mut input, KeyCode::Up), WidgetResult::Changed(v) if v == 0.9));
+        assert!(matches!(press(&mut input, KeyCode::Up), WidgetResult::Changed(v) if v == 1.0));
+        assert!(matches!(
+            press(&mut input, KeyCode::Up),
+            WidgetResult::Continue
+        ));
+        for _ in 0..5 {
+            press(&mut input, KeyCode::Down);
+        }
+        assert_eq!(input.get_value(), 0.5);
+        assert!(matches!(
+            press(&mut input, KeyCode::Down),
+            WidgetResult::Continue
+        ));
+    }
+
+    #[test]
+    fn test_left_right_move_the_cursor_for_typing() {
+        let mut input = FloatInput::new("Scale", 1.5, None, None, None);
+        input.activate();
+
+        press(&mut input, KeyCode::Left);
+        press(&mut input, KeyCode::Left);
+        assert!(matches!(
+            press(&mut input, KeyCode::Char('2')),
+            WidgetResult::Changed(v) if v == 12.5
+        ));
+        assert!(matches!(
+            press(&mut input, KeyCode::Right),
+            WidgetResult::Continue
+        ));
+        assert!(matches!(press(&mut input, KeyCode::Down), WidgetResult::Changed(v) if v == 11.5));
+        assert!(matches!(
+            press(&mut input, KeyCode::Esc),
+            WidgetResult::Cancelled
+        ));
+    }
+}
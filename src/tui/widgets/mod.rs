@@ -1,15 +1,23 @@
 mod base;
+mod color_picker;
 mod dropdown;
 mod float_input;
+mod multiline_editor;
 mod number_input;
 mod searchable_dropdown;
+mod secret_input;
+mod slider;
 mod text_input;
 mod toggle;
 
 pub use base::*;
+pub use color_picker::*;
 pub use dropdown::*;
 pub use float_input::*;
+pub use multiline_editor::*;
 pub use number_input::*;
 pub use searchable_dropdown::*;
+pub use secret_input::*;
+pub use slider::*;
 pub use text_input::*;
 pub use toggle::*;
@@ -0,0 +1,706 @@
+use super::{EditAction, Widget, WidgetResult, WidgetState};
+use crate::tui::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+use std::cell::Cell;
+
+const TAB: &str = "    ";
+
+/// Position in the text as (line, column), both counted in chars
+type Pos = (usize, usize);
+
+/// Popup text editor for multi-line strings
+///
+/// Soft-wraps long lines and scrolls to keep the cursor visible; Up/Down
+/// move by wrapped row. Enter inserts a newline; Ctrl-S confirms, Esc
+/// cancels and Ctrl-E toggles between the compact popup and a full-screen
+/// editor. The text is only reported once confirmed.
+pub struct MultilineEditor {
+    lines: Vec<Vec<char>>,
+    cursor: Pos,
+    // Other end of the selection, if one is active
+    anchor: Option<Pos>,
+    expanded: bool,
+    state: WidgetState,
+    label: String,
+    // Updated while rendering, which only has `&self`
+    scroll: Cell<usize>,
+    page_height: Cell<usize>,
+    wrap_width: Cell<usize>,
+}
+
+impl MultilineEditor {
+    pub fn new(label: impl Into<String>, initial_value: &str) -> Self {
+        let mut editor = Self {
+            lines: Vec::new(),
+            cursor: (0, 0),
+            anchor: None,
+            expanded: false,
+            state: WidgetState::Normal,
+            label: label.into(),
+            scroll: Cell::new(0),
+            page_height: Cell::new(10),
+            wrap_width: Cell::new(usize::MAX),
+        };
+        editor.set_text(initial_value);
+        editor
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.lines = text.split('\n').map(|l| l.chars().collect()).collect();
+        self.anchor = None;
+        self.cursor = self.end_of_text();
+        self.scroll.set(0);
+    }
+
+    fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|l| l.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn end_of_text(&self) -> Pos {
+        let last = self.lines.len() - 1;
+        (last, self.lines[last].len())
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].len()
+    }
+
+    fn selection(&self) -> Option<(Pos, Pos)> {
+        let anchor = self.anchor.filter(|a| *a != self.cursor)?;
+        Some((anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+
+    fn delete_range(&mut self, start: Pos, end: Pos) {
+        if start.0 == end.0 {
+            self.lines[start.0].drain(start.1..end.1);
+        } else {
+            let tail: Vec<char> = self.lines[end.0][end.1..].to_vec();
+            self.lines[start.0].truncate(start.1);
+            self.lines[start.0].extend(tail);
+            self.lines.drain(start.0 + 1..=end.0);
+        }
+        self.cursor = start;
+        self.anchor = None;
+    }
+
+    /// Remove the selected text, returning whether there was any
+    fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some((start, end)) => {
+                self.delete_range(start, end);
+                true
+            }
+            None => {
+                self.anchor = None;
+                false
+            }
+        }
+    }
+
+    fn insert_str(&mut self, s: &str) {
+        self.delete_selection();
+        for c in s.chars() {
+            let (row, col) = self.cursor;
+            if c == '\n' {
+                let tail = self.lines[row].split_off(col);
+                self.lines.insert(row + 1, tail);
+                self.cursor = (row + 1, 0);
+            } else {
+                self.lines[row].insert(col, c);
+                self.cursor = (row, col + 1);
+            }
+        }
+    }
+
+    fn backspace(&mut self) {
+        if self.delete_selection() {
+            return;
+        }
+        let (row, col) = self.cursor;
+        if col > 0 {
+            self.delete_range((row, col - 1), (row, col));
+        } else if row > 0 {
+            let prev_len = self.line_len(row - 1);
+            self.delete_range((row - 1, prev_len), (row, 0));
+        }
+    }
+
+    fn delete_forward(&mut self) {
+        if self.delete_selection() {
+            return;
+        }
+        let (row, col) = self.cursor;
+        if col < self.line_len(row) {
+            self.delete_range((row, col), (row, col + 1));
+        } else if row + 1 < self.lines.len() {
+            self.delete_range((row, col), (row + 1, 0));
+        }
+    }
+
+    fn char_at(&self, (row, col): Pos) -> Option<char> {
+        self.lines[row].get(col).copied()
+    }
+
+    fn is_word_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+
+    fn step_left(&self, (row, col): Pos) -> Option<Pos> {
+        if col > 0 {
+            Some((row, col - 1))
+        } else if row > 0 {
+            Some((row - 1, self.line_len(row - 1)))
+        } else {
+            None
+        }
+    }
+
+    fn step_right(&self, (row, col): Pos) -> Option<Pos> {
+        if col < self.line_len(row) {
+            Some((row, col + 1))
+        } else if row + 1 < self.lines.len() {
+            Some((row + 1, 0))
+        } else {
+            None
+        }
+    }
+
+    /// Start of the word before the cursor
+    fn word_left(&self) -> Pos {
+        let mut pos = self.cursor;
+        let is_word_before = |p: Pos| {
+            self.step_left(p)
+                .and_then(|prev| self.char_at(prev))
+                .is_some_and(Self::is_word_char)
+        };
+
+        while self.step_left(pos).is_some() && !is_word_before(pos) {
+            pos = self.step_left(pos).unwrap_or(pos);
+        }
+        while is_word_before(pos) {
+            pos = self.step_left(pos).unwrap_or(pos);
+        }
+        pos
+    }
+
+    /// End of the word after the cursor
+    fn word_right(&self) -> Pos {
+        let mut pos = self.cursor;
+        let is_word_at = |p: Pos| self.char_at(p).is_some_and(Self::is_word_char);
+
+        while self.step_right(pos).is_some() && !is_word_at(pos) {
+            pos = self.step_right(pos).unwrap_or(pos);
+        }
+        while is_word_at(pos) {
+            pos = self.step_right(pos).unwrap_or(pos);
+        }
+        pos
+    }
+
+    /// Soft-wrapped rows of the whole text as (line, start, end)
+    fn visual_rows(&self) -> Vec<(usize, usize, usize)> {
+        let width = self.wrap_width.get();
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                Self::wrap(line, width)
+                    .into_iter()
+                    .map(move |(start, end)| (row, start, end))
+            })
+            .collect()
+    }
+
+    /// Move by `rows` wrapped rows, keeping the offset within the row
+    fn move_vertical(&self, rows: isize) -> Pos {
+        let visual = self.visual_rows();
+        let (row, col) = self.cursor;
+        // The cursor sits on the last of its line's rows that starts before it
+        let current = visual
+            .iter()
+            .rposition(|&(r, start, _)| r == row && start <= col)
+            .unwrap_or(0);
+        let offset = col - visual[current].1;
+
+        let target = (current as isize + rows).clamp(0, visual.len() as isize - 1) as usize;
+        let (row, start, end) = visual[target];
+        // A column at the end of a wrapped row belongs to the next one
+        let last_of_line = visual.get(target + 1).is_none_or(|next| next.0 != row);
+        let max = if last_of_line { end } else { end - 1 };
+        (row, (start + offset).min(max))
+    }
+
+    fn move_cursor(&mut self, to: Pos, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = to;
+    }
+
+    fn confirm(&mut self) -> WidgetResult {
+        self.state = WidgetState::Normal;
+        self.anchor = None;
+        WidgetResult::Confirmed(self.get_value())
+    }
+
+    /// Move the cursor; `ctrl` moves by word or to the ends of the text and
+    /// `shift` extends the selection
+    fn apply_motion(&mut self, motion: EditAction, ctrl: bool, shift: bool) {
+        let page = self.page_height.get().max(1) as isize;
+        let to = match motion {
+            EditAction::Left if ctrl => self.word_left(),
+            EditAction::Right if ctrl => self.word_right(),
+            EditAction::Left => self.step_left(self.cursor).unwrap_or(self.cursor),
+            EditAction::Right => self.step_right(self.cursor).unwrap_or(self.cursor),
+            EditAction::Up => self.move_vertical(-1),
+            EditAction::Down => self.move_vertical(1),
+            EditAction::PageUp => self.move_vertical(-page),
+            EditAction::PageDown => self.move_vertical(page),
+            EditAction::Home if ctrl => (0, 0),
+            EditAction::End if ctrl => self.end_of_text(),
+            EditAction::Home => (self.cursor.0, 0),
+            EditAction::End => (self.cursor.0, self.line_len(self.cursor.0)),
+            EditAction::Confirm | EditAction::Cancel => return,
+        };
+        self.move_cursor(to, shift);
+    }
+
+    /// Split a line into soft-wrapped rows of at most `width` chars
+    fn wrap(line: &[char], width: usize) -> Vec<(usize, usize)> {
+        if line.is_empty() {
+            return vec![(0, 0)];
+        }
+        (0..line.len())
+            .step_by(width)
+            .map(|start| (start, (start + width).min(line.len())))
+            .collect()
+    }
+
+    fn popup_area(&self, area: Rect, screen: Rect) -> Rect {
+        if self.expanded {
+            return Rect {
+                x: screen.x + 1,
+                y: screen.y + 1,
+                width: screen.width.saturating_sub(2),
+                height: screen.height.saturating_sub(2),
+            };
+        }
+
+        let available = screen.bottom().saturating_sub(area.y);
+        let wanted = (self.lines.len() as u16 + 3).clamp(6, (screen.height / 2).max(6));
+        Rect {
+            x: area.x,
+            y: area.y,
+            width: area.width,
+            height: wanted.min(available),
+        }
+    }
+
+    fn render_compact(&self, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+        let style = if focused {
+            Style::default().fg(theme.focused)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        let first = self.lines[0].iter().collect::<String>();
+        let mut spans = vec![
+            Span::styled(
+                format!("{}: ", self.label),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(first, style),
+        ];
+        if self.lines.len() > 1 {
+            spans.push(Span::styled(
+                format!(" … (+{} lines)", self.lines.len() - 1),
+                Style::default().fg(theme.text_dim),
+            ));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+
+    fn render_editor(&self, frame: &mut Frame, area: Rect, bounds: Rect, theme: &Theme) {
+        let popup = self.popup_area(area, bounds);
+        let width = popup.width.saturating_sub(2).max(1) as usize;
+        let height = popup.height.saturating_sub(2).max(1) as usize;
+        self.page_height.set(height);
+        self.wrap_width.set(width);
+
+        let text_style = Style::default().fg(theme.popup_fg).bg(theme.popup_bg);
+        let selected_style = text_style.add_modifier(Modifier::REVERSED);
+        let cursor_style = Style::default()
+            .fg(theme.popup_bg)
+            .bg(theme.editing)
+            .add_modifier(Modifier::BOLD);
+        let selection = self.selection();
+
+        let mut rows: Vec<Line> = Vec::new();
+        let mut cursor_row = 0;
+        for (row, line) in self.lines.iter().enumerate() {
+            let segments = Self::wrap(line, width);
+            let last_segment = segments.len() - 1;
+
+            for (i, (start, end)) in segments.into_iter().enumerate() {
+                let cursor_here = self.cursor.0 == row
+                    && self.cursor.1 >= start
+                    && (self.cursor.1 < end || (i == last_segment && self.cursor.1 == end));
+                if cursor_here {
+                    cursor_row = rows.len();
+                }
+
+                let mut spans: Vec<Span> = (start..end)
+                    .map(|col| {
+                        let style = if cursor_here && col == self.cursor.1 {
+                            cursor_style
+                        } else if selection.is_some_and(|(s, e)| (row, col) >= s && (row, col) < e)
+                        {
+                            selected_style
+                        } else {
+                            text_style
+                        };
+                        Span::styled(line[col].to_string(), style)
+                    })
+                    .collect();
+                if cursor_here && self.cursor.1 == end {
+                    spans.push(Span::styled(" ", cursor_style));
+                }
+                rows.push(Line::from(spans));
+            }
+        }
+
+        let mut scroll = self.scroll.get().min(rows.len().saturating_sub(1));
+        if cursor_row < scroll {
+            scroll = cursor_row;
+        } else if cursor_row >= scroll + height {
+            scroll = cursor_row + 1 - height;
+        }
+        self.scroll.set(scroll);
+
+        let visible: Vec<Line> = rows.into_iter().skip(scroll).take(height).collect();
+        let title = format!(
+            "{} — Ln {}, Col {} (Ctrl-S save, Esc cancel, Ctrl-E {})",
+            self.label,
+            self.cursor.0 + 1,
+            self.cursor.1 + 1,
+            if self.expanded { "shrink" } else { "expand" }
+        );
+
+        frame.render_widget(Clear, popup);
+        let editor = Paragraph::new(visible).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(
+                    Style::default()
+                        .fg(theme.editing)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .style(text_style),
+        );
+        frame.render_widget(editor, popup);
+    }
+}
+
+impl Widget for MultilineEditor {
+    fn render(&self, frame: &mut Frame, area: Rect, bounds: Rect, focused: bool, theme: &Theme) {
+        if self.state == WidgetState::Editing {
+            self.render_editor(frame, area, bounds, theme);
+        } else {
+            self.render_compact(frame, area, focused, theme);
+        }
+    }
+
+    fn handle_action(&mut self, action: EditAction) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        match action {
+            EditAction::Confirm => self.confirm(),
+            EditAction::Cancel => {
+                self.state = WidgetState::Normal;
+                self.anchor = None;
+                WidgetResult::Cancelled
+            }
+            motion => {
+                self.apply_motion(motion, false, false);
+                WidgetResult::Continue
+            }
+        }
+    }
+
+    // Enter is a newline here, so confirming takes Ctrl-S (or another key
+    // bound to `edit.confirm`)
+    fn takes_input(&self, key: &KeyEvent) -> bool {
+        key.code == KeyCode::Enter
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+        match key.code {
+            KeyCode::Char('s') if ctrl => return self.confirm(),
+            KeyCode::Char('e') if ctrl => self.expanded = !self.expanded,
+            KeyCode::Char('a') if ctrl => {
+                self.anchor = Some((0, 0));
+                self.cursor = self.end_of_text();
+            }
+            KeyCode::Char(c) if !ctrl => self.insert_str(&c.to_string()),
+            KeyCode::Enter => self.insert_str("\n"),
+            KeyCode::Tab => self.insert_str(TAB),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete_forward(),
+            // Shift selects and Ctrl moves by word or to the ends of the text
+            code if ctrl || shift => {
+                if let Some(motion) = EditAction::for_code(code) {
+                    self.apply_motion(motion, ctrl, shift);
+                }
+            }
+            _ => {}
+        }
+
+        WidgetResult::Continue
+    }
+
+    fn get_value(&self) -> Value {
+        Value::String(self.text())
+    }
+
+    fn set_value(&mut self, value: Value) {
+        if let Some(s) = value.as_str() {
+            self.set_text(s);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state = WidgetState::Normal;
+        self.anchor = None;
+        self.expanded = false;
+    }
+
+    fn activate(&mut self) {
+        self.state = WidgetState::Editing;
+        self.anchor = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn editor(text: &str) -> MultilineEditor {
+        let mut editor = MultilineEditor::new("Script", text);
+        editor.activate();
+        editor
+    }
+
+    fn press(editor: &mut MultilineEditor, code: KeyCode, modifiers: KeyModifiers) -> WidgetResult {
+        editor.handle_key(KeyEvent::new(code, modifiers))
+    }
+
+    fn key(editor: &mut MultilineEditor, code: KeyCode) -> WidgetResult {
+        press(editor, code, KeyModifiers::NONE)
+    }
+
+    /// Draw the editor in a `width` column wide field, setting its wrap width
+    /// (`width - 2`) and page height
+    fn render(editor: &MultilineEditor, width: u16) {
+        let mut terminal = Terminal::new(TestBackend::new(width, 20)).unwrap();
+        terminal
+            .draw(|frame| {
+                editor.render(
+                    frame,
+                    Rect::new(0, 0, width, 1),
+                    frame.area(),
+                    true,
+                    &Theme::default(),
+                );
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_line_navigation() {
+        let mut editor = editor("one\ntwo words\nthree");
+        assert_eq!(editor.cursor, (2, 5));
+
+        key(&mut editor, KeyCode::Up);
+        assert_eq!(editor.cursor, (1, 5));
+        key(&mut editor, KeyCode::Up);
+        assert_eq!(editor.cursor, (0, 3));
+        key(&mut editor, KeyCode::Up);
+        assert_eq!(editor.cursor, (0, 3));
+        key(&mut editor, KeyCode::Right);
+        assert_eq!(editor.cursor, (1, 0));
+        key(&mut editor, KeyCode::Left);
+        assert_eq!(editor.cursor, (0, 3));
+        key(&mut editor, KeyCode::Home);
+        assert_eq!(editor.cursor, (0, 0));
+        key(&mut editor, KeyCode::Down);
+        key(&mut editor, KeyCode::End);
+        assert_eq!(editor.cursor, (1, 9));
+        press(&mut editor, KeyCode::End, KeyModifiers::CONTROL);
+        assert_eq!(editor.cursor, (2, 5));
+        press(&mut editor, KeyCode::Home, KeyModifiers::CONTROL);
+        assert_eq!(editor.cursor, (0, 0));
+    }
+
+    #[test]
+    fn test_word_movement() {
+        let mut editor = editor("foo bar_baz, qux\nnext");
+        press(&mut editor, KeyCode::Home, KeyModifiers::CONTROL);
+
+        let mut stops = Vec::new();
+        for _ in 0..5 {
+            press(&mut editor, KeyCode::Right, KeyModifiers::CONTROL);
+            stops.push(editor.cursor);
+        }
+        assert_eq!(stops, vec![(0, 3), (0, 11), (0, 16), (1, 4), (1, 4)]);
+
+        let mut stops = Vec::new();
+        for _ in 0..4 {
+            press(&mut editor, KeyCode::Left, KeyModifiers::CONTROL);
+            stops.push(editor.cursor);
+        }
+        assert_eq!(stops, vec![(1, 0), (0, 13), (0, 4), (0, 0)]);
+    }
+
+    #[test]
+    fn test_selection_replaces_and_deletes() {
+        let mut editor = editor("hello world\nsecond line");
+        press(&mut editor, KeyCode::Home, KeyModifiers::CONTROL);
+        for _ in 0..5 {
+            press(&mut editor, KeyCode::Right, KeyModifiers::SHIFT);
+        }
+        key(&mut editor, KeyCode::Char('H'));
+        assert_eq!(editor.text(), "H world\nsecond line");
+
+        // Across lines
+        press(&mut editor, KeyCode::Right, KeyModifiers::CONTROL);
+        press(&mut editor, KeyCode::Down, KeyModifiers::SHIFT);
+        key(&mut editor, KeyCode::Delete);
+        assert_eq!(editor.text(), "H worldline");
+
+        // Moving without Shift drops the selection
+        press(&mut editor, KeyCode::Left, KeyModifiers::SHIFT);
+        key(&mut editor, KeyCode::Left);
+        key(&mut editor, KeyCode::Backspace);
+        assert_eq!(editor.text(), "H woldline");
+
+        press(&mut editor, KeyCode::Char('a'), KeyModifiers::CONTROL);
+        key(&mut editor, KeyCode::Backspace);
+        assert_eq!(editor.text(), "");
+    }
+
+    #[test]
+    fn test_up_down_follow_soft_wrapped_rows() {
+        // 25 chars wrap into rows of 10, 10 and 5
+        let mut editor = editor(&format!("{}\nab", "x".repeat(25)));
+        render(&editor, 12);
+        press(&mut editor, KeyCode::Home, KeyModifiers::CONTROL);
+        for _ in 0..5 {
+            key(&mut editor, KeyCode::Right);
+        }
+
+        key(&mut editor, KeyCode::Down);
+        assert_eq!(editor.cursor, (0, 15));
+        key(&mut editor, KeyCode::Down);
+        assert_eq!(editor.cursor, (0, 25));
+        key(&mut editor, KeyCode::Down);
+        assert_eq!(editor.cursor, (1, 2));
+        key(&mut editor, KeyCode::Up);
+        assert_eq!(editor.cursor, (0, 22));
+        key(&mut editor, KeyCode::Up);
+        assert_eq!(editor.cursor, (0, 12));
+
+        // The end of a full wrapped row is the start of the next one
+        key(&mut editor, KeyCode::End);
+        key(&mut editor, KeyCode::Up);
+        key(&mut editor, KeyCode::Up);
+        assert_eq!(editor.cursor, (0, 5));
+        key(&mut editor, KeyCode::Home);
+        key(&mut editor, KeyCode::Up);
+        assert_eq!(editor.cursor, (0, 0));
+        key(&mut editor, KeyCode::Down);
+        key(&mut editor, KeyCode::End);
+        assert_eq!(editor.cursor, (0, 25));
+        key(&mut editor, KeyCode::Left);
+        key(&mut editor, KeyCode::Left);
+        key(&mut editor, KeyCode::Left);
+        key(&mut editor, KeyCode::Left);
+        key(&mut editor, KeyCode::Left);
+        key(&mut editor, KeyCode::Up);
+        assert_eq!(editor.cursor, (0, 10));
+    }
+
+    #[test]
+    fn test_scrolls_to_keep_the_cursor_visible() {
+        let text: Vec<String> = (1..=30).map(|n| format!("line {}", n)).collect();
+        let mut editor = editor(&text.join("\n"));
+        // 20 rows high: the popup is capped at 10 rows, 8 inside the borders
+        render(&editor, 20);
+        assert_eq!(editor.page_height.get(), 8);
+        assert_eq!(editor.scroll.get(), 22);
+
+        press(&mut editor, KeyCode::Home, KeyModifiers::CONTROL);
+        render(&editor, 20);
+        assert_eq!(editor.scroll.get(), 0);
+
+        key(&mut editor, KeyCode::PageDown);
+        assert_eq!(editor.cursor, (8, 0));
+        render(&editor, 20);
+        assert_eq!(editor.scroll.get(), 1);
+
+        key(&mut editor, KeyCode::PageDown);
+        render(&editor, 20);
+        assert_eq!(editor.scroll.get(), 9);
+        key(&mut editor, KeyCode::PageUp);
+        render(&editor, 20);
+        assert_eq!(editor.cursor, (8, 0));
+        assert_eq!(editor.scroll.get(), 8);
+    }
+
+    #[test]
+    fn test_text_is_only_reported_when_confirmed() {
+        let mut editor = editor("a");
+        assert!(matches!(
+            key(&mut editor, KeyCode::Char('b')),
+            WidgetResult::Continue
+        ));
+        assert!(matches!(
+            key(&mut editor, KeyCode::Enter),
+            WidgetResult::Continue
+        ));
+        assert!(matches!(
+            press(&mut editor, KeyCode::Char('s'), KeyModifiers::CONTROL),
+            WidgetResult::Confirmed(v) if v == "ab\n"
+        ));
+
+        editor.activate();
+        key(&mut editor, KeyCode::Char('c'));
+        assert!(matches!(
+            key(&mut editor, KeyCode::Esc),
+            WidgetResult::Cancelled
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Widget, WidgetResult, WidgetState};
+use super::{EditAction, Widget, WidgetResult, WidgetState};
 use crate::tui::theme::Theme;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
@@ -71,6 +71,27 @@ impl NumberInput {
         Some(num)
     }
 
+    /// Nudge the value by `delta`, within the limits
+    fn increment(&mut self, delta: i64) -> WidgetResult {
+        let Ok(current) = self.buffer.parse::<i64>() else {
+            return WidgetResult::Continue;
+        };
+        let mut new_val = current.saturating_add(delta);
+        if let Some(min) = self.min {
+            new_val = new_val.max(min);
+        }
+        if let Some(max) = self.max {
+            new_val = new_val.min(max);
+        }
+        if new_val == current {
+            return WidgetResult::Continue;
+        }
+
+        self.buffer = new_val.to_string();
+        self.cursor_pos = self.buffer.len();
+        WidgetResult::Changed(self.get_value())
+    }
+
     fn get_display_text(&self) -> String {
         if self.state == WidgetState::Editing {
             let mut display = self.buffer.clone();
@@ -83,7 +104,7 @@ impl NumberInput {
 }
 
 impl Widget for NumberInput {
-    fn render(&self, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+    fn render(&self, frame: &mut Frame, area: Rect, _bounds: Rect, focused: bool, theme: &Theme) {
         let is_valid = self.validate().is_some();
 
         let style = if self.state == WidgetState::Editing {
@@ -143,24 +164,37 @@ impl Widget for NumberInput {
         frame.render_widget(paragraph, area);
     }
 
-    fn handle_key(&mut self, key: KeyEvent) -> WidgetResult {
+    fn handle_action(&mut self, action: EditAction) -> WidgetResult {
         if self.state != WidgetState::Editing {
             return WidgetResult::Continue;
         }
 
-        match key.code {
-            KeyCode::Enter => {
+        match action {
+            EditAction::Confirm => {
                 if let Some(num) = self.validate() {
                     self.state = WidgetState::Normal;
-                    WidgetResult::Confirmed(Value::Number(num.into()))
-                } else {
-                    WidgetResult::Continue
+                    return WidgetResult::Confirmed(Value::Number(num.into()));
                 }
             }
-            KeyCode::Esc => {
+            EditAction::Cancel => {
                 self.state = WidgetState::Normal;
-                WidgetResult::Cancelled
+                return WidgetResult::Cancelled;
             }
+            EditAction::Left => self.cursor_pos = self.cursor_pos.saturating_sub(1),
+            EditAction::Right => self.cursor_pos = (self.cursor_pos + 1).min(self.buffer.len()),
+            EditAction::Up => return self.increment(1),
+            EditAction::Down => return self.increment(-1),
+            _ => {}
+        }
+        WidgetResult::Continue
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        match key.code {
             KeyCode::Char(c) => {
                 self.insert_char(c);
                 WidgetResult::Changed(self.get_value())
@@ -169,18 +203,6 @@ impl Widget for NumberInput {
                 self.delete_char();
                 WidgetResult::Changed(self.get_value())
             }
-            KeyCode::Left => {
-                if self.cursor_pos > 0 {
-                    self.cursor_pos -= 1;
-                }
-                WidgetResult::Continue
-            }
-            KeyCode::Right => {
-                if self.cursor_pos < self.buffer.len() {
-                    self.cursor_pos += 1;
-                }
-                WidgetResult::Continue
-            }
             _ => WidgetResult::Continue,
         }
     }
@@ -210,3 +232,60 @@ impl Widget for NumberInput {
         self.cursor_pos = self.buffer.len();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn press(input: &mut NumberInput, code: KeyCode) -> WidgetResult {
+        input.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_arrow_keys_step_within_limits() {
+        let mut input = NumberInput::new("Size", 9, Some(8), Some(10));
+        input.activate();
+
+        assert!(matches!(press(&mut input, KeyCode::Up), WidgetResult::Changed(v) if v == 10));
+        assert!(matches!(
+            press(&mut input, KeyCode::Up),
+            WidgetResult::Continue
+        ));
+        assert!(matches!(press(&mut input, KeyCode::Down), WidgetResult::Changed(v) if v == 9));
+        assert!(matches!(press(&mut input, KeyCode::Down), WidgetResult::Changed(v) if v == 8));
+        assert!(matches!(
+            press(&mut input, KeyCode::Down),
+            WidgetResult::Continue
+        ));
+        assert_eq!(input.get_value(), 8);
+    }
+
+    #[test]
+    fn test_left_right_move_the_cursor_for_typing() {
+        let mut input = NumberInput::new("Size", 12, None, None);
+        input.activate();
+
+        assert!(matches!(
+            press(&mut input, KeyCode::Left),
+            WidgetResult::Continue
+        ));
+        assert!(matches!(
+            press(&mut input, KeyCode::Char('5')),
+            WidgetResult::Changed(v) if v == 152
+        ));
+        assert!(matches!(
+            press(&mut input, KeyCode::Right),
+            WidgetResult::Continue
+        ));
+        assert!(matches!(
+            press(&mut input, KeyCode::Char('0')),
+            WidgetResult::Changed(v) if v == 1520
+        ));
+        assert!(matches!(press(&mut input, KeyCode::Up), WidgetResult::Changed(v) if v == 1521));
+        assert!(matches!(
+            press(&mut input, KeyCode::Enter),
+            WidgetResult::Confirmed(v) if v == 1521
+        ));
+    }
+}
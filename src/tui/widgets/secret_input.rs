@@ -0,0 +1,271 @@
+use super::{EditAction, Widget, WidgetResult, WidgetState};
+use crate::tui::theme::Theme;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+
+const MASK_CHAR: char = '•';
+
+/// Masked text input for secrets; Ctrl-R toggles revealing the value
+pub struct SecretInput {
+    buffer: String,
+    cursor_pos: usize,
+    state: WidgetState,
+    label: String,
+    revealed: bool,
+    stored: Option<StoredSecret>,
+    error: Option<String>,
+}
+
+/// A secret kept in a storage backend that hasn't been loaded yet
+struct StoredSecret {
+    backend: String,
+    load: Box<dyn Fn() -> Result<String>>,
+}
+
+impl SecretInput {
+    pub fn new(label: impl Into<String>, initial_value: impl Into<String>) -> Self {
+        let buffer = initial_value.into();
+        let cursor_pos = buffer.chars().count();
+
+        Self {
+            buffer,
+            cursor_pos,
+            state: WidgetState::Normal,
+            label: label.into(),
+            revealed: false,
+            stored: None,
+            error: None,
+        }
+    }
+
+    /// The current secret lives in `backend` and is only loaded once it is
+    /// revealed or edited; confirming without either keeps it unchanged
+    pub fn with_stored(
+        mut self,
+        backend: impl Into<String>,
+        load: impl Fn() -> Result<String> + 'static,
+    ) -> Self {
+        self.stored = Some(StoredSecret {
+            backend: backend.into(),
+            load: Box::new(load),
+        });
+        self
+    }
+
+    /// Load a stored secret into the buffer; false if that failed
+    fn load_stored(&mut self) -> bool {
+        let Some(stored) = self.stored.take() else {
+            return true;
+        };
+        match (stored.load)() {
+            Ok(secret) => {
+                self.buffer = secret;
+                self.cursor_pos = self.char_count();
+                self.error = None;
+                true
+            }
+            Err(e) => {
+                self.error = Some(e.to_string());
+                self.stored = Some(stored);
+                false
+            }
+        }
+    }
+
+    fn toggle_reveal(&mut self) {
+        if self.load_stored() {
+            self.revealed = !self.revealed;
+        }
+    }
+
+    /// Stop editing and forget the plaintext
+    fn finish(&mut self) {
+        self.state = WidgetState::Normal;
+        self.revealed = false;
+        self.buffer.clear();
+        self.cursor_pos = 0;
+    }
+
+    pub fn is_revealed(&self) -> bool {
+        self.revealed
+    }
+
+    fn byte_index(&self, char_pos: usize) -> usize {
+        self.buffer
+            .char_indices()
+            .nth(char_pos)
+            .map(|(i, _)| i)
+            .unwrap_or(self.buffer.len())
+    }
+
+    fn char_count(&self) -> usize {
+        self.buffer.chars().count()
+    }
+
+    fn insert_char(&mut self, c: char) {
+        let idx = self.byte_index(self.cursor_pos);
+        self.buffer.insert(idx, c);
+        self.cursor_pos += 1;
+    }
+
+    fn delete_char(&mut self) {
+        if self.cursor_pos > 0 {
+            let idx = self.byte_index(self.cursor_pos - 1);
+            self.buffer.remove(idx);
+            self.cursor_pos -= 1;
+        }
+    }
+
+    fn delete_forward(&mut self) {
+        if self.cursor_pos < self.char_count() {
+            let idx = self.byte_index(self.cursor_pos);
+            self.buffer.remove(idx);
+        }
+    }
+
+    fn get_display_text(&self) -> String {
+        if let Some(stored) = &self.stored {
+            return format!("•••••••• ({})", stored.backend);
+        }
+
+        let mut chars: Vec<char> = if self.revealed {
+            self.buffer.chars().collect()
+        } else {
+            std::iter::repeat_n(MASK_CHAR, self.char_count()).collect()
+        };
+
+        if self.state == WidgetState::Editing {
+            chars.insert(self.cursor_pos, '█');
+        }
+        chars.into_iter().collect()
+    }
+}
+
+impl Widget for SecretInput {
+    fn render(&self, frame: &mut Frame, area: Rect, _bounds: Rect, focused: bool, theme: &Theme) {
+        let style = if self.state == WidgetState::Editing {
+            Style::default()
+                .fg(theme.popup_fg)
+                .bg(theme.popup_bg)
+                .add_modifier(Modifier::BOLD)
+        } else if focused {
+            Style::default().fg(theme.focused)
+        } else {
+            Style::default().fg(theme.text)
+        };
+
+        let hint = match &self.error {
+            Some(error) => Span::styled(format!(" ({})", error), Style::default().fg(theme.error)),
+            None if self.revealed => {
+                Span::styled(" (Ctrl-R hide)", Style::default().fg(theme.text_dim))
+            }
+            None => Span::styled(" (Ctrl-R reveal)", Style::default().fg(theme.text_dim)),
+        };
+
+        let content = Line::from(vec![
+            Span::styled(
+                format!("{}: ", self.label),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(self.get_display_text(), style),
+            hint,
+        ]);
+
+        let block = Block::default()
+            .borders(if focused { Borders::ALL } else { Borders::NONE })
+            .border_style(if self.state == WidgetState::Editing {
+                Style::default().fg(theme.editing)
+            } else {
+                Style::default()
+            })
+            .style(if self.state == WidgetState::Editing {
+                Style::default().bg(theme.popup_bg)
+            } else {
+                Style::default()
+            });
+
+        let paragraph = Paragraph::new(content).block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    // Secrets are only reported on confirm, never as live changes, so a
+    // storage backend isn't invoked on every keystroke
+    fn handle_action(&mut self, action: EditAction) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        match action {
+            EditAction::Confirm => {
+                let unchanged = self.stored.is_some();
+                let value = self.get_value();
+                self.finish();
+                return if unchanged {
+                    WidgetResult::Cancelled
+                } else {
+                    WidgetResult::Confirmed(value)
+                };
+            }
+            EditAction::Cancel => {
+                self.finish();
+                return WidgetResult::Cancelled;
+            }
+            EditAction::Left => self.cursor_pos = self.cursor_pos.saturating_sub(1),
+            EditAction::Right => self.cursor_pos = (self.cursor_pos + 1).min(self.char_count()),
+            EditAction::Home => self.cursor_pos = 0,
+            EditAction::End => self.cursor_pos = self.char_count(),
+            _ => {}
+        }
+
+        WidgetResult::Continue
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        match key.code {
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.toggle_reveal();
+            }
+            KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Delete if !self.load_stored() => {}
+            KeyCode::Char(c) => self.insert_char(c),
+            KeyCode::Backspace => self.delete_char(),
+            KeyCode::Delete => self.delete_forward(),
+            _ => {}
+        }
+
+        WidgetResult::Continue
+    }
+
+    fn get_value(&self) -> Value {
+        Value::String(self.buffer.clone())
+    }
+
+    fn set_value(&mut self, value: Value) {
+        if let Some(s) = value.as_str() {
+            self.buffer = s.to_string();
+            self.cursor_pos = self.char_count();
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state = WidgetState::Normal;
+        self.revealed = false;
+        self.cursor_pos = self.char_count();
+    }
+
+    fn activate(&mut self) {
+        self.state = WidgetState::Editing;
+        self.cursor_pos = self.char_count();
+    }
+}
@@ -0,0 +1,333 @@
+use super::{EditAction, Widget, WidgetResult, WidgetState};
+use crate::tui::theme::Theme;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use serde_json::Value;
+
+/// Horizontal slider for a bounded number
+///
+/// Left/Right move by `step`, PageUp/PageDown by ten steps and Home/End jump
+/// to the limits. Values stay on the `min + n * step` grid, except that `max`
+/// is always reachable. Every move is reported as a live change.
+pub struct Slider {
+    value: f64,
+    min: f64,
+    max: f64,
+    step: f64,
+    integer: bool,
+    state: WidgetState,
+    label: String,
+}
+
+impl Slider {
+    pub fn new(
+        label: impl Into<String>,
+        initial_value: f64,
+        min: f64,
+        max: f64,
+        step: f64,
+    ) -> Self {
+        let step = if step > 0.0 { step } else { 1.0 };
+        Self {
+            value: initial_value.clamp(min, max),
+            min,
+            max,
+            step,
+            integer: false,
+            state: WidgetState::Normal,
+            label: label.into(),
+        }
+    }
+
+    /// Report values as integers, for `Number` fields
+    pub fn integer(mut self) -> Self {
+        self.integer = true;
+        self
+    }
+
+    /// Move `steps` grid points up or down from the current value
+    fn move_by(&mut self, steps: f64) -> WidgetResult {
+        // Off-grid values (e.g. `max`) count as the grid point behind them
+        let position = (self.value - self.min) / self.step;
+        let nearest = position.round();
+        let position = if (position - nearest).abs() < 1e-9 {
+            nearest
+        } else if steps > 0.0 {
+            position.floor()
+        } else {
+            position.ceil()
+        };
+        self.move_to(self.min + (position + steps) * self.step)
+    }
+
+    fn move_to(&mut self, value: f64) -> WidgetResult {
+        let value = snap_to_step(value, self.min, self.max, self.step);
+        if value == self.value {
+            return WidgetResult::Continue;
+        }
+        self.value = value;
+        WidgetResult::Changed(self.get_value())
+    }
+
+    fn display_value(&self) -> String {
+        if self.integer {
+            (self.value.round() as i64).to_string()
+        } else {
+            format_stepped(self.value, self.step, 0)
+        }
+    }
+
+    fn bar(&self, width: usize) -> String {
+        let range = self.max - self.min;
+        let ratio = if range > 0.0 {
+            (self.value - self.min) / range
+        } else {
+            1.0
+        };
+        let filled = ((ratio * width as f64).round() as usize).min(width);
+        format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+    }
+}
+
+/// Move `value` by `steps` increments of `step`, clamped to the optional limits
+pub(super) fn step_value(
+    value: f64,
+    steps: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+    step: f64,
+) -> f64 {
+    let mut next = value + steps * step;
+    if let Some(min) = min {
+        next = next.max(min);
+    }
+    if let Some(max) = max {
+        next = next.min(max);
+    }
+    next
+}
+
+/// Format a float with as many decimals as `step` has (but at least
+/// `min_decimals`), hiding rounding noise
+pub(super) fn format_stepped(value: f64, step: f64, min_decimals: usize) -> String {
+    let decimals = decimals_of(&step.to_string()).max(min_decimals);
+    format!("{:.*}", decimals, value)
+}
+
+/// Number of fraction digits written in a decimal string
+pub(super) fn decimals_of(s: &str) -> usize {
+    s.split_once('.')
+        .map(|(_, frac)| frac.len().min(10))
+        .unwrap_or(0)
+}
+
+/// Nearest grid point to `value`, or `max` itself at or past the top
+fn snap_to_step(value: f64, min: f64, max: f64, step: f64) -> f64 {
+    if value >= max {
+        return max;
+    }
+    let snapped = min + ((value - min) / step).round() * step;
+    snapped.clamp(min, max)
+}
+
+impl Widget for Slider {
+    fn render(&self, frame: &mut Frame, area: Rect, _bounds: Rect, focused: bool, theme: &Theme) {
+        let style = if self.state == WidgetState::Editing {
+            Style::default()
+                .fg(theme.popup_fg)
+                .bg(theme.popup_bg)
+                .add_modifier(Modifier::BOLD)
+        } else if focused {
+            Style::default().fg(theme.focused)
+        } else {
+            Style::default().fg(theme.text)
+        };
+
+        let label = format!("{}: ", self.label);
+        let value = format!(" {}", self.display_value());
+        let inner_width = area.width.saturating_sub(2) as usize;
+        let bar_width = inner_width
+            .saturating_sub(label.chars().count() + value.chars().count() + 2)
+            .clamp(5, 40);
+
+        let content = Line::from(vec![
+            Span::styled(label, Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(format!("[{}]", self.bar(bar_width)), style),
+            Span::styled(value, style),
+        ]);
+
+        let block = Block::default()
+            .borders(if focused { Borders::ALL } else { Borders::NONE })
+            .border_style(if self.state == WidgetState::Editing {
+                Style::default().fg(theme.editing)
+            } else {
+                Style::default()
+            })
+            .style(if self.state == WidgetState::Editing {
+                Style::default().bg(theme.popup_bg)
+            } else {
+                Style::default()
+            });
+
+        frame.render_widget(Paragraph::new(content).block(block), area);
+    }
+
+    fn handle_action(&mut self, action: EditAction) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        match action {
+            EditAction::Confirm => {
+                self.state = WidgetState::Normal;
+                WidgetResult::Confirmed(self.get_value())
+            }
+            EditAction::Cancel => {
+                self.state = WidgetState::Normal;
+                WidgetResult::Cancelled
+            }
+            EditAction::Left => self.move_by(-1.0),
+            EditAction::Right => self.move_by(1.0),
+            EditAction::PageDown => self.move_by(-10.0),
+            EditAction::PageUp => self.move_by(10.0),
+            EditAction::Home => self.move_to(self.min),
+            EditAction::End => self.move_to(self.max),
+            EditAction::Up | EditAction::Down => WidgetResult::Continue,
+        }
+    }
+
+    fn handle_input(&mut self, _key: KeyEvent) -> WidgetResult {
+        WidgetResult::Continue
+    }
+
+    fn get_value(&self) -> Value {
+        if self.integer {
+            Value::from(self.value.round() as i64)
+        } else {
+            self.display_value()
+                .parse::<f64>()
+                .map(Value::from)
+                .unwrap_or_else(|_| Value::from(self.value))
+        }
+    }
+
+    fn set_value(&mut self, value: Value) {
+        if let Some(n) = value.as_f64() {
+            self.value = n.clamp(self.min, self.max);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state = WidgetState::Normal;
+    }
+
+    fn activate(&mut self) {
+        self.state = WidgetState::Editing;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    fn press(widget: &mut impl Widget, code: KeyCode) -> WidgetResult {
+        widget.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn changed(result: WidgetResult) -> Option<Value> {
+        match result {
+            WidgetResult::Changed(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_slider_keys_report_live_changes() {
+        let mut slider = Slider::new("Volume", 50.0, 0.0, 100.0, 5.0).integer();
+        slider.activate();
+
+        assert_eq!(changed(press(&mut slider, KeyCode::Right)), Some(55.into()));
+        assert_eq!(changed(press(&mut slider, KeyCode::Left)), Some(50.into()));
+        assert_eq!(
+            changed(press(&mut slider, KeyCode::PageUp)),
+            Some(100.into())
+        );
+        assert!(matches!(
+            press(&mut slider, KeyCode::Right),
+            WidgetResult::Continue
+        ));
+        assert_eq!(
+            changed(press(&mut slider, KeyCode::PageDown)),
+            Some(50.into())
+        );
+        assert_eq!(changed(press(&mut slider, KeyCode::Home)), Some(0.into()));
+        assert!(matches!(
+            press(&mut slider, KeyCode::Left),
+            WidgetResult::Continue
+        ));
+        assert_eq!(changed(press(&mut slider, KeyCode::End)), Some(100.into()));
+        assert!(matches!(
+            press(&mut slider, KeyCode::Enter),
+            WidgetResult::Confirmed(v) if v == 100
+        ));
+        // Not editing any more
+        assert!(matches!(
+            press(&mut slider, KeyCode::Left),
+            WidgetResult::Continue
+        ));
+    }
+
+    #[test]
+    fn test_slider_reaches_max_off_the_step_grid() {
+        let mut slider = Slider::new("Gap", 6.0, 0.0, 10.0, 3.0).integer();
+        slider.activate();
+
+        assert_eq!(changed(press(&mut slider, KeyCode::Right)), Some(9.into()));
+        assert_eq!(changed(press(&mut slider, KeyCode::Right)), Some(10.into()));
+        assert!(matches!(
+            press(&mut slider, KeyCode::Right),
+            WidgetResult::Continue
+        ));
+        assert_eq!(changed(press(&mut slider, KeyCode::Left)), Some(9.into()));
+        assert_eq!(
+            changed(press(&mut slider, KeyCode::PageUp)),
+            Some(10.into())
+        );
+        assert_eq!(
+            changed(press(&mut slider, KeyCode::PageDown)),
+            Some(0.into())
+        );
+        assert_eq!(changed(press(&mut slider, KeyCode::End)), Some(10.into()));
+    }
+
+    #[test]
+    fn test_float_slider_steps_without_rounding_noise() {
+        let mut slider = Slider::new("Opacity", 0.8, 0.0, 1.0, 0.1);
+        slider.activate();
+
+        assert_eq!(
+            changed(press(&mut slider, KeyCode::Right)),
+            Some(0.9.into())
+        );
+        assert_eq!(
+            changed(press(&mut slider, KeyCode::Right)),
+            Some(1.0.into())
+        );
+        assert!(matches!(
+            press(&mut slider, KeyCode::Right),
+            WidgetResult::Continue
+        ));
+        assert_eq!(changed(press(&mut slider, KeyCode::Left)), Some(0.9.into()));
+        assert!(matches!(
+            press(&mut slider, KeyCode::Esc),
+            WidgetResult::Cancelled
+        ));
+    }
+}
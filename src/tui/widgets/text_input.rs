@@ -1,4 +1,4 @@
-use super::{Widget, WidgetResult, WidgetState};
+use super::{EditAction, Widget, WidgetResult, WidgetState};
 use crate::tui::theme::Theme;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
@@ -72,7 +72,7 @@ impl TextInput {
 }
 
 impl Widget for TextInput {
-    fn render(&self, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+    fn render(&self, frame: &mut Frame, area: Rect, _bounds: Rect, focused: bool, theme: &Theme) {
         let style = if self.state == WidgetState::Editing {
             Style::default()
                 .fg(theme.popup_fg)
@@ -110,20 +110,35 @@ impl Widget for TextInput {
         frame.render_widget(paragraph, area);
     }
 
-    fn handle_key(&mut self, key: KeyEvent) -> WidgetResult {
+    fn handle_action(&mut self, action: EditAction) -> WidgetResult {
         if self.state != WidgetState::Editing {
             return WidgetResult::Continue;
         }
 
-        match key.code {
-            KeyCode::Enter => {
+        match action {
+            EditAction::Confirm => {
                 self.state = WidgetState::Normal;
-                WidgetResult::Confirmed(self.get_value())
+                return WidgetResult::Confirmed(self.get_value());
             }
-            KeyCode::Esc => {
+            EditAction::Cancel => {
                 self.state = WidgetState::Normal;
-                WidgetResult::Cancelled
+                return WidgetResult::Cancelled;
             }
+            EditAction::Left => self.move_cursor_left(),
+            EditAction::Right => self.move_cursor_right(),
+            EditAction::Home => self.cursor_pos = 0,
+            EditAction::End => self.cursor_pos = self.buffer.len(),
+            _ => {}
+        }
+        WidgetResult::Continue
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> WidgetResult {
+        if self.state != WidgetState::Editing {
+            return WidgetResult::Continue;
+        }
+
+        match key.code {
             KeyCode::Char(c) => {
                 self.insert_char(c);
                 WidgetResult::Changed(self.get_value())
@@ -136,22 +151,6 @@ impl Widget for TextInput {
                 self.delete_forward();
                 WidgetResult::Changed(self.get_value())
             }
-            KeyCode::Left => {
-                self.move_cursor_left();
-                WidgetResult::Continue
-            }
-            KeyCode::Right => {
-                self.move_cursor_right();
-                WidgetResult::Continue
-            }
-            KeyCode::Home => {
-                self.cursor_pos = 0;
-                WidgetResult::Continue
-            }
-            KeyCode::End => {
-                self.cursor_pos = self.buffer.len();
-                WidgetResult::Continue
-            }
             _ => WidgetResult::Continue,
         }
     }
@@ -1,4 +1,4 @@
-use super::{Widget, WidgetResult};
+use super::{EditAction, Widget, WidgetResult};
 use crate::tui::theme::Theme;
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
@@ -29,7 +29,7 @@ impl Toggle {
 }
 
 impl Widget for Toggle {
-    fn render(&self, frame: &mut Frame, area: Rect, focused: bool, theme: &Theme) {
+    fn render(&self, frame: &mut Frame, area: Rect, _bounds: Rect, focused: bool, theme: &Theme) {
         let style = if focused {
             Style::default()
                 .fg(theme.focused)
@@ -64,9 +64,19 @@ impl Widget for Toggle {
         frame.render_widget(paragraph, area);
     }
 
-    fn handle_key(&mut self, key: KeyEvent) -> WidgetResult {
+    fn handle_action(&mut self, action: EditAction) -> WidgetResult {
+        match action {
+            EditAction::Confirm => {
+                self.toggle();
+                WidgetResult::Confirmed(self.get_value())
+            }
+            _ => WidgetResult::Continue,
+        }
+    }
+
+    fn handle_input(&mut self, key: KeyEvent) -> WidgetResult {
         match key.code {
-            KeyCode::Enter | KeyCode::Char(' ') => {
+            KeyCode::Char(' ') => {
                 self.toggle();
                 WidgetResult::Confirmed(self.get_value())
             }
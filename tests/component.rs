@@ -0,0 +1,883 @@
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::{backend::TestBackend, layout::Rect, Terminal};
+use schema_tui::options::OptionsFuture;
+use schema_tui::{
+    AfterChange, BeforeChange, ChangeEvent, ChangeSource, ConfigSchema, HostCommand, Keymap,
+    Outcome, SaveMode, SchemaParser, SchemaTUI, SchemaTUIBuilder,
+};
+use schema_tui::{AsyncOptionProvider, CancellationToken, ProviderContext};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+fn editor() -> SchemaTUI {
+    editor_with_hook(None)
+}
+
+fn editor_with_hook(on_change: Option<&str>) -> SchemaTUI {
+    build(test_schema(on_change))
+}
+
+/// The test schema, with `on_change` set on the general section
+fn test_schema(on_change: Option<&str>) -> ConfigSchema {
+    SchemaParser::from_string(
+        &r#"{
+            "title": "Test",
+            "version": "1.0",
+            "sections": [
+                {
+                    "id": "general",
+                    "title": "General",
+                    "on_change": ON_CHANGE,
+                    "fields": [
+                        {"id": "enabled", "label": "Enabled", "description": "On or off", "type": "boolean", "default": false, "ui_widget": "toggle"},
+                        {"id": "name", "label": "Name", "description": "Your name", "type": "string"}
+                    ]
+                },
+                {
+                    "id": "display",
+                    "title": "Display",
+                    "fields": [
+                        {"id": "size", "label": "Size", "description": "Font size", "type": "number", "default": 12}
+                    ]
+                }
+            ]
+        }"#
+        .replace("ON_CHANGE", &serde_json::to_string(&on_change).unwrap()),
+    )
+    .unwrap()
+}
+
+fn build(schema: ConfigSchema) -> SchemaTUI {
+    SchemaTUIBuilder::new()
+        .schema(schema)
+        .initial_values(HashMap::new())
+        .build()
+        .unwrap()
+}
+
+fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn click(column: u16, row: u16) -> Event {
+    Event::Mouse(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    })
+}
+
+#[test]
+fn test_component_reports_event_outcomes() {
+    let mut tui = editor();
+
+    assert_eq!(
+        tui.handle_event(&key(KeyCode::Char('z'))).unwrap(),
+        Outcome::Ignored
+    );
+    assert_eq!(
+        tui.handle_event(&key(KeyCode::Enter)).unwrap(),
+        Outcome::Changed(vec!["general.enabled".to_string()])
+    );
+    assert_eq!(
+        tui.get_value("general.enabled"),
+        Some(&serde_json::json!(true))
+    );
+
+    assert_eq!(
+        tui.handle_event(&key(KeyCode::Char('j'))).unwrap(),
+        Outcome::Consumed
+    );
+    assert_eq!(tui.current_field_key().as_deref(), Some("general.name"));
+
+    assert_eq!(
+        tui.handle_event(&key(KeyCode::Char('q'))).unwrap(),
+        Outcome::QuitRequested
+    );
+    // Quitting is up to the host; the editor keeps working
+    assert_eq!(
+        tui.handle_event(&key(KeyCode::Char('k'))).unwrap(),
+        Outcome::Consumed
+    );
+}
+
+#[test]
+fn test_ctrl_c_asks_before_discarding_unsaved_changes() {
+    let mut tui = SchemaTUIBuilder::new()
+        .schema(test_schema(None))
+        .save_mode(SaveMode::Explicit)
+        .build()
+        .unwrap();
+    let ctrl_c = Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert_eq!(tui.handle_event(&ctrl_c).unwrap(), Outcome::Consumed);
+    assert!(tui.status_message().unwrap().contains("Unsaved changes"));
+    assert_eq!(tui.handle_event(&ctrl_c).unwrap(), Outcome::QuitRequested);
+}
+
+#[test]
+fn test_component_renders_into_a_pane() {
+    let mut tui = editor();
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    // The right half of the screen, as a host with a sidebar would give it
+    let pane = Rect::new(40, 0, 60, 30);
+    terminal.draw(|frame| tui.render(frame, pane)).unwrap();
+
+    let buffer = terminal.backend().buffer().clone();
+    let left: String = (0..40)
+        .map(|x| buffer[(x, 4)].symbol().to_string())
+        .collect();
+    assert!(left.trim().is_empty());
+
+    // Clicks outside the pane belong to the host
+    assert_eq!(tui.handle_event(&click(5, 4)).unwrap(), Outcome::Ignored);
+
+    // The tab row is the second block: border at y=3, titles at y=4
+    let column = (40..100)
+        .find(|&x| {
+            (x..100)
+                .map(|x| buffer[(x, 4)].symbol())
+                .collect::<String>()
+                .starts_with("Display")
+        })
+        .unwrap();
+    assert_eq!(
+        tui.handle_event(&click(column, 4)).unwrap(),
+        Outcome::Consumed
+    );
+    assert_eq!(tui.current_section_id(), Some("display"));
+}
+
+#[test]
+fn test_change_stream_and_host_commands() {
+    let mut tui = editor();
+    let mut changes = tui.subscribe();
+    let commands = tui.command_sender();
+
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert_eq!(
+        changes.try_recv().unwrap(),
+        ChangeEvent {
+            key: "general.enabled".to_string(),
+            old: Some(json!(false)),
+            new: Some(json!(true)),
+            source: ChangeSource::User,
+        }
+    );
+
+    commands
+        .send(HostCommand::SetValue {
+            key: "display.size".to_string(),
+            value: json!(14),
+        })
+        .unwrap();
+    commands
+        .send(HostCommand::SetValue {
+            key: "display.missing".to_string(),
+            value: json!(1),
+        })
+        .unwrap();
+    assert_eq!(
+        tui.tick(),
+        Outcome::Changed(vec!["display.size".to_string()])
+    );
+    let event = changes.try_recv().unwrap();
+    assert_eq!(event.source, ChangeSource::Host);
+    assert_eq!((event.old, event.new), (Some(json!(12)), Some(json!(14))));
+    assert!(changes.try_recv().is_err());
+    assert_eq!(
+        tui.status_message(),
+        Some("display.missing: unknown setting")
+    );
+
+    commands
+        .send(HostCommand::Message("Synced".to_string()))
+        .unwrap();
+    commands.send(HostCommand::Quit).unwrap();
+    assert_eq!(tui.tick(), Outcome::QuitRequested);
+    assert_eq!(tui.status_message(), Some("Synced"));
+}
+
+fn set(key: &str, value: serde_json::Value) -> HostCommand {
+    HostCommand::SetValue {
+        key: key.to_string(),
+        value,
+    }
+}
+
+#[test]
+fn test_change_hooks_and_on_change_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("changes.log");
+    let mut tui = editor_with_hook(Some(&format!(
+        "echo \"$CHANGED_KEY\" >> '{}'",
+        log.display()
+    )));
+    tui.before_change(|key, value| match (key, value.as_str()) {
+        ("general.name", Some("root")) => BeforeChange::Reject("reserved".to_string()),
+        ("display.size", _) => BeforeChange::Replace(json!(value.as_i64().unwrap() * 2)),
+        _ => BeforeChange::Accept,
+    });
+    tui.after_change(|key, _| match key {
+        "display.size" => AfterChange::Message("size applied".to_string()),
+        _ => AfterChange::Ok,
+    });
+    let commands = tui.command_sender();
+
+    commands.send(set("general.name", json!("root"))).unwrap();
+    tui.tick();
+    assert_eq!(tui.get_value("general.name"), None);
+    assert_eq!(
+        tui.status_message(),
+        Some("general.name: rejected: reserved")
+    );
+
+    commands.send(set("display.size", json!(7))).unwrap();
+    tui.tick();
+    assert_eq!(tui.get_value("display.size"), Some(&json!(14)));
+    assert_eq!(tui.status_message(), Some("size applied"));
+    assert!(!log.exists());
+
+    // One run of the section command for both changes
+    commands.send(set("general.enabled", json!(true))).unwrap();
+    commands.send(set("general.name", json!("ada"))).unwrap();
+    tui.tick();
+    assert_eq!(std::fs::read_to_string(&log).unwrap(), "general.enabled\n");
+}
+
+#[test]
+fn test_live_apply_reverts_unless_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("applied.log");
+    let apply = format!("echo \"$CURRENT_VALUE\" >> '{}'", log.display());
+    let schema = json!({
+        "title": "Test",
+        "version": "1.0",
+        "sections": [{
+            "id": "display",
+            "title": "Display",
+            "apply_command": apply,
+            "confirm_timeout": 60,
+            "fields": [
+                {"id": "gaps", "label": "Gaps", "description": "Gaps", "type": "number", "default": 4, "confirm_timeout": 1},
+                {"id": "opacity", "label": "Opacity", "description": "Opacity", "type": "float", "default": 1.0, "confirm_timeout": 0},
+                {"id": "theme", "label": "Theme", "description": "Theme", "type": "string", "default": "dark"},
+                {"id": "font", "label": "Font", "description": "Font", "type": "string", "default": "mono", "apply_command": "exit 3"}
+            ]
+        }]
+    });
+    let mut tui = build(SchemaParser::from_string(&schema.to_string()).unwrap());
+    let commands = tui.command_sender();
+    let applied = || std::fs::read_to_string(&log).unwrap_or_default();
+
+    // Not confirmed in time: the old value is restored and applied again
+    commands.send(set("display.gaps", json!(10))).unwrap();
+    tui.tick();
+    assert!(tui.has_dialog());
+    assert_eq!(applied(), "10\n");
+    std::thread::sleep(Duration::from_millis(1100));
+    tui.tick();
+    assert!(!tui.has_dialog());
+    assert_eq!(tui.get_value("display.gaps"), Some(&json!(4)));
+    assert_eq!(applied(), "10\n4\n");
+
+    // Kept before the deadline
+    commands.send(set("display.theme", json!("light"))).unwrap();
+    tui.tick();
+    assert!(tui.has_dialog());
+    tui.handle_event(&key(KeyCode::Char('y'))).unwrap();
+    tui.tick();
+    assert!(!tui.has_dialog());
+    assert_eq!(tui.get_value("display.theme"), Some(&json!("light")));
+    assert_eq!(applied(), "10\n4\nlight\n");
+
+    // A field's 0 skips the section's countdown
+    commands.send(set("display.opacity", json!(0.5))).unwrap();
+    tui.tick();
+    assert!(!tui.has_dialog());
+    assert_eq!(tui.get_value("display.opacity"), Some(&json!(0.5)));
+    assert_eq!(applied(), "10\n4\nlight\n0.5\n");
+
+    // A failing apply command reverts at once
+    commands.send(set("display.font", json!("sans"))).unwrap();
+    tui.tick();
+    assert!(!tui.has_dialog());
+    assert_eq!(tui.get_value("display.font"), Some(&json!("mono")));
+    assert!(tui
+        .status_message()
+        .unwrap()
+        .contains("reverted display.font"));
+}
+
+#[test]
+fn test_live_apply_waits_for_the_save_in_explicit_mode() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("applied.log");
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "").unwrap();
+    let schema = json!({
+        "title": "Test",
+        "version": "1.0",
+        "sections": [{
+            "id": "display",
+            "title": "Display",
+            "apply_command": format!("echo \"$CURRENT_VALUE\" >> '{}'", log.display()),
+            "confirm_timeout": 60,
+            "fields": [
+                {"id": "gaps", "label": "Gaps", "description": "Gaps", "type": "number", "default": 4}
+            ]
+        }]
+    });
+    let mut tui = SchemaTUIBuilder::new()
+        .schema(SchemaParser::from_string(&schema.to_string()).unwrap())
+        .config_file(&path)
+        .unwrap()
+        .save_mode(SaveMode::Explicit)
+        .build()
+        .unwrap();
+    let applied = || std::fs::read_to_string(&log).unwrap_or_default();
+    let ctrl_s = Event::Key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+
+    tui.command_sender()
+        .send(set("display.gaps", json!(10)))
+        .unwrap();
+    tui.tick();
+    assert!(!tui.has_dialog());
+    assert_eq!(applied(), "");
+
+    // Saving from the review screen applies and starts the countdown
+    tui.handle_event(&ctrl_s).unwrap();
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    tui.tick();
+    assert!(tui.has_dialog());
+    assert_eq!(applied(), "10\n");
+    tui.handle_event(&key(KeyCode::Char('y'))).unwrap();
+    assert!(!tui.has_dialog());
+    assert_eq!(tui.get_value("display.gaps"), Some(&json!(10)));
+}
+
+/// Models for the chosen engine; the "slow" engine never answers, and the
+/// token of every resolution is kept so tests can see it cancelled
+struct ModelProvider {
+    tokens: Arc<Mutex<Vec<CancellationToken>>>,
+}
+
+impl AsyncOptionProvider for ModelProvider {
+    fn options<'a>(&'a self, ctx: ProviderContext<'a>) -> OptionsFuture<'a> {
+        Box::pin(async move {
+            self.tokens.lock().unwrap().push(ctx.cancel.clone());
+            let engine = ctx.value_str("voice.engine").unwrap_or_default();
+            if engine == "slow" {
+                std::future::pending::<()>().await;
+            }
+            Ok(vec![
+                format!("{}-small", engine).into(),
+                format!("{}-large", engine).into(),
+            ])
+        })
+    }
+
+    fn depends_on(&self) -> Vec<String> {
+        vec!["voice.engine".to_string()]
+    }
+}
+
+#[test]
+fn test_dependency_change_cancels_provider_in_flight() {
+    let schema = json!({
+        "title": "Test",
+        "version": "1.0",
+        "sections": [{
+            "id": "voice",
+            "title": "Voice",
+            "fields": [
+                {"id": "engine", "label": "Engine", "description": "Engine", "type": "string", "default": "fast"},
+                {"id": "model", "label": "Model", "description": "Model", "type": "enum", "default": "fast-small",
+                 "options_source": {"type": "provider", "provider": "models"}, "on_dependency_change": "reset"}
+            ]
+        }]
+    });
+    let tokens = Arc::new(Mutex::new(Vec::new()));
+    let provider = ModelProvider {
+        tokens: Arc::clone(&tokens),
+    };
+    let mut tui = SchemaTUIBuilder::new()
+        .schema(SchemaParser::from_string(&schema.to_string()).unwrap())
+        .initial_values(HashMap::new())
+        .register_async_option_provider("models", Box::new(provider))
+        .build()
+        .unwrap();
+    let commands = tui.command_sender();
+    commands.send(set("voice.engine", json!("slow"))).unwrap();
+    tui.tick();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while tokens.lock().unwrap().is_empty() {
+        assert!(Instant::now() < deadline, "provider was not called");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    let slow = tokens.lock().unwrap()[0].clone();
+    assert!(!slow.is_cancelled());
+
+    // Changing the engine again makes the running check pointless
+    commands.send(set("voice.engine", json!("turbo"))).unwrap();
+    tui.tick();
+    assert!(slow.is_cancelled());
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while tui.get_value("voice.model") != Some(&json!("turbo-small")) {
+        assert!(Instant::now() < deadline, "model was not reset");
+        std::thread::sleep(Duration::from_millis(5));
+        tui.tick();
+    }
+    assert_eq!(
+        tui.status_message(),
+        Some("voice.model reset from 'fast-small' to 'turbo-small'")
+    );
+}
+
+#[test]
+fn test_dependents_are_rechecked_only_for_confirmed_values() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("checks.log");
+    let command = format!(
+        "echo '${{voice.engine}}' >> '{}'; echo '[\"${{voice.engine}}-small\"]'",
+        log.display()
+    );
+    let schema = json!({
+        "title": "Test",
+        "version": "1.0",
+        "sections": [{
+            "id": "voice",
+            "title": "Voice",
+            "fields": [
+                {"id": "engine", "label": "Engine", "description": "Engine", "type": "string", "default": "fast"},
+                {"id": "model", "label": "Model", "description": "Model", "type": "enum", "default": "fast-small",
+                 "options_source": {"type": "script", "command": command, "depends_on": ["voice.engine"]},
+                 "on_dependency_change": "reset"}
+            ]
+        }]
+    });
+    let mut tui = build(SchemaParser::from_string(&schema.to_string()).unwrap());
+    let checks = || std::fs::read_to_string(&log).unwrap_or_default();
+
+    // Typing previews the engine without running the script; cancelling
+    // leaves the model alone
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    for c in "abc".chars() {
+        tui.handle_event(&key(KeyCode::Char(c))).unwrap();
+    }
+    assert_eq!(tui.get_value("voice.engine"), Some(&json!("fastabc")));
+    std::thread::sleep(Duration::from_millis(200));
+    tui.tick();
+    tui.handle_event(&key(KeyCode::Esc)).unwrap();
+    assert_eq!(tui.get_value("voice.engine"), Some(&json!("fast")));
+    assert_eq!(tui.get_value("voice.model"), Some(&json!("fast-small")));
+    assert_eq!(checks(), "");
+
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    tui.handle_event(&key(KeyCode::Char('x'))).unwrap();
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while tui.get_value("voice.model") != Some(&json!("fastx-small")) {
+        assert!(Instant::now() < deadline, "model was not reset");
+        std::thread::sleep(Duration::from_millis(5));
+        tui.tick();
+    }
+    assert_eq!(checks(), "fastx\n");
+}
+
+/// Text of the whole screen after drawing the editor
+fn screen(tui: &mut SchemaTUI) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    terminal
+        .draw(|frame| tui.render(frame, frame.area()))
+        .unwrap();
+    let buffer = terminal.backend().buffer().clone();
+    buffer.content().iter().map(|cell| cell.symbol()).collect()
+}
+
+#[test]
+fn test_secret_command_only_runs_on_reveal() {
+    let dir = tempfile::tempdir().unwrap();
+    let calls = dir.path().join("calls");
+    let get = format!("echo x >> '{}'; echo hunter2", calls.display());
+    let schema = SchemaParser::from_string(&format!(
+        r#"{{
+            "title": "Test",
+            "version": "1.0",
+            "sections": [{{
+                "id": "api",
+                "title": "API",
+                "fields": [
+                    {{"id": "token", "label": "Token", "description": "API token", "type": "secret",
+                     "storage": {{"type": "command", "get": {}}}}}
+                ]
+            }}]
+        }}"#,
+        json!(get)
+    ))
+    .unwrap();
+    let reference = format!("secret:cmd:{}", get);
+    let mut tui = SchemaTUIBuilder::new()
+        .schema(schema)
+        .initial_values(HashMap::from([("api.token".to_string(), json!(reference))]))
+        .build()
+        .unwrap();
+    let ctrl_r = Event::Key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+
+    // Opening and confirming the editor leaves the stored secret alone
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert!(tui.is_editing());
+    assert!(screen(&mut tui).contains("(command)"));
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert!(!calls.exists());
+
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    tui.handle_event(&ctrl_r).unwrap();
+    assert!(screen(&mut tui).contains("hunter2"));
+    assert_eq!(std::fs::read_to_string(&calls).unwrap(), "x\n");
+
+    // Once editing stops the plaintext is gone, and reopening doesn't fetch it
+    tui.handle_event(&key(KeyCode::Esc)).unwrap();
+    assert!(!screen(&mut tui).contains("hunter2"));
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert!(!screen(&mut tui).contains("hunter2"));
+    assert_eq!(std::fs::read_to_string(&calls).unwrap(), "x\n");
+    assert_eq!(tui.get_value("api.token"), Some(&json!(reference)));
+}
+
+#[test]
+fn test_slider_and_multiline_fields_open_for_editing() {
+    let schema = SchemaParser::from_string(
+        r#"{
+            "title": "Test",
+            "version": "1.0",
+            "sections": [{
+                "id": "general",
+                "title": "General",
+                "fields": [
+                    {"id": "volume", "label": "Volume", "description": "Volume", "type": "number", "min": 0, "max": 10, "default": 5, "ui_widget": "slider"},
+                    {"id": "notes", "label": "Notes", "description": "Notes", "type": "string", "ui_widget": "multiline"}
+                ]
+            }]
+        }"#,
+    )
+    .unwrap();
+    let mut tui = build(schema);
+
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert!(tui.is_editing());
+    tui.handle_event(&key(KeyCode::Right)).unwrap();
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert!(!tui.is_editing());
+    assert_eq!(tui.get_value("general.volume"), Some(&json!(6)));
+
+    tui.handle_event(&key(KeyCode::Char('j'))).unwrap();
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    for code in [KeyCode::Char('a'), KeyCode::Enter, KeyCode::Char('b')] {
+        tui.handle_event(&key(code)).unwrap();
+    }
+    let ctrl_s = Event::Key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+    tui.handle_event(&ctrl_s).unwrap();
+    assert!(!tui.is_editing());
+    assert_eq!(tui.get_value("general.notes"), Some(&json!("a\nb")));
+}
+
+#[test]
+fn test_rebound_confirm_key_replaces_enter() {
+    let schema = SchemaParser::from_string(
+        r#"{
+            "title": "Test",
+            "version": "1.0",
+            "sections": [{
+                "id": "general",
+                "title": "General",
+                "fields": [
+                    {"id": "name", "label": "Name", "description": "Your name", "type": "string"},
+                    {"id": "notes", "label": "Notes", "description": "Notes", "type": "string", "ui_widget": "multiline"}
+                ]
+            }]
+        }"#,
+    )
+    .unwrap();
+    let mut tui = SchemaTUIBuilder::new()
+        .schema(schema)
+        .initial_values(HashMap::new())
+        .keymap(Keymap::vim().bind("edit.confirm", &["ctrl-j"]).unwrap())
+        .build()
+        .unwrap();
+    let ctrl_j = Event::Key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL));
+
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    tui.handle_event(&key(KeyCode::Char('x'))).unwrap();
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert!(tui.is_editing());
+    tui.handle_event(&ctrl_j).unwrap();
+    assert!(!tui.is_editing());
+    assert_eq!(tui.get_value("general.name"), Some(&json!("x")));
+
+    // Enter stays a newline in the multi-line editor; the bound key confirms
+    tui.handle_event(&key(KeyCode::Char('j'))).unwrap();
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    for code in [KeyCode::Char('a'), KeyCode::Enter, KeyCode::Char('b')] {
+        tui.handle_event(&key(code)).unwrap();
+    }
+    assert!(tui.is_editing());
+    tui.handle_event(&ctrl_j).unwrap();
+    assert!(!tui.is_editing());
+    assert_eq!(tui.get_value("general.notes"), Some(&json!("a\nb")));
+}
+
+#[test]
+fn test_array_field_edits_one_item_per_line() {
+    let schema = SchemaParser::from_string(
+        r#"{
+            "title": "Test",
+            "version": "1.0",
+            "sections": [{
+                "id": "network",
+                "title": "Network",
+                "fields": [
+                    {"id": "ports", "label": "Ports", "description": "Ports", "type": "array", "items": "number", "default": [80]}
+                ]
+            }]
+        }"#,
+    )
+    .unwrap();
+    let mut tui = build(schema);
+    assert!(screen(&mut tui).contains("80"));
+    let ctrl_s = Event::Key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    for code in [
+        KeyCode::End,
+        KeyCode::Enter,
+        KeyCode::Char('8'),
+        KeyCode::Char('1'),
+    ] {
+        tui.handle_event(&key(code)).unwrap();
+    }
+    tui.handle_event(&ctrl_s).unwrap();
+    assert!(!tui.is_editing());
+    assert_eq!(tui.get_value("network.ports"), Some(&json!([80, 81])));
+    assert!(screen(&mut tui).contains("80, 81"));
+
+    // Items that don't parse discard the edit
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    for code in [KeyCode::Enter, KeyCode::Char('x')] {
+        tui.handle_event(&key(code)).unwrap();
+    }
+    tui.handle_event(&ctrl_s).unwrap();
+    assert_eq!(tui.get_value("network.ports"), Some(&json!([80, 81])));
+    assert!(tui.status_message().is_some_and(|m| m.contains("Item 3")));
+}
+
+#[test]
+fn test_reset_writes_defaults_to_the_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    let written = || std::fs::read_to_string(&path).unwrap();
+    let markers = |tui: &mut SchemaTUI| screen(tui).matches('●').count();
+    std::fs::write(
+        &path,
+        "[general]\nenabled = true\nname = \"ada\"\n\n[display]\nsize = 14\n",
+    )
+    .unwrap();
+    let mut tui = SchemaTUIBuilder::new()
+        .schema(test_schema(None))
+        .config_file(&path)
+        .unwrap()
+        .keymap(Keymap::vim().bind("app.reset_all", &["X"]).unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(markers(&mut tui), 2);
+
+    // Enter cancels the confirmation
+    tui.handle_event(&key(KeyCode::Char('r'))).unwrap();
+    assert!(tui.has_dialog());
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert!(!tui.has_dialog());
+    assert_eq!(tui.get_value("general.enabled"), Some(&json!(true)));
+    assert!(written().contains("enabled = true"));
+
+    // Field
+    tui.handle_event(&key(KeyCode::Char('r'))).unwrap();
+    tui.handle_event(&key(KeyCode::Char('y'))).unwrap();
+    assert_eq!(tui.get_value("general.enabled"), Some(&json!(false)));
+    assert!(written().contains("enabled = false"));
+    assert_eq!(markers(&mut tui), 1);
+
+    // Section: a field without a default is unset
+    tui.handle_event(&key(KeyCode::Char('R'))).unwrap();
+    tui.handle_event(&key(KeyCode::Char('y'))).unwrap();
+    assert_eq!(tui.get_value("general.name"), None);
+    assert!(!written().contains("name ="));
+    assert!(written().contains("size = 14"));
+    assert_eq!(markers(&mut tui), 0);
+
+    // All
+    tui.handle_event(&key(KeyCode::Char('X'))).unwrap();
+    tui.handle_event(&key(KeyCode::Char('y'))).unwrap();
+    assert_eq!(tui.get_value("display.size"), Some(&json!(12)));
+    assert!(written().contains("size = 12"));
+    tui.handle_event(&key(KeyCode::Char('X'))).unwrap();
+    assert!(!tui.has_dialog());
+}
+
+#[test]
+fn test_reset_with_omit_defaults_removes_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[general]\nenabled = true\n\n[display]\nsize = 14\n").unwrap();
+    let mut tui = SchemaTUIBuilder::new()
+        .schema(test_schema(None))
+        .config_file(&path)
+        .unwrap()
+        .omit_defaults()
+        .build()
+        .unwrap();
+
+    tui.handle_event(&key(KeyCode::Char('r'))).unwrap();
+    tui.handle_event(&key(KeyCode::Char('y'))).unwrap();
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(!written.contains("enabled ="));
+    assert!(written.contains("size = 14"));
+}
+
+#[test]
+fn test_popups_stay_inside_the_pane() {
+    let schema = SchemaParser::from_string(
+        r#"{
+            "title": "Test",
+            "version": "1.0",
+            "sections": [{
+                "id": "general",
+                "title": "General",
+                "fields": [
+                    {"id": "city", "label": "City", "description": "City", "type": "enum", "options_source": {"type": "static", "values": ["Lisbon", "Oslo"]}, "ui_widget": "dropdown_searchable"},
+                    {"id": "notes", "label": "Notes", "description": "Notes", "type": "string", "ui_widget": "multiline"}
+                ]
+            }]
+        }"#,
+    )
+    .unwrap();
+    let mut tui = build(schema);
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    let pane = Rect::new(0, 0, 40, 20);
+    let mut outside_is_blank = |tui: &mut SchemaTUI| {
+        terminal.draw(|frame| tui.render(frame, pane)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..30).all(|y| {
+            (0..100)
+                .filter(|&x| x >= 40 || y >= 20)
+                .all(|x| buffer[(x, y)].symbol() == " ")
+        })
+    };
+
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert!(tui.is_editing());
+    assert!(outside_is_blank(&mut tui));
+
+    tui.handle_event(&key(KeyCode::Esc)).unwrap();
+    tui.handle_event(&key(KeyCode::Char('j'))).unwrap();
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    for _ in 0..30 {
+        tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    }
+    assert!(tui.is_editing());
+    assert!(outside_is_blank(&mut tui));
+}
+
+#[test]
+fn test_before_change_runs_on_confirm_not_per_keystroke() {
+    let mut tui = editor();
+    let checked = Arc::new(Mutex::new(Vec::new()));
+    let seen = checked.clone();
+    tui.before_change(move |key, value| {
+        seen.lock().unwrap().push(value.clone());
+        match (key, value.as_str()) {
+            ("general.name", Some("root")) => BeforeChange::Reject("reserved".to_string()),
+            _ => BeforeChange::Accept,
+        }
+    });
+    let type_name = |tui: &mut SchemaTUI, name: &str| {
+        tui.handle_event(&key(KeyCode::Enter)).unwrap();
+        for c in name.chars() {
+            tui.handle_event(&key(KeyCode::Char(c))).unwrap();
+        }
+    };
+    tui.handle_event(&key(KeyCode::Char('j'))).unwrap();
+
+    // Typing shows the value live; the hook sees only the confirmed one
+    type_name(&mut tui, "ada");
+    assert_eq!(tui.get_value("general.name"), Some(&json!("ada")));
+    assert!(checked.lock().unwrap().is_empty());
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert_eq!(*checked.lock().unwrap(), vec![json!("ada")]);
+
+    // A rejected or cancelled edit puts the previous value back
+    type_name(&mut tui, "");
+    for _ in 0..3 {
+        tui.handle_event(&key(KeyCode::Backspace)).unwrap();
+    }
+    for c in "root".chars() {
+        tui.handle_event(&key(KeyCode::Char(c))).unwrap();
+    }
+    tui.handle_event(&key(KeyCode::Enter)).unwrap();
+    assert_eq!(tui.get_value("general.name"), Some(&json!("ada")));
+    assert_eq!(
+        tui.status_message(),
+        Some("general.name rejected: reserved")
+    );
+
+    type_name(&mut tui, "ne");
+    assert_eq!(tui.get_value("general.name"), Some(&json!("adane")));
+    tui.handle_event(&key(KeyCode::Esc)).unwrap();
+    assert_eq!(tui.get_value("general.name"), Some(&json!("ada")));
+    assert_eq!(checked.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn test_change_effects_wait_for_a_successful_save() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[general]\nname = \"ada\"\n").unwrap();
+    let mut tui = SchemaTUIBuilder::new()
+        .schema(test_schema(None))
+        .config_file(&path)
+        .unwrap()
+        .build()
+        .unwrap();
+    let applied = Arc::new(Mutex::new(Vec::new()));
+    let log = applied.clone();
+    tui.after_change(move |key, _| {
+        log.lock().unwrap().push(key.to_string());
+        AfterChange::Ok
+    });
+    let commands = tui.command_sender();
+
+    // A directory in place of the file can't be written, even by root
+    std::fs::remove_file(&path).unwrap();
+    std::fs::create_dir(&path).unwrap();
+    commands.send(set("general.name", json!("grace"))).unwrap();
+    tui.tick();
+    assert!(tui.status_message().unwrap().starts_with("Failed to save"));
+    assert!(applied.lock().unwrap().is_empty());
+
+    std::fs::remove_dir(&path).unwrap();
+    commands.send(set("display.size", json!(14))).unwrap();
+    tui.tick();
+    assert!(std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("name = \"grace\""));
+    assert_eq!(
+        *applied.lock().unwrap(),
+        vec!["general.name".to_string(), "display.size".to_string()]
+    );
+}
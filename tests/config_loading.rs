@@ -1,4 +1,5 @@
-use schema_tui::config::{expand_env_vars, ConfigLoader, ConfigStore};
+use schema_tui::config::{expand_env_vars, ConfigLoader, ConfigSaver, ConfigStore};
+use schema_tui::schema::SchemaParser;
 use std::env;
 use std::io::Write;
 use tempfile::NamedTempFile;
@@ -75,3 +76,231 @@ fn test_config_from_file() {
 
     assert_eq!(value.as_str().unwrap(), "value");
 }
+
+#[test]
+fn test_config_with_secret_references() {
+    let dir = tempfile::tempdir().unwrap();
+    let secrets_path = dir.path().join("secrets.toml");
+    std::fs::write(&secrets_path, "[api]\ntoken = \"abc123\"\n").unwrap();
+
+    let marker = dir.path().join("ran");
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            "[api]\ntoken = \"secret:file:{}#api.token\"\nuser = \"me\"\n\
+             note = \"secret:cmd:touch {marker}\"\nextra = \"secret:cmd:touch {marker}\"\n",
+            secrets_path.display(),
+            marker = marker.display()
+        ),
+    )
+    .unwrap();
+
+    let schema = SchemaParser::from_string(&format!(
+        r#"{{
+            "version": "1.0",
+            "sections": [
+                {{
+                    "id": "api",
+                    "title": "API",
+                    "fields": [
+                        {{ "id": "token", "label": "Token", "description": "Token", "type": "secret",
+                           "storage": {{ "type": "file", "path": "{}" }} }},
+                        {{ "id": "user", "label": "User", "description": "User", "type": "string" }},
+                        {{ "id": "note", "label": "Note", "description": "Note", "type": "string" }}
+                    ]
+                }}
+            ]
+        }}"#,
+        secrets_path.display()
+    ))
+    .unwrap();
+
+    let raw = ConfigLoader::from_toml_file(&config_path).unwrap();
+    assert!(raw
+        .get_nested("api.token")
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .starts_with("secret:file:"));
+
+    let resolved = ConfigLoader::from_toml_file_with_secrets(&config_path, &schema).unwrap();
+    assert_eq!(
+        resolved.get_nested("api.token").unwrap().as_str().unwrap(),
+        "abc123"
+    );
+    assert_eq!(
+        resolved.get_nested("api.user").unwrap().as_str().unwrap(),
+        "me"
+    );
+
+    // References outside the schema's secret fields are left alone
+    assert!(resolved
+        .get_nested("api.note")
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .starts_with("secret:cmd:"));
+    assert!(resolved
+        .get_nested("api.extra")
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .starts_with("secret:cmd:"));
+    assert!(!marker.exists());
+}
+
+#[test]
+fn test_save_multiline_and_escaped_strings() {
+    let schema = SchemaParser::from_string(
+        r#"{
+            "version": "1.0",
+            "sections": [
+                {
+                    "id": "hooks",
+                    "title": "Hooks",
+                    "fields": [
+                        { "id": "script", "label": "Script", "description": "Script", "type": "string", "ui_widget": "multiline" },
+                        { "id": "pattern", "label": "Pattern", "description": "Pattern", "type": "string" }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let script = "#!/bin/sh\necho \"a\\tb\"\n\tprintf '%s\\n' \"\"\"\n";
+    let pattern = r#"C:\path\"quoted"\n"#;
+
+    let mut store = ConfigStore::new();
+    store.set_nested("hooks.script", serde_json::json!(script));
+    store.set_nested("hooks.pattern", serde_json::json!(pattern));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    ConfigSaver::save_toml(&store, &schema, &path).unwrap();
+
+    let reloaded = ConfigLoader::from_toml_file_with_expansion(&path, false).unwrap();
+    assert_eq!(
+        reloaded
+            .get_nested("hooks.script")
+            .unwrap()
+            .as_str()
+            .unwrap(),
+        script
+    );
+    assert_eq!(
+        reloaded
+            .get_nested("hooks.pattern")
+            .unwrap()
+            .as_str()
+            .unwrap(),
+        pattern
+    );
+}
+
+#[test]
+fn test_save_round_trip_is_type_correct() {
+    let schema = SchemaParser::from_string(
+        r#"{
+            "version": "1.0",
+            "sections": [
+                {
+                    "id": "ui",
+                    "title": "UI",
+                    "fields": [
+                        { "id": "opacity", "label": "Opacity", "description": "Opacity", "type": "float" },
+                        { "id": "scale", "label": "Scale", "description": "Scale", "type": "float" },
+                        { "id": "gap", "label": "Gap", "description": "Gap", "type": "number" },
+                        { "id": "path", "label": "Path", "description": "Path", "type": "path" },
+                        { "id": "title", "label": "Title", "description": "Title", "type": "string" },
+                        { "id": "unset", "label": "Unset", "description": "Unset", "type": "number" }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let mut store = ConfigStore::new();
+    store.set_nested("ui.opacity", serde_json::json!(1.0));
+    store.set_nested("ui.scale", serde_json::json!(2));
+    store.set_nested("ui.gap", serde_json::json!(8));
+    store.set_nested("ui.path", serde_json::json!(r"C:\Users\me"));
+    store.set_nested("ui.title", serde_json::json!("tab\there \u{1b}[1m'bold'"));
+    store.set_nested("ui.unset", serde_json::Value::Null);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    ConfigSaver::save_toml(&store, &schema, &path).unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(content.contains("opacity = 1.0\n"));
+    assert!(content.contains("scale = 2.0\n"));
+    assert!(content.contains(r"path = 'C:\Users\me'"));
+    assert!(!content.contains("null"));
+    assert!(!content.contains("unset ="));
+
+    let reloaded = ConfigLoader::from_toml_file_with_expansion(&path, false).unwrap();
+    assert!(reloaded.get_nested("ui.opacity").unwrap().is_f64());
+    assert_eq!(reloaded.get_nested("ui.scale").unwrap().as_f64(), Some(2.0));
+    assert_eq!(reloaded.get_nested("ui.gap").unwrap().as_i64(), Some(8));
+    assert_eq!(
+        reloaded.get_nested("ui.path").unwrap().as_str(),
+        Some(r"C:\Users\me")
+    );
+    assert_eq!(
+        reloaded.get_nested("ui.title").unwrap().as_str(),
+        Some("tab\there \u{1b}[1m'bold'")
+    );
+    assert!(reloaded.get_nested("ui.unset").is_none());
+
+    // Saving what was loaded reproduces the file exactly
+    ConfigSaver::save_toml(&reloaded, &schema, &path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+}
+
+#[test]
+fn test_multiline_strings_round_trip() {
+    let schema = SchemaParser::from_string(
+        r#"{
+            "version": "1.0",
+            "sections": [
+                {
+                    "id": "hooks",
+                    "title": "Hooks",
+                    "fields": [
+                        { "id": "script", "label": "Script", "description": "Script", "type": "string", "ui_widget": "multiline" }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let cases = [
+        // A trailing backslash would otherwise continue the line
+        "line one \\\nline two\\",
+        "ends in a quote\n\"",
+        "quotes \"\"\"\" and \"\"\"\"\"\nin a row\"\"\"",
+        "\nleading newline",
+        "crlf\r\nand\ttab\\n literal",
+        "\\\"\"\"\\\n\"\"\"\\",
+    ];
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    for script in cases {
+        let mut store = ConfigStore::new();
+        store.set_nested("hooks.script", serde_json::json!(script));
+        ConfigSaver::save_toml(&store, &schema, &path).unwrap();
+
+        let reloaded = ConfigLoader::from_toml_file_with_expansion(&path, false).unwrap();
+        assert_eq!(
+            reloaded.get_nested("hooks.script").and_then(|v| v.as_str()),
+            Some(script),
+            "{}",
+            std::fs::read_to_string(&path).unwrap()
+        );
+    }
+}
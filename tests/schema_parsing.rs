@@ -1,4 +1,7 @@
-use schema_tui::schema::{FieldType, OptionSource, SchemaParser};
+use schema_tui::schema::{
+    ActionKind, ArrayItems, ColorFormat, DependencyChangePolicy, DurationUnit, FieldType,
+    OptionSource, SchemaParser, SchemaValidator, SecretStorage,
+};
 
 #[test]
 fn test_parse_basic_schema() {
@@ -79,6 +82,7 @@ fn test_parse_enum_field() {
         FieldType::Enum {
             options_source,
             default,
+            ..
         } => {
             match options_source {
                 OptionSource::Static { values } => {
@@ -126,7 +130,7 @@ fn test_parse_path_field() {
             ..
         } => {
             assert_eq!(default.as_ref().unwrap(), "~/config.toml");
-            assert_eq!(*must_exist, false);
+            assert!(!*must_exist);
         }
         _ => panic!("Expected path field type"),
     }
@@ -141,3 +145,340 @@ fn test_invalid_schema() {
     let result = SchemaParser::from_string(invalid_json);
     assert!(result.is_err());
 }
+
+#[test]
+fn test_parse_option_source_dependencies() {
+    let schema_json = r#"{
+        "version": "1.0",
+        "sections": [
+            {
+                "id": "theme",
+                "title": "Theme",
+                "fields": [
+                    {
+                        "id": "variant",
+                        "label": "Variant",
+                        "description": "Light or dark",
+                        "type": "enum",
+                        "options_source": { "type": "static", "values": ["light", "dark"] }
+                    },
+                    {
+                        "id": "name",
+                        "label": "Theme",
+                        "description": "Theme file",
+                        "type": "enum",
+                        "on_dependency_change": "reset",
+                        "options_source": {
+                            "type": "file_list",
+                            "directory": "~/.themes/${theme.variant}",
+                            "pattern": "*.toml",
+                            "depends_on": ["theme.variant"]
+                        }
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    let schema = SchemaParser::from_string(schema_json).unwrap();
+
+    match &schema.sections[0].fields[0].field_type {
+        FieldType::Enum {
+            options_source,
+            on_dependency_change,
+            ..
+        } => {
+            assert!(options_source.depends_on().is_empty());
+            assert_eq!(*on_dependency_change, DependencyChangePolicy::Flag);
+        }
+        _ => panic!("Expected enum field type"),
+    }
+
+    match &schema.sections[0].fields[1].field_type {
+        FieldType::Enum {
+            options_source,
+            on_dependency_change,
+            ..
+        } => {
+            assert_eq!(options_source.depends_on(), ["theme.variant"]);
+            assert_eq!(*on_dependency_change, DependencyChangePolicy::Reset);
+        }
+        _ => panic!("Expected enum field type"),
+    }
+}
+
+#[test]
+fn test_parse_secret_field() {
+    let schema_json = r#"{
+        "version": "1.0",
+        "sections": [
+            {
+                "id": "api",
+                "title": "API",
+                "fields": [
+                    {
+                        "id": "token",
+                        "label": "Token",
+                        "description": "API token",
+                        "type": "secret",
+                        "storage": { "type": "command", "get": "pass show app/token", "set": "pass insert -m -f app/token" }
+                    },
+                    {
+                        "id": "inline",
+                        "label": "Inline",
+                        "description": "Inline secret",
+                        "type": "secret"
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    let schema = SchemaParser::from_string(schema_json).unwrap();
+
+    match &schema.sections[0].fields[0].field_type {
+        FieldType::Secret {
+            storage: SecretStorage::Command { get, set },
+            ..
+        } => {
+            assert_eq!(get, "pass show app/token");
+            assert_eq!(set.as_deref(), Some("pass insert -m -f app/token"));
+        }
+        _ => panic!("Expected secret field with command storage"),
+    }
+
+    assert!(matches!(
+        schema.sections[0].fields[1].field_type,
+        FieldType::Secret {
+            storage: SecretStorage::Inline,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_parse_color_field() {
+    let schema_json = r##"{
+        "version": "1.0",
+        "sections": [
+            {
+                "id": "theme",
+                "title": "Theme",
+                "fields": [
+                    {
+                        "id": "accent",
+                        "label": "Accent",
+                        "description": "Accent color",
+                        "type": "color",
+                        "default": "#89b4fa",
+                        "format": "hex_alpha",
+                        "palette": { "type": "static", "values": ["#f38ba8", "#a6e3a1"] }
+                    }
+                ]
+            }
+        ]
+    }"##;
+
+    let schema = SchemaParser::from_string(schema_json).unwrap();
+    let field_type = &schema.sections[0].fields[0].field_type;
+
+    match field_type {
+        FieldType::Color {
+            default,
+            format,
+            palette,
+        } => {
+            assert_eq!(default.as_deref(), Some("#89b4fa"));
+            assert_eq!(*format, ColorFormat::HexAlpha);
+            assert!(matches!(palette, Some(OptionSource::Static { .. })));
+        }
+        _ => panic!("Expected color field"),
+    }
+
+    let normalized =
+        SchemaValidator::normalize_value(field_type, &serde_json::json!("rgb(137, 180, 250)"))
+            .unwrap();
+    assert_eq!(normalized, serde_json::json!("#89b4faff"));
+    assert!(SchemaValidator::validate_value(field_type, &serde_json::json!("nope")).is_err());
+}
+
+#[test]
+fn test_parse_unit_fields() {
+    let schema_json = r#"{
+        "version": "1.0",
+        "sections": [
+            {
+                "id": "cache",
+                "title": "Cache",
+                "fields": [
+                    {
+                        "id": "timeout",
+                        "label": "Timeout",
+                        "description": "Request timeout",
+                        "type": "duration",
+                        "default": "30s",
+                        "min": "1s",
+                        "max": "5m"
+                    },
+                    {
+                        "id": "size",
+                        "label": "Size",
+                        "description": "Cache size",
+                        "type": "byte_size",
+                        "store_as": "string"
+                    },
+                    {
+                        "id": "width",
+                        "label": "Width",
+                        "description": "Panel width",
+                        "type": "number",
+                        "unit": "px"
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    let schema = SchemaParser::from_string(schema_json).unwrap();
+    let fields = &schema.sections[0].fields;
+
+    let timeout = &fields[0].field_type;
+    assert!(matches!(
+        timeout,
+        FieldType::Duration {
+            unit: DurationUnit::Seconds,
+            ..
+        }
+    ));
+    assert_eq!(
+        SchemaValidator::normalize_value(timeout, &serde_json::json!("1m30s")).unwrap(),
+        serde_json::json!(90)
+    );
+    assert!(SchemaValidator::validate_value(timeout, &serde_json::json!("500ms")).is_err());
+    assert!(SchemaValidator::validate_value(timeout, &serde_json::json!(600)).is_err());
+
+    assert_eq!(
+        SchemaValidator::normalize_value(&fields[1].field_type, &serde_json::json!("512M"))
+            .unwrap(),
+        serde_json::json!("512MiB")
+    );
+
+    match &fields[2].field_type {
+        FieldType::Number { unit, .. } => assert_eq!(unit.as_deref(), Some("px")),
+        _ => panic!("Expected number field"),
+    }
+}
+
+#[test]
+fn test_parse_field_actions() {
+    let schema_json = r#"{
+        "version": "1.0",
+        "sections": [
+            {
+                "id": "display",
+                "title": "Display",
+                "actions": [
+                    { "key": "ctrl-t", "label": "Test", "type": "run", "command": "notify-send \"$CURRENT_VALUE\"" }
+                ],
+                "fields": [
+                    {
+                        "id": "scale",
+                        "label": "Scale",
+                        "description": "UI scale",
+                        "type": "float",
+                        "keybind": "S",
+                        "actions": [
+                            { "key": "a", "label": "Detect", "type": "command", "command": "echo 1.5" },
+                            { "key": "e", "label": "Edit", "type": "editor" }
+                        ]
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    let schema = SchemaParser::from_string(schema_json).unwrap();
+    let section = &schema.sections[0];
+    assert!(matches!(section.actions[0].kind, ActionKind::Run { .. }));
+
+    let field = &section.fields[0];
+    assert_eq!(field.keybind.as_deref(), Some("S"));
+    assert_eq!(field.actions[0].label, "Detect");
+    assert!(
+        matches!(&field.actions[0].kind, ActionKind::Command { command } if command == "echo 1.5")
+    );
+    assert!(matches!(
+        field.actions[1].kind,
+        ActionKind::Editor { extension: None }
+    ));
+
+    // Command output is parsed into the field's type
+    assert_eq!(
+        SchemaValidator::parse_text(&field.field_type, "1.5\n").unwrap(),
+        serde_json::json!(1.5)
+    );
+    assert!(SchemaValidator::parse_text(&field.field_type, "big").is_err());
+}
+
+#[test]
+fn test_parse_array_field() {
+    let schema_json = r#"{
+        "version": "1.0",
+        "sections": [
+            {
+                "id": "network",
+                "title": "Network",
+                "fields": [
+                    {
+                        "id": "ports",
+                        "label": "Ports",
+                        "description": "Listening ports",
+                        "type": "array",
+                        "items": "number",
+                        "default": [80, 443],
+                        "max_items": 3
+                    },
+                    {
+                        "id": "hosts",
+                        "label": "Hosts",
+                        "description": "Allowed hosts",
+                        "type": "array"
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    let schema = SchemaParser::from_string(schema_json).unwrap();
+    let ports = &schema.sections[0].fields[0].field_type;
+    match ports {
+        FieldType::Array { items, default, .. } => {
+            assert_eq!(*items, ArrayItems::Number);
+            assert_eq!(
+                default.as_deref(),
+                Some(&[serde_json::json!(80), serde_json::json!(443)][..])
+            );
+        }
+        _ => panic!("Expected array field"),
+    }
+    assert!(matches!(
+        schema.sections[0].fields[1].field_type,
+        FieldType::Array {
+            items: ArrayItems::String,
+            ..
+        }
+    ));
+
+    // Edited text has one item per line
+    assert_eq!(
+        SchemaValidator::parse_text(ports, "8080\n\n 9090 \n").unwrap(),
+        serde_json::json!([8080, 9090])
+    );
+    assert_eq!(
+        SchemaValidator::format_text(&serde_json::json!([8080, 9090])),
+        "8080\n9090"
+    );
+    assert!(SchemaValidator::parse_text(ports, "8080\nhttp").is_err());
+    assert!(SchemaValidator::parse_text(ports, "1\n2\n3\n4").is_err());
+    assert!(SchemaValidator::validate_value(ports, &serde_json::json!(8080)).is_err());
+}